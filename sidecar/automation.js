@@ -8,13 +8,14 @@
  *   node automation.js <interface> <text> [url]
  *   
  * Arguments:
- *   interface - AI interface name (chatgpt, claude, gemini, aistudio)
+ *   interface - AI interface name (chatgpt, claude, gemini, aistudio or a custom id)
  *   text - The prompt text to fill
- *   url - Optional custom URL (overrides default interface URL)
+ *   url - URL to open. The app always passes one from its interface registry; the URLs in
+ *         selectors.js are only a fallback for standalone runs. Required for custom ids
  */
 
 import { chromium } from 'playwright';
-import { getInterfaceConfig, getAvailableInterfaces } from './selectors.js';
+import { getInterfaceConfig, getAvailableInterfaces, GENERIC_INTERFACE } from './selectors.js';
 import { fileURLToPath } from 'url';
 import { dirname, join } from 'path';
 
@@ -25,10 +26,12 @@ const __dirname = dirname(__filename);
  * Parse command line arguments
  */
 function parseArgs() {
-  const args = process.argv.slice(2);
+  const rawArgs = process.argv.slice(2);
+  const profileArg = rawArgs.find((arg) => arg.startsWith('--profile-dir='));
+  const args = rawArgs.filter((arg) => !arg.startsWith('--profile-dir='));
   
   if (args.length < 2) {
-    console.error('Usage: node automation.js <interface> <text> [url]');
+    console.error('Usage: node automation.js [--profile-dir=<path>] <interface> <text> [url]');
     console.error(`Available interfaces: ${getAvailableInterfaces().join(', ')}`);
     process.exit(1);
   }
@@ -37,6 +40,7 @@ function parseArgs() {
     interface: args[0],
     text: args[1],
     url: args[2] || null,
+    profileDir: profileArg ? profileArg.slice('--profile-dir='.length) : null,
   };
 }
 
@@ -84,14 +88,15 @@ async function tryFillInput(page, selectors, text) {
  * @param {string} interfaceName - Name of the AI interface
  * @param {string} text - Prompt text to fill
  * @param {string|null} customUrl - Optional custom URL
+ * @param {string|null} profileDir - Optional browser profile directory
  */
-async function fillAndLeaveOpen(interfaceName, text, customUrl = null) {
+async function fillAndLeaveOpen(interfaceName, text, customUrl = null, profileDir = null) {
   console.log(`\n=== AI Context Collector - Browser Automation ===`);
   console.log(`Interface: ${interfaceName}`);
   console.log(`Prompt length: ${text.length} characters`);
   
-  // Get interface configuration
-  const config = getInterfaceConfig(interfaceName);
+  // Get interface configuration, falling back to generic selectors for custom interfaces
+  const config = getInterfaceConfig(interfaceName) || (customUrl ? GENERIC_INTERFACE : null);
   if (!config) {
     console.error(`✗ Unknown interface: ${interfaceName}`);
     console.error(`Available interfaces: ${getAvailableInterfaces().join(', ')}`);
@@ -101,8 +106,8 @@ async function fillAndLeaveOpen(interfaceName, text, customUrl = null) {
   const targetUrl = customUrl || config.url;
   console.log(`Target URL: ${targetUrl}`);
   
-  // Create browser data directory in a persistent location
-  const browserDataDir = join(__dirname, '.browser-data');
+  // Use the profile directory provided by the app, falling back to a local one for standalone runs
+  const browserDataDir = profileDir || join(__dirname, '.browser-data');
   console.log(`Browser data directory: ${browserDataDir}`);
   
   let context;
@@ -182,8 +187,8 @@ async function fillAndLeaveOpen(interfaceName, text, customUrl = null) {
  * Main entry point
  */
 async function main() {
-  const { interface: interfaceName, text, url } = parseArgs();
-  await fillAndLeaveOpen(interfaceName, text, url);
+  const { interface: interfaceName, text, url, profileDir } = parseArgs();
+  await fillAndLeaveOpen(interfaceName, text, url, profileDir);
 }
 
 // Run main function
@@ -4,6 +4,9 @@
  * Defines selectors for different AI chat interfaces to locate
  * the input field where the prompt should be filled.
  * 
+ * The app always passes the URL to open, taken from its interface registry;
+ * the URLs here are only used when the sidecar is run on its own.
+ *
  * NOTE: These URLs and selectors were last verified: January 2026
  * AI interfaces update their UIs frequently. If automation fails,
  * check the actual interface and update selectors accordingly.
@@ -61,6 +64,21 @@ export const AI_INTERFACES = {
   },
 };
 
+/**
+ * Generic configuration used for user-added interfaces that have no
+ * dedicated selectors. The URL is always supplied by the caller.
+ */
+export const GENERIC_INTERFACE = {
+  name: 'Custom',
+  url: null,
+  selectors: [
+    'textarea',
+    'div[contenteditable="true"][role="textbox"]',
+    '[contenteditable="true"]',
+  ],
+  waitForSelector: 'textarea, [contenteditable="true"]',
+};
+
 /**
  * Get selectors for a specific AI interface
  * @param {string} interfaceName - Name of the AI interface (chatgpt, claude, gemini, aistudio)
@@ -1,3 +1,3 @@
 pub mod text_cache;
 
-pub use text_cache::TextCache;
+pub use text_cache::{CacheStats, TextCache};
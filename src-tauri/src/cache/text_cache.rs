@@ -1,4 +1,5 @@
 use crate::error::AppResult;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -12,6 +13,14 @@ pub struct TextCache {
     current_size_bytes: u64,
 }
 
+/// Current usage of a `TextCache`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub size_bytes: u64,
+    pub max_size_bytes: u64,
+}
+
 #[derive(Debug, Clone)]
 struct CacheEntry {
     fingerprint: String,
@@ -57,6 +66,7 @@ impl TextCache {
                 let cache_file = self.cache_file_path(&cache_key);
                 if cache_file.exists() {
                     let text = fs::read_to_string(&cache_file)?;
+                    Self::touch(&cache_file);
                     log::debug!("Cache hit for: {}", path);
                     return Ok(Some(text));
                 }
@@ -76,8 +86,9 @@ impl TextCache {
         let cache_key = Self::cache_key(path);
         let cache_file = self.cache_file_path(&cache_key);
 
-        // Write to disk
+        // Write to disk, with the fingerprint beside it so it survives a restart
         fs::write(&cache_file, text)?;
+        fs::write(self.fingerprint_file_path(&cache_key), fingerprint)?;
 
         let size_bytes = text.len() as u64;
         let now = Self::current_timestamp();
@@ -105,16 +116,49 @@ impl TextCache {
         Ok(())
     }
 
-    /// Remove a cache entry
-    fn remove(&mut self, cache_key: &str) -> AppResult<()> {
-        if let Some(entry) = self.index.remove(cache_key) {
-            self.current_size_bytes -= entry.size_bytes;
-            let cache_file = self.cache_file_path(cache_key);
-            if cache_file.exists() {
-                fs::remove_file(cache_file)?;
-            }
+    /// Change the maximum cache size, evicting entries if the cache is now over the limit
+    pub fn set_max_size(&mut self, max_size_bytes: u64) -> AppResult<()> {
+        self.max_size_bytes = max_size_bytes;
+        self.evict_if_needed()
+    }
+
+    /// Directory the cached text is stored in
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+
+    /// Number of cached entries, the bytes they take and the size limit
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.index.len(),
+            size_bytes: self.current_size_bytes,
+            max_size_bytes: self.max_size_bytes,
         }
-        Ok(())
+    }
+
+    /// Drop the cached text for a file path so the next lookup re-extracts it
+    ///
+    /// Returns whether anything was cached for the path.
+    pub fn invalidate(&mut self, path: &str) -> AppResult<bool> {
+        self.remove(&Self::cache_key(path))
+    }
+
+    /// Remove a cache entry, returning whether it existed
+    fn remove(&mut self, cache_key: &str) -> AppResult<bool> {
+        let Some(entry) = self.index.remove(cache_key) else {
+            return Ok(false);
+        };
+
+        self.current_size_bytes -= entry.size_bytes;
+        let cache_file = self.cache_file_path(cache_key);
+        if cache_file.exists() {
+            fs::remove_file(cache_file)?;
+        }
+        let fingerprint_file = self.fingerprint_file_path(cache_key);
+        if fingerprint_file.exists() {
+            fs::remove_file(fingerprint_file)?;
+        }
+        Ok(true)
     }
 
     /// Evict least recently used entries until under max size
@@ -138,6 +182,10 @@ impl TextCache {
     }
 
     /// Rebuild cache index from disk
+    ///
+    /// A cache file's mtime is its last access, since `get` touches the file on every hit,
+    /// so the LRU order survives a restart. Fingerprints come from the `.fp` file `put`
+    /// writes beside each entry.
     fn rebuild_index(&mut self) -> AppResult<()> {
         if !self.cache_dir.exists() {
             return Ok(());
@@ -160,13 +208,20 @@ impl TextCache {
                         .map(|d| d.as_secs())
                         .unwrap_or(0);
 
-                    if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                        // We don't have fingerprint info on rebuild, so set empty
-                        // It will be invalidated on next access if fingerprint doesn't match
+                    // Keys are stored without the .txt that `cache_file_path` adds
+                    let cache_key = path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .and_then(|n| n.strip_suffix(".txt"));
+                    if let Some(cache_key) = cache_key {
+                        // Entries cached before fingerprints were stored get an empty one
+                        // and are invalidated on next access
+                        let fingerprint = fs::read_to_string(self.fingerprint_file_path(cache_key))
+                            .unwrap_or_default();
                         self.index.insert(
-                            file_name.to_string(),
+                            cache_key.to_string(),
                             CacheEntry {
-                                fingerprint: String::new(),
+                                fingerprint,
                                 size_bytes,
                                 last_accessed,
                             },
@@ -200,6 +255,22 @@ impl TextCache {
         self.cache_dir.join(format!("{}.txt", cache_key))
     }
 
+    /// Get the path of the file holding a cache entry's fingerprint
+    fn fingerprint_file_path(&self, cache_key: &str) -> PathBuf {
+        self.cache_dir.join(format!("{}.fp", cache_key))
+    }
+
+    /// Set a cache file's mtime to now, recording the access where `rebuild_index` reads it
+    fn touch(cache_file: &Path) {
+        let result = fs::File::options()
+            .write(true)
+            .open(cache_file)
+            .and_then(|file| file.set_modified(std::time::SystemTime::now()));
+        if let Err(e) = result {
+            log::warn!("Failed to record access to {:?}: {}", cache_file, e);
+        }
+    }
+
     /// Get current timestamp
     fn current_timestamp() -> u64 {
         std::time::SystemTime::now()
@@ -268,4 +339,91 @@ mod tests {
         // Clean up
         let _ = fs::remove_dir_all(&temp_dir);
     }
+
+    #[test]
+    fn test_cache_invalidate_path() {
+        let temp_dir = env::temp_dir().join("test_cache_invalidate");
+        let _ = fs::remove_dir_all(&temp_dir);
+
+        let mut cache = TextCache::new(temp_dir.clone()).unwrap();
+        cache.put("/test/a.txt", "fp", "aaaa").unwrap();
+        cache.put("/test/b.txt", "fp", "bbbb").unwrap();
+
+        assert!(cache.invalidate("/test/a.txt").unwrap());
+        assert!(!cache.invalidate("/test/a.txt").unwrap());
+        assert_eq!(cache.get("/test/a.txt", "fp").unwrap(), None);
+        assert_eq!(
+            cache.get("/test/b.txt", "fp").unwrap(),
+            Some("bbbb".to_string())
+        );
+        assert_eq!(cache.current_size_bytes, 4);
+
+        // Clean up
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_cache_set_max_size_evicts() {
+        let temp_dir = env::temp_dir().join("test_cache_resize");
+        let _ = fs::remove_dir_all(&temp_dir);
+
+        let mut cache = TextCache::new(temp_dir.clone()).unwrap();
+
+        cache.put("/test/a.txt", "fp", "aaaaaaaaaa").unwrap();
+        cache.put("/test/b.txt", "fp", "bbbbbbbbbb").unwrap();
+
+        // Shrinking below the current size evicts until it fits
+        cache.set_max_size(10).unwrap();
+        assert!(cache.current_size_bytes <= 10);
+        assert_eq!(cache.index.len(), 1);
+
+        // Clean up
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_cache_access_order_survives_rebuild() {
+        let temp_dir = env::temp_dir().join("test_cache_lru_rebuild");
+        let _ = fs::remove_dir_all(&temp_dir);
+
+        let mut cache = TextCache::new(temp_dir.clone()).unwrap();
+        cache.put("/test/hot.txt", "fp", "hothothot!").unwrap();
+        cache.put("/test/cold.txt", "fp", "coldcold!!").unwrap();
+
+        // Both were written long ago, the hot one the earliest
+        let written = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        for (path, age) in [("/test/hot.txt", 60), ("/test/cold.txt", 0)] {
+            let file = fs::File::options()
+                .write(true)
+                .open(cache.cache_file_path(&TextCache::cache_key(path)))
+                .unwrap();
+            file.set_modified(written - std::time::Duration::from_secs(age))
+                .unwrap();
+        }
+        assert!(cache.get("/test/hot.txt", "fp").unwrap().is_some());
+        drop(cache);
+
+        // Simulate a restart, then leave room for one entry
+        let mut cache = TextCache::new(temp_dir.clone()).unwrap();
+        assert_eq!(cache.index.len(), 2);
+        assert_eq!(cache.current_size_bytes, 20);
+        cache.set_max_size(10).unwrap();
+        assert!(cache
+            .index
+            .contains_key(&TextCache::cache_key("/test/hot.txt")));
+        assert!(!cache
+            .index
+            .contains_key(&TextCache::cache_key("/test/cold.txt")));
+
+        // The fingerprint was persisted, so the surviving entry is still a hit
+        assert_eq!(
+            cache.get("/test/hot.txt", "fp").unwrap(),
+            Some("hothothot!".to_string())
+        );
+        // The evicted entry left no files behind
+        assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 2);
+
+        // Clean up
+        let _ = fs::remove_dir_all(&temp_dir);
+    }
 }
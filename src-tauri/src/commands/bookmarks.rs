@@ -0,0 +1,171 @@
+use crate::db::DbConnection;
+use crate::error::{CommandError, CommandResult};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// A bookmarked file or folder from the index
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Bookmark {
+    pub path: String,
+    pub name: String,
+    pub is_dir: bool,
+    pub added_at: i64,
+}
+
+/// Internal function to bookmark an indexed path
+///
+/// Bookmarking an already bookmarked path keeps its original `added_at`.
+fn add_bookmark_internal(conn: &rusqlite::Connection, path: &str) -> CommandResult<()> {
+    let indexed = conn
+        .query_row("SELECT 1 FROM files WHERE path = ?", params![path], |_| {
+            Ok(())
+        })
+        .optional()?
+        .is_some();
+    if !indexed {
+        return Err(CommandError::new(
+            CommandError::NOT_FOUND,
+            format!("Path is not indexed: {}", path),
+        ));
+    }
+
+    let added_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+
+    conn.execute(
+        "INSERT OR IGNORE INTO bookmarks (path, added_at) VALUES (?1, ?2)",
+        params![path, added_at],
+    )?;
+    Ok(())
+}
+
+/// Internal function to remove a bookmark
+///
+/// Returns whether a bookmark was removed.
+fn remove_bookmark_internal(conn: &rusqlite::Connection, path: &str) -> rusqlite::Result<bool> {
+    let removed = conn.execute("DELETE FROM bookmarks WHERE path = ?", params![path])?;
+    Ok(removed > 0)
+}
+
+/// Internal function to list bookmarks, newest first
+///
+/// Bookmarks whose path is no longer in the index are left out.
+fn list_bookmarks_internal(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<Bookmark>> {
+    let mut stmt = conn.prepare(
+        "SELECT b.path, f.name, f.is_dir, b.added_at
+         FROM bookmarks b
+         JOIN files f ON f.path = b.path
+         ORDER BY b.added_at DESC, b.path",
+    )?;
+
+    let bookmarks = stmt
+        .query_map([], |row| {
+            Ok(Bookmark {
+                path: row.get(0)?,
+                name: row.get(1)?,
+                is_dir: row.get::<_, i32>(2)? != 0,
+                added_at: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(bookmarks)
+}
+
+/// Bookmark an indexed file or folder for quick re-selection
+#[tauri::command]
+pub async fn add_bookmark(path: String, db: tauri::State<'_, DbConnection>) -> CommandResult<()> {
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+    add_bookmark_internal(&conn, &path)
+}
+
+/// Remove a bookmark; removing a path that isn't bookmarked is not an error
+#[tauri::command]
+pub async fn remove_bookmark(
+    path: String,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<bool> {
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+    Ok(remove_bookmark_internal(&conn, &path)?)
+}
+
+/// List bookmarks that still point at indexed paths
+#[tauri::command]
+pub async fn list_bookmarks(db: tauri::State<'_, DbConnection>) -> CommandResult<Vec<Bookmark>> {
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+    Ok(list_bookmarks_internal(&conn)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::init_database;
+    use rusqlite::Connection;
+
+    fn setup_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_database(&conn).unwrap();
+        for (path, name, is_dir) in [
+            ("/project", "project", true),
+            ("/project/main.rs", "main.rs", false),
+            ("/project/lib.rs", "lib.rs", false),
+        ] {
+            conn.execute(
+                "INSERT INTO files (path, parent_path, name, is_dir) VALUES (?, NULL, ?, ?)",
+                params![path, name, is_dir as i32],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    #[test]
+    fn test_add_list_and_remove_bookmarks() {
+        let conn = setup_test_db();
+
+        add_bookmark_internal(&conn, "/project/main.rs").unwrap();
+        add_bookmark_internal(&conn, "/project").unwrap();
+        // Adding twice is a no-op
+        add_bookmark_internal(&conn, "/project/main.rs").unwrap();
+
+        let bookmarks = list_bookmarks_internal(&conn).unwrap();
+        let mut paths: Vec<&str> = bookmarks.iter().map(|b| b.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["/project", "/project/main.rs"]);
+        let dir = bookmarks.iter().find(|b| b.path == "/project").unwrap();
+        assert!(dir.is_dir);
+        assert_eq!(dir.name, "project");
+
+        assert!(remove_bookmark_internal(&conn, "/project").unwrap());
+        assert!(!remove_bookmark_internal(&conn, "/project").unwrap());
+
+        let bookmarks = list_bookmarks_internal(&conn).unwrap();
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!(bookmarks[0].path, "/project/main.rs");
+    }
+
+    #[test]
+    fn test_add_bookmark_requires_indexed_path() {
+        let conn = setup_test_db();
+
+        let err = add_bookmark_internal(&conn, "/project/missing.rs").unwrap_err();
+        assert_eq!(err.code, CommandError::NOT_FOUND);
+        assert!(list_bookmarks_internal(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_list_bookmarks_skips_deindexed_paths() {
+        let conn = setup_test_db();
+        add_bookmark_internal(&conn, "/project/main.rs").unwrap();
+        add_bookmark_internal(&conn, "/project/lib.rs").unwrap();
+
+        conn.execute("DELETE FROM files WHERE path = '/project/lib.rs'", [])
+            .unwrap();
+
+        let bookmarks = list_bookmarks_internal(&conn).unwrap();
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!(bookmarks[0].path, "/project/main.rs");
+    }
+}
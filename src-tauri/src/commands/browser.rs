@@ -1,9 +1,76 @@
-use crate::error::{AppError, AppResult};
+use super::settings::{get_setting_internal, load_settings_internal, save_setting_internal};
+use crate::db::DbConnection;
+use crate::error::{AppError, AppResult, CommandError, CommandResult};
 use log::{info, warn};
-use std::process::{Command, Stdio};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant};
 use tauri::Manager;
 
-/// Available AI chat interfaces
+/// Minimum Node.js major version required by the Playwright sidecar
+const MIN_NODE_MAJOR_VERSION: u32 = 18;
+
+/// Set once the prerequisite check has passed, so later launches skip it
+static PREREQUISITES_VERIFIED: AtomicBool = AtomicBool::new(false);
+
+/// Settings key holding user-added interfaces as a JSON array
+const CUSTOM_INTERFACES_KEY: &str = "custom_interfaces";
+
+/// Subdirectory of the app data directory used when no profile directory is configured
+const DEFAULT_PROFILES_SUBDIR: &str = "browser-profiles";
+
+/// How long to watch the sidecar for an early exit before reporting success
+const STARTUP_WINDOW: Duration = Duration::from_millis(1500);
+
+/// Information about a launched sidecar process, returned to the frontend
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BrowserLaunch {
+    pub pid: u32,
+    pub interface: String,
+    pub started_at: i64,
+}
+
+/// Tauri-managed state tracking the most recent sidecar launch per interface
+#[derive(Default)]
+pub struct BrowserProcesses {
+    launches: Mutex<HashMap<String, (BrowserLaunch, Option<Child>)>>,
+}
+
+impl BrowserProcesses {
+    /// Record a launch, replacing (and reaping) any previous one for the same interface
+    fn insert(&self, launch: BrowserLaunch, child: Option<Child>) -> AppResult<()> {
+        let mut launches = self
+            .launches
+            .lock()
+            .map_err(|e| AppError::Unknown(format!("Failed to lock browser processes: {}", e)))?;
+
+        if let Some((_, Some(mut previous))) =
+            launches.insert(launch.interface.clone(), (launch, child))
+        {
+            // Reap the previous sidecar if it already exited so it doesn't linger as a zombie
+            let _ = previous.try_wait();
+        }
+
+        Ok(())
+    }
+
+    /// Look up the most recent launch for an interface
+    fn get(&self, interface: &str) -> AppResult<Option<BrowserLaunch>> {
+        let launches = self
+            .launches
+            .lock()
+            .map_err(|e| AppError::Unknown(format!("Failed to lock browser processes: {}", e)))?;
+
+        Ok(launches.get(interface).map(|(launch, _)| launch.clone()))
+    }
+}
+
+/// Built-in AI chat interfaces
 #[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 #[serde(rename_all = "lowercase")]
 pub enum AiInterface {
@@ -14,6 +81,13 @@ pub enum AiInterface {
 }
 
 impl AiInterface {
+    const ALL: [AiInterface; 4] = [
+        AiInterface::ChatGPT,
+        AiInterface::Claude,
+        AiInterface::Gemini,
+        AiInterface::AIStudio,
+    ];
+
     fn as_str(&self) -> &str {
         match self {
             AiInterface::ChatGPT => "chatgpt",
@@ -22,6 +96,419 @@ impl AiInterface {
             AiInterface::AIStudio => "aistudio",
         }
     }
+
+    fn display_name(&self) -> &str {
+        match self {
+            AiInterface::ChatGPT => "ChatGPT",
+            AiInterface::Claude => "Claude",
+            AiInterface::Gemini => "Gemini",
+            AiInterface::AIStudio => "AI Studio",
+        }
+    }
+
+    fn default_url(&self) -> &str {
+        match self {
+            AiInterface::ChatGPT => "https://chat.openai.com/",
+            AiInterface::Claude => "https://claude.ai/",
+            AiInterface::Gemini => "https://gemini.google.com/",
+            AiInterface::AIStudio => "https://aistudio.google.com/",
+        }
+    }
+}
+
+/// An AI chat interface entry in the registry (built-in or user-added)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InterfaceDef {
+    pub id: String,
+    pub name: String,
+    pub default_url: String,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+impl From<&AiInterface> for InterfaceDef {
+    fn from(interface: &AiInterface) -> Self {
+        InterfaceDef {
+            id: interface.as_str().to_string(),
+            name: interface.display_name().to_string(),
+            default_url: interface.default_url().to_string(),
+            builtin: true,
+        }
+    }
+}
+
+/// Get the built-in interface table
+fn builtin_interfaces() -> Vec<InterfaceDef> {
+    AiInterface::ALL.iter().map(InterfaceDef::from).collect()
+}
+
+/// Load user-added interfaces from settings
+fn load_custom_interfaces(db: &DbConnection) -> Result<Vec<InterfaceDef>, String> {
+    let Some(json) = get_setting_internal(db, CUSTOM_INTERFACES_KEY)? else {
+        return Ok(Vec::new());
+    };
+
+    match serde_json::from_str::<Vec<InterfaceDef>>(&json) {
+        Ok(mut interfaces) => {
+            for interface in &mut interfaces {
+                interface.builtin = false;
+            }
+            Ok(interfaces)
+        }
+        Err(e) => {
+            warn!(
+                "Ignoring malformed {} setting: {}",
+                CUSTOM_INTERFACES_KEY, e
+            );
+            Ok(Vec::new())
+        }
+    }
+}
+
+/// Persist user-added interfaces to settings
+fn save_custom_interfaces(db: &DbConnection, interfaces: &[InterfaceDef]) -> Result<(), String> {
+    let json = serde_json::to_string(interfaces)
+        .map_err(|e| format!("Failed to serialize custom interfaces: {}", e))?;
+    save_setting_internal(db, CUSTOM_INTERFACES_KEY, &json)
+}
+
+/// Get the full interface registry: built-ins first, then user-added entries
+fn get_interface_registry(db: &DbConnection) -> Result<Vec<InterfaceDef>, String> {
+    let mut registry = builtin_interfaces();
+    registry.extend(load_custom_interfaces(db)?);
+    Ok(registry)
+}
+
+/// Look up an interface id in the registry
+fn resolve_interface(db: &DbConnection, id: &str) -> AppResult<InterfaceDef> {
+    let registry = get_interface_registry(db).map_err(AppError::BrowserError)?;
+    let id_lower = id.to_lowercase();
+
+    registry
+        .into_iter()
+        .find(|interface| interface.id == id_lower)
+        .ok_or_else(|| AppError::InvalidArgument(format!("Unknown AI interface: {}", id)))
+}
+
+/// URL the sidecar should open: `custom_url` once validated, else the interface's default
+///
+/// The URL is always passed explicitly, so the registry is the only place defaults live.
+fn launch_url(interface: &InterfaceDef, custom_url: Option<String>) -> AppResult<String> {
+    match custom_url {
+        Some(url) => {
+            let url = url.trim().to_string();
+            validate_http_url(&url).map_err(AppError::BrowserError)?;
+            Ok(url)
+        }
+        None => Ok(interface.default_url.clone()),
+    }
+}
+
+/// Interface ids are lowercase ASCII letters, digits, '-' and '_'
+fn is_valid_interface_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// Check that `url` is an absolute http(s) URL with a host
+///
+/// Anything else, such as `file:`, `javascript:` or a bare host name, is rejected rather than
+/// handed to the browser.
+fn validate_http_url(url: &str) -> Result<(), String> {
+    let lower = url.to_ascii_lowercase();
+    let Some(rest) = lower
+        .strip_prefix("https://")
+        .or_else(|| lower.strip_prefix("http://"))
+    else {
+        return Err(format!("URL must start with http:// or https://: {}", url));
+    };
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(format!("URL must not contain whitespace: {}", url));
+    }
+
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = authority.rsplit('@').next().unwrap_or("");
+    if host.is_empty() || host.starts_with(':') {
+        return Err(format!("URL has no host: {}", url));
+    }
+    Ok(())
+}
+
+/// Internal function to add a custom interface
+fn add_custom_interface_internal(
+    db: &DbConnection,
+    id: &str,
+    name: &str,
+    default_url: &str,
+) -> AppResult<InterfaceDef> {
+    if !is_valid_interface_id(id) {
+        return Err(AppError::InvalidArgument(format!(
+            "Invalid interface id '{}': use lowercase letters, digits, '-' or '_'",
+            id
+        )));
+    }
+    if name.trim().is_empty() {
+        return Err(AppError::InvalidArgument(
+            "Interface name cannot be empty".to_string(),
+        ));
+    }
+    validate_http_url(default_url)
+        .map_err(|e| AppError::InvalidArgument(format!("Invalid interface URL: {}", e)))?;
+
+    let registry = get_interface_registry(db).map_err(AppError::BrowserError)?;
+    if registry.iter().any(|interface| interface.id == id) {
+        return Err(AppError::InvalidArgument(format!(
+            "An interface with id '{}' already exists",
+            id
+        )));
+    }
+
+    let interface = InterfaceDef {
+        id: id.to_string(),
+        name: name.trim().to_string(),
+        default_url: default_url.to_string(),
+        builtin: false,
+    };
+
+    let mut custom = load_custom_interfaces(db).map_err(AppError::BrowserError)?;
+    custom.push(interface.clone());
+    save_custom_interfaces(db, &custom).map_err(AppError::BrowserError)?;
+
+    Ok(interface)
+}
+
+/// Internal function to remove a custom interface
+fn remove_custom_interface_internal(db: &DbConnection, id: &str) -> AppResult<()> {
+    if builtin_interfaces()
+        .iter()
+        .any(|interface| interface.id == id)
+    {
+        return Err(AppError::InvalidArgument(format!(
+            "Built-in interface '{}' cannot be removed",
+            id
+        )));
+    }
+
+    let mut custom = load_custom_interfaces(db).map_err(AppError::BrowserError)?;
+    let before = custom.len();
+    custom.retain(|interface| interface.id != id);
+
+    if custom.len() == before {
+        return Err(AppError::InvalidArgument(format!(
+            "Unknown AI interface: {}",
+            id
+        )));
+    }
+
+    save_custom_interfaces(db, &custom).map_err(AppError::BrowserError)
+}
+
+/// Result of checking whether browser automation can run on this system
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct BrowserPrerequisites {
+    pub node_available: bool,
+    pub node_version: Option<String>,
+    pub node_version_supported: bool,
+    pub min_node_version: String,
+    pub sidecar_found: bool,
+    pub sidecar_path: Option<String>,
+    pub errors: Vec<String>,
+}
+
+impl BrowserPrerequisites {
+    /// Whether every prerequisite is satisfied
+    pub fn is_ready(&self) -> bool {
+        self.node_available && self.node_version_supported && self.sidecar_found
+    }
+}
+
+/// Parse the output of `node --version` (e.g. "v20.11.1") into (major, minor, patch)
+fn parse_node_version(output: &str) -> Option<(u32, u32, u32)> {
+    let version = output.trim().trim_start_matches('v');
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().unwrap_or("0").parse().ok()?;
+    let patch = parts.next().unwrap_or("0").parse().ok()?;
+    Some((major, minor, patch))
+}
+
+/// Internal function to check Node.js and sidecar availability
+pub(crate) fn check_prerequisites_internal() -> BrowserPrerequisites {
+    let mut prerequisites = BrowserPrerequisites {
+        node_available: false,
+        node_version: None,
+        node_version_supported: false,
+        min_node_version: format!("{}.0.0", MIN_NODE_MAJOR_VERSION),
+        sidecar_found: false,
+        sidecar_path: None,
+        errors: Vec::new(),
+    };
+
+    match Command::new("node").arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            let raw_version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            prerequisites.node_available = true;
+
+            match parse_node_version(&raw_version) {
+                Some((major, _, _)) if major >= MIN_NODE_MAJOR_VERSION => {
+                    prerequisites.node_version_supported = true;
+                }
+                Some(_) => prerequisites.errors.push(format!(
+                    "Node.js {} is too old, version {} or newer is required",
+                    raw_version, prerequisites.min_node_version
+                )),
+                None => prerequisites
+                    .errors
+                    .push(format!("Could not parse Node.js version: {}", raw_version)),
+            }
+
+            prerequisites.node_version = Some(raw_version);
+        }
+        Ok(output) => prerequisites.errors.push(format!(
+            "Node.js exited with {} when checking its version",
+            output.status
+        )),
+        Err(e) => prerequisites.errors.push(format!(
+            "Node.js was not found on PATH ({}). Install Node.js {} or newer to use browser automation",
+            e, prerequisites.min_node_version
+        )),
+    }
+
+    match get_sidecar_path() {
+        Ok(path) => {
+            prerequisites.sidecar_found = path.exists();
+            if !prerequisites.sidecar_found {
+                prerequisites
+                    .errors
+                    .push(format!("Sidecar script not found at: {}", path.display()));
+            }
+            prerequisites.sidecar_path = Some(path.to_string_lossy().to_string());
+        }
+        Err(e) => prerequisites.errors.push(e.to_string()),
+    }
+
+    prerequisites
+}
+
+/// Check whether Node.js and the sidecar script are available
+///
+/// The frontend should call this before the first launch so it can show a
+/// helpful message instead of a raw spawn error. Node.js is run on a blocking thread.
+#[tauri::command]
+pub async fn check_browser_prerequisites() -> CommandResult<BrowserPrerequisites> {
+    tauri::async_runtime::spawn_blocking(check_prerequisites_internal)
+        .await
+        .map_err(|e| {
+            CommandError::new(
+                CommandError::BROWSER,
+                format!("Prerequisite check failed: {}", e),
+            )
+        })
+}
+
+/// Verify prerequisites once per session before spawning the sidecar
+fn ensure_prerequisites() -> AppResult<()> {
+    if PREREQUISITES_VERIFIED.load(Ordering::Relaxed) {
+        return Ok(());
+    }
+
+    let prerequisites = check_prerequisites_internal();
+    if !prerequisites.is_ready() {
+        return Err(AppError::BrowserError(prerequisites.errors.join("; ")));
+    }
+
+    PREREQUISITES_VERIFIED.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Watch a freshly spawned sidecar for a short window and fail if it exits with an error.
+///
+/// stderr is drained on a background thread for the lifetime of the process so the
+/// pipe never fills up; only lines captured during the window are reported.
+fn watch_startup(child: &mut Child) -> AppResult<()> {
+    let (tx, rx) = mpsc::channel::<String>();
+
+    if let Some(stderr) = child.stderr.take() {
+        std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                // The receiver goes away after the startup window; keep draining regardless
+                let _ = tx.send(line);
+            }
+        });
+    }
+
+    let deadline = Instant::now() + STARTUP_WINDOW;
+    while Instant::now() < deadline {
+        if let Some(status) = child.try_wait()? {
+            if status.success() {
+                return Ok(());
+            }
+
+            let mut stderr_lines = Vec::new();
+            while let Ok(line) = rx.recv_timeout(Duration::from_millis(200)) {
+                stderr_lines.push(line);
+            }
+
+            return Err(AppError::BrowserError(format!(
+                "Browser automation exited with {}: {}",
+                status,
+                stderr_lines.join("\n").trim()
+            )));
+        }
+
+        std::thread::sleep(Duration::from_millis(50));
+    }
+
+    Ok(())
+}
+
+/// Resolve the base directory holding browser profiles.
+///
+/// A configured (non-blank) directory wins; otherwise profiles live under the app data directory.
+fn browser_profile_base(configured: &str, app_data_dir: &Path) -> PathBuf {
+    let configured = configured.trim();
+    if configured.is_empty() {
+        app_data_dir.join(DEFAULT_PROFILES_SUBDIR)
+    } else {
+        PathBuf::from(configured)
+    }
+}
+
+/// Build the profile directory for a single interface so each AI service keeps its own session
+fn interface_profile_dir(base: &Path, interface_id: &str) -> AppResult<PathBuf> {
+    // Ids are validated on registration, but guard against path traversal regardless
+    if !is_valid_interface_id(interface_id) {
+        return Err(AppError::InvalidArgument(format!(
+            "Invalid interface id for profile directory: {}",
+            interface_id
+        )));
+    }
+
+    Ok(base.join(interface_id))
+}
+
+/// Create the profile directory if needed and make sure it is usable
+fn prepare_profile_dir(dir: &Path) -> AppResult<()> {
+    std::fs::create_dir_all(dir)?;
+
+    if !dir.is_dir() {
+        return Err(AppError::Path(format!(
+            "Browser profile path is not a directory: {}",
+            dir.display()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Get current timestamp in seconds
+fn current_timestamp() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
 }
 
 /// Launch browser with AI interface and fill prompt
@@ -33,81 +520,178 @@ impl AiInterface {
 /// **Disconnect Pattern:** The sidecar process exits via `process.exit(0)` 
 /// without calling `context.close()`. This leaves the browser process running 
 /// independently, allowing the user to review and submit the prompt manually.
-/// The persistent context stores session data in `.browser-data/` for reuse.
+/// The persistent context stores session data in a per-interface profile directory
+/// (under the `browser_profile_dir` setting, or the app data directory) for reuse.
 /// 
 /// # Arguments
-/// * `interface` - The AI interface to use (chatgpt, claude, gemini, aistudio)
+/// * `interface` - Id of a registered AI interface (chatgpt, claude, gemini, aistudio or a custom id)
 /// * `text` - The prompt text to fill
 /// * `custom_url` - Optional custom URL to override the default interface URL
 /// 
 /// # Returns
-/// * `Ok(())` if the sidecar was launched successfully
-/// * `Err(AppError)` if there was an error launching the sidecar
+/// * `Ok(BrowserLaunch)` with the sidecar PID if it survived its startup window
+/// * `Err(CommandError)` with code `BROWSER` if the sidecar could not be spawned or exited
+///   early with an error
 #[tauri::command]
 pub async fn launch_browser(
-    interface: AiInterface,
+    interface: String,
     text: String,
     custom_url: Option<String>,
-) -> Result<(), String> {
+    app: tauri::AppHandle,
+    db: tauri::State<'_, DbConnection>,
+    processes: tauri::State<'_, BrowserProcesses>,
+) -> CommandResult<BrowserLaunch> {
+    launch_browser_internal(&interface, text, custom_url, &app, &db, &processes).await
+}
+
+/// Internal function to launch the sidecar for `launch_browser` and `build_and_launch`
+///
+/// Checking prerequisites and spawning and watching the sidecar all run on a blocking thread.
+pub(crate) async fn launch_browser_internal(
+    interface: &str,
+    text: String,
+    custom_url: Option<String>,
+    app: &tauri::AppHandle,
+    db: &DbConnection,
+    processes: &BrowserProcesses,
+) -> CommandResult<BrowserLaunch> {
     info!(
-        "Launching browser for interface: {:?}, text length: {}",
+        "Launching browser for interface: {}, text length: {}",
         interface,
         text.len()
     );
 
+    let interface = resolve_interface(db, interface)?;
+    let url = launch_url(&interface, custom_url)?;
+
+    // Resolve the per-interface profile directory so logins survive any launch cwd
+    let settings = load_settings_internal(db)?.settings;
+    let app_data_dir = app.path().app_data_dir().map_err(|e| {
+        CommandError::new(
+            CommandError::PATH,
+            format!("Failed to get app data directory: {}", e),
+        )
+    })?;
+    let profile_dir = interface_profile_dir(
+        &browser_profile_base(&settings.browser_profile_dir, &app_data_dir),
+        &interface.id,
+    )?;
+
+    let (launch, child) = tauri::async_runtime::spawn_blocking(move || {
+        spawn_sidecar(&interface.id, &profile_dir, text, url)
+    })
+    .await
+    .map_err(|e| {
+        CommandError::new(
+            CommandError::BROWSER,
+            format!("Browser automation task failed: {}", e),
+        )
+    })??;
+
+    processes.insert(launch.clone(), Some(child))?;
+
+    Ok(launch)
+}
+
+/// Spawn the sidecar for `interface_id` and watch it through its startup window
+fn spawn_sidecar(
+    interface_id: &str,
+    profile_dir: &Path,
+    text: String,
+    url: String,
+) -> AppResult<(BrowserLaunch, Child)> {
+    ensure_prerequisites()?;
+
     // Get the resource path for the sidecar
-    let sidecar_path = get_sidecar_path().map_err(|e| e.to_string())?;
+    let sidecar_path = get_sidecar_path()?;
 
     info!("Sidecar path: {}", sidecar_path.display());
 
+    prepare_profile_dir(profile_dir)?;
+
+    info!("Browser profile directory: {}", profile_dir.display());
+
     // Build command arguments
-    let mut args = vec![
+    let args = vec![
         sidecar_path.to_string_lossy().to_string(),
-        interface.as_str().to_string(),
+        format!("--profile-dir={}", profile_dir.display()),
+        interface_id.to_string(),
         text,
+        url,
     ];
 
-    if let Some(url) = custom_url {
-        args.push(url);
-    }
-
     // Spawn the Node.js sidecar process
     // Note: We use spawn instead of output to avoid blocking
-    // Use Stdio::null() to prevent buffer fill-up which can terminate the child process
-    let child = Command::new("node")
+    // stdout is discarded; stderr is piped so an early Playwright failure can be reported
+    let mut child = Command::new("node")
         .args(&args)
         .stdout(Stdio::null())
-        .stderr(Stdio::null())
+        .stderr(Stdio::piped())
         .spawn()
         .map_err(|e| {
             warn!("Failed to spawn sidecar process: {}", e);
-            format!("Failed to launch browser automation: {}", e)
+            AppError::BrowserError(format!("Failed to launch browser automation: {}", e))
         })?;
 
-    info!(
-        "Sidecar process spawned with PID: {}",
-        child.id()
-    );
+    let launch = BrowserLaunch {
+        pid: child.id(),
+        interface: interface_id.to_string(),
+        started_at: current_timestamp(),
+    };
+
+    info!("Sidecar process spawned with PID: {}", launch.pid);
 
     // Note: We intentionally do NOT wait for the child process to complete
     // The sidecar will exit, but the browser will remain open
     // This is the key to the "disconnect" pattern
+    watch_startup(&mut child).inspect_err(|e| warn!("Sidecar failed during startup: {}", e))?;
 
-    Ok(())
+    Ok((launch, child))
+}
+
+/// Get the most recent sidecar launch for an interface, if any
+#[tauri::command]
+pub async fn get_browser_launch(
+    interface: String,
+    processes: tauri::State<'_, BrowserProcesses>,
+) -> CommandResult<Option<BrowserLaunch>> {
+    Ok(processes.get(&interface.to_lowercase())?)
 }
 
 /// Get available AI interfaces
 ///
-/// Returns a list of supported AI chat interfaces that can be used
-/// with the launch_browser command.
+/// Returns the built-in AI chat interfaces followed by any user-added ones.
+/// Each entry's `id` can be passed to the launch_browser command.
 #[tauri::command]
-pub async fn get_available_interfaces() -> Result<Vec<String>, String> {
-    Ok(vec![
-        "chatgpt".to_string(),
-        "claude".to_string(),
-        "gemini".to_string(),
-        "aistudio".to_string(),
-    ])
+pub async fn get_available_interfaces(
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<Vec<InterfaceDef>> {
+    Ok(get_interface_registry(&db)?)
+}
+
+/// Register a user-defined AI interface
+#[tauri::command]
+pub async fn add_custom_interface(
+    id: String,
+    name: String,
+    default_url: String,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<InterfaceDef> {
+    Ok(add_custom_interface_internal(
+        &db,
+        &id,
+        &name,
+        &default_url,
+    )?)
+}
+
+/// Remove a user-defined AI interface
+#[tauri::command]
+pub async fn remove_custom_interface(
+    id: String,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<()> {
+    Ok(remove_custom_interface_internal(&db, &id)?)
 }
 
 /// Get the path to the sidecar automation script
@@ -170,6 +754,15 @@ fn get_sidecar_path() -> AppResult<std::path::PathBuf> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::schema::init_database;
+    use rusqlite::Connection;
+    use std::sync::{Arc, Mutex};
+
+    fn setup_test_db() -> DbConnection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_database(&conn).unwrap();
+        Arc::new(Mutex::new(conn))
+    }
 
     #[test]
     fn test_interface_as_str() {
@@ -233,16 +826,213 @@ mod tests {
         assert!(debug_str.contains("ChatGPT"));
     }
 
+    #[test]
+    fn test_browser_processes_insert_and_lookup() {
+        let processes = BrowserProcesses::default();
+        assert_eq!(processes.get("claude").unwrap(), None);
+
+        let launch = BrowserLaunch {
+            pid: 1234,
+            interface: "claude".to_string(),
+            started_at: 1_700_000_000,
+        };
+        processes.insert(launch.clone(), None).unwrap();
+
+        assert_eq!(processes.get("claude").unwrap(), Some(launch));
+        assert_eq!(processes.get("chatgpt").unwrap(), None);
+    }
+
+    #[test]
+    fn test_browser_processes_replaces_previous_launch() {
+        let processes = BrowserProcesses::default();
+
+        for pid in [100, 200] {
+            processes
+                .insert(
+                    BrowserLaunch {
+                        pid,
+                        interface: "gemini".to_string(),
+                        started_at: 1_700_000_000,
+                    },
+                    None,
+                )
+                .unwrap();
+        }
+
+        assert_eq!(processes.get("gemini").unwrap().unwrap().pid, 200);
+    }
+
+    #[test]
+    fn test_browser_profile_base() {
+        let app_data_dir = Path::new("/data/app");
+
+        assert_eq!(
+            browser_profile_base("", app_data_dir),
+            PathBuf::from("/data/app/browser-profiles")
+        );
+        assert_eq!(
+            browser_profile_base("   ", app_data_dir),
+            PathBuf::from("/data/app/browser-profiles")
+        );
+        assert_eq!(
+            browser_profile_base("/custom/profiles", app_data_dir),
+            PathBuf::from("/custom/profiles")
+        );
+    }
+
+    #[test]
+    fn test_interface_profile_dir() {
+        let base = Path::new("/profiles");
+
+        assert_eq!(
+            interface_profile_dir(base, "claude").unwrap(),
+            PathBuf::from("/profiles/claude")
+        );
+        assert_ne!(
+            interface_profile_dir(base, "claude").unwrap(),
+            interface_profile_dir(base, "chatgpt").unwrap()
+        );
+        assert!(interface_profile_dir(base, "../escape").is_err());
+        assert!(interface_profile_dir(base, "").is_err());
+    }
+
+    #[test]
+    fn test_prepare_profile_dir_creates_directory() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path().join("profiles").join("gemini");
+
+        prepare_profile_dir(&dir).unwrap();
+        assert!(dir.is_dir());
+
+        // Preparing an existing directory is fine
+        prepare_profile_dir(&dir).unwrap();
+
+        let file = temp.path().join("not-a-dir");
+        std::fs::write(&file, "x").unwrap();
+        assert!(prepare_profile_dir(&file).is_err());
+    }
+
+    #[test]
+    fn test_parse_node_version() {
+        assert_eq!(parse_node_version("v20.11.1\n"), Some((20, 11, 1)));
+        assert_eq!(parse_node_version("18.0.0"), Some((18, 0, 0)));
+        assert_eq!(parse_node_version("v22"), Some((22, 0, 0)));
+        assert_eq!(parse_node_version("not a version"), None);
+        assert_eq!(parse_node_version(""), None);
+    }
+
+    #[test]
+    fn test_prerequisites_is_ready() {
+        let mut prerequisites = BrowserPrerequisites {
+            node_available: true,
+            node_version: Some("v20.0.0".to_string()),
+            node_version_supported: true,
+            min_node_version: "18.0.0".to_string(),
+            sidecar_found: true,
+            sidecar_path: Some("/app/sidecar/automation.js".to_string()),
+            errors: Vec::new(),
+        };
+        assert!(prerequisites.is_ready());
+
+        prerequisites.node_version_supported = false;
+        assert!(!prerequisites.is_ready());
+    }
+
     #[test]
     fn test_get_available_interfaces() {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async {
-            let interfaces = get_available_interfaces().await.unwrap();
-            assert_eq!(interfaces.len(), 4);
-            assert!(interfaces.contains(&"chatgpt".to_string()));
-            assert!(interfaces.contains(&"claude".to_string()));
-            assert!(interfaces.contains(&"gemini".to_string()));
-            assert!(interfaces.contains(&"aistudio".to_string()));
-        });
+        let db = setup_test_db();
+        let interfaces = get_interface_registry(&db).unwrap();
+        let ids: Vec<&str> = interfaces.iter().map(|i| i.id.as_str()).collect();
+
+        assert_eq!(ids, vec!["chatgpt", "claude", "gemini", "aistudio"]);
+        assert!(interfaces.iter().all(|i| i.builtin));
+    }
+
+    #[test]
+    fn test_resolve_builtin_interface_ids() {
+        let db = setup_test_db();
+
+        for id in ["chatgpt", "claude", "gemini", "aistudio", "ChatGPT"] {
+            let interface = resolve_interface(&db, id).unwrap();
+            assert_eq!(interface.id, id.to_lowercase());
+        }
+
+        assert!(resolve_interface(&db, "perplexity").is_err());
+    }
+
+    #[test]
+    fn test_add_and_remove_custom_interface() {
+        let db = setup_test_db();
+
+        add_custom_interface_internal(
+            &db,
+            "perplexity",
+            "Perplexity",
+            "https://www.perplexity.ai/",
+        )
+        .unwrap();
+
+        let registry = get_interface_registry(&db).unwrap();
+        assert_eq!(registry.len(), 5);
+        let perplexity = resolve_interface(&db, "perplexity").unwrap();
+        assert_eq!(perplexity.default_url, "https://www.perplexity.ai/");
+        assert!(!perplexity.builtin);
+
+        remove_custom_interface_internal(&db, "perplexity").unwrap();
+        assert_eq!(get_interface_registry(&db).unwrap().len(), 4);
+        assert!(resolve_interface(&db, "perplexity").is_err());
+    }
+
+    #[test]
+    fn test_add_custom_interface_rejects_invalid_input() {
+        let db = setup_test_db();
+
+        assert!(
+            add_custom_interface_internal(&db, "Bad Id", "Bad", "https://example.com").is_err()
+        );
+        assert!(add_custom_interface_internal(&db, "grok", "Grok", "ftp://example.com").is_err());
+        assert!(add_custom_interface_internal(&db, "grok", "  ", "https://grok.com").is_err());
+        // Built-in ids cannot be shadowed
+        assert!(
+            add_custom_interface_internal(&db, "claude", "Claude 2", "https://claude.ai").is_err()
+        );
+    }
+
+    #[test]
+    fn test_validate_http_url() {
+        assert!(validate_http_url("https://chat.openai.com/").is_ok());
+        assert!(validate_http_url("http://localhost:3000/chat?model=x").is_ok());
+        assert!(validate_http_url("HTTPS://Example.com").is_ok());
+
+        assert!(validate_http_url("file:///etc/passwd").is_err());
+        assert!(validate_http_url("javascript:alert(1)").is_err());
+        assert!(validate_http_url("ftp://example.com").is_err());
+        assert!(validate_http_url("chat.openai.com").is_err());
+        assert!(validate_http_url("https://").is_err());
+        assert!(validate_http_url("https:///path").is_err());
+        assert!(validate_http_url("https://exa mple.com").is_err());
+    }
+
+    #[test]
+    fn test_launch_url_prefers_valid_custom_url() {
+        let db = setup_test_db();
+        let claude = resolve_interface(&db, "claude").unwrap();
+
+        assert_eq!(launch_url(&claude, None).unwrap(), "https://claude.ai/");
+        assert_eq!(
+            launch_url(&claude, Some(" https://claude.ai/new ".to_string())).unwrap(),
+            "https://claude.ai/new"
+        );
+        assert!(matches!(
+            launch_url(&claude, Some("javascript:alert(1)".to_string())),
+            Err(AppError::BrowserError(_))
+        ));
+    }
+
+    #[test]
+    fn test_remove_builtin_interface_fails() {
+        let db = setup_test_db();
+        assert!(remove_custom_interface_internal(&db, "chatgpt").is_err());
+        assert!(remove_custom_interface_internal(&db, "unknown").is_err());
     }
 }
@@ -0,0 +1,18 @@
+use crate::db::{checkpoint, CheckpointResult, DbConnection};
+use crate::error::{CommandError, CommandResult};
+
+/// Checkpoint the WAL into the main database file and truncate it
+#[tauri::command]
+pub async fn checkpoint_database(
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<CheckpointResult> {
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+    let result = checkpoint(&conn)?;
+    log::info!(
+        "Checkpointed database: {} of {} WAL frames{}",
+        result.checkpointed_frames,
+        result.log_frames,
+        if result.busy { " (blocked)" } else { "" }
+    );
+    Ok(result)
+}
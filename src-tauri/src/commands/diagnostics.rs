@@ -0,0 +1,226 @@
+use super::browser::{check_prerequisites_internal, BrowserPrerequisites};
+use crate::cache::{CacheStats, TextCache};
+use crate::db::schema::SCHEMA_VERSION;
+use crate::db::{DbConnection, DB_FILE_NAME};
+use crate::error::{CommandError, CommandResult};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+/// Tables `init_database` creates; any of them missing means the schema is broken
+const EXPECTED_TABLES: [&str; 4] = ["files", "history", "bookmarks", "settings"];
+
+/// Whether the database answers queries, is on the current schema and has every table
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DatabaseHealth {
+    pub reachable: bool,
+    /// `PRAGMA user_version`; `None` when the database could not be queried
+    pub schema_version: Option<i64>,
+    /// The version this build's `init_database` writes
+    pub expected_schema_version: i64,
+    pub missing_tables: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// Whether the text cache can be written to, and how full it is
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CacheHealth {
+    pub dir: String,
+    pub writable: bool,
+    /// `None` when the cache could not be locked
+    pub stats: Option<CacheStats>,
+    pub error: Option<String>,
+}
+
+/// Everything `system_healthcheck` checked; a failed check is reported, never returned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub database: DatabaseHealth,
+    pub cache: CacheHealth,
+    /// Node.js and sidecar script availability, as `check_browser_prerequisites` reports it
+    pub browser: BrowserPrerequisites,
+    pub app_data_dir: Option<String>,
+    /// Size of the database file, without its WAL
+    pub database_size_bytes: Option<u64>,
+    /// Space left for the current user on the volume holding the app data directory
+    pub app_data_free_bytes: Option<u64>,
+}
+
+/// Internal function to read the schema version and look for the expected tables
+fn check_database(db: &DbConnection) -> DatabaseHealth {
+    let result = db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))
+        .and_then(|conn| {
+            let schema_version = conn
+                .query_row("PRAGMA user_version", [], |row| row.get::<_, i64>(0))
+                .map_err(|e| e.to_string())?;
+
+            let mut missing_tables = Vec::new();
+            for table in EXPECTED_TABLES {
+                let exists = conn
+                    .query_row(
+                        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?",
+                        [table],
+                        |row| row.get::<_, i64>(0),
+                    )
+                    .map_err(|e| e.to_string())?
+                    > 0;
+                if !exists {
+                    missing_tables.push(table.to_string());
+                }
+            }
+            Ok((schema_version, missing_tables))
+        });
+
+    match result {
+        Ok((schema_version, missing_tables)) => DatabaseHealth {
+            reachable: true,
+            schema_version: Some(schema_version),
+            expected_schema_version: SCHEMA_VERSION,
+            missing_tables,
+            error: None,
+        },
+        Err(e) => DatabaseHealth {
+            reachable: false,
+            schema_version: None,
+            expected_schema_version: SCHEMA_VERSION,
+            missing_tables: Vec::new(),
+            error: Some(e),
+        },
+    }
+}
+
+/// Internal function to read the cache usage and probe its directory with a temporary file
+fn check_cache(cache: &Mutex<TextCache>) -> CacheHealth {
+    let (dir, stats) = match cache.lock() {
+        Ok(cache) => (cache.cache_dir().to_path_buf(), cache.stats()),
+        Err(e) => {
+            return CacheHealth {
+                dir: String::new(),
+                writable: false,
+                stats: None,
+                error: Some(format!("Failed to lock text cache: {}", e)),
+            }
+        }
+    };
+
+    let error = probe_writable(&dir).err();
+    CacheHealth {
+        dir: dir.to_string_lossy().to_string(),
+        writable: error.is_none(),
+        stats: Some(stats),
+        error,
+    }
+}
+
+/// Create and remove a file in `dir` to find out whether it can be written to
+fn probe_writable(dir: &Path) -> Result<(), String> {
+    let probe = dir.join(".healthcheck");
+    fs::write(&probe, b"ok").map_err(|e| format!("Cannot write to {}: {}", dir.display(), e))?;
+    fs::remove_file(&probe).map_err(|e| format!("Cannot clean up {}: {}", probe.display(), e))
+}
+
+/// Check the database, text cache, browser automation prerequisites and app data directory
+///
+/// Backs the diagnostics screen and bug reports, so every check runs even if another fails.
+/// The checks touch the disk and run Node.js, so they run on a blocking thread.
+#[tauri::command]
+pub async fn system_healthcheck(app: AppHandle) -> CommandResult<HealthReport> {
+    tauri::async_runtime::spawn_blocking(move || healthcheck_internal(&app))
+        .await
+        .map_err(|e| {
+            CommandError::new(
+                CommandError::UNKNOWN,
+                format!("Healthcheck task failed: {}", e),
+            )
+        })
+}
+
+/// Internal function to run every check for `system_healthcheck`
+fn healthcheck_internal(app: &AppHandle) -> HealthReport {
+    let database = check_database(&app.state::<DbConnection>());
+    let cache = check_cache(&app.state::<Mutex<TextCache>>());
+    let browser = check_prerequisites_internal();
+
+    let app_data_dir = app.path().app_data_dir().ok();
+    let database_size_bytes = app_data_dir
+        .as_ref()
+        .and_then(|dir| fs::metadata(dir.join(DB_FILE_NAME)).ok())
+        .map(|metadata| metadata.len());
+    let app_data_free_bytes = app_data_dir
+        .as_ref()
+        .and_then(|dir| fs4::available_space(dir).ok());
+
+    HealthReport {
+        database,
+        cache,
+        browser,
+        app_data_dir: app_data_dir.map(|dir| dir.to_string_lossy().to_string()),
+        database_size_bytes,
+        app_data_free_bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_check_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_database(&conn).unwrap();
+        let db: DbConnection = Arc::new(Mutex::new(conn));
+
+        let health = check_database(&db);
+        assert!(health.reachable);
+        assert_eq!(health.schema_version, Some(SCHEMA_VERSION));
+        assert_eq!(health.expected_schema_version, SCHEMA_VERSION);
+        assert!(health.missing_tables.is_empty());
+        assert_eq!(health.error, None);
+
+        db.lock()
+            .unwrap()
+            .execute("DROP TABLE bookmarks", [])
+            .unwrap();
+        assert_eq!(check_database(&db).missing_tables, vec!["bookmarks"]);
+
+        // A database no build has migrated yet
+        db.lock()
+            .unwrap()
+            .pragma_update(None, "user_version", 0)
+            .unwrap();
+        assert_eq!(check_database(&db).schema_version, Some(0));
+    }
+
+    #[test]
+    fn test_check_cache_reports_usage_and_writability() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cache = Mutex::new(TextCache::new(temp_dir.path().join("cache")).unwrap());
+        cache
+            .lock()
+            .unwrap()
+            .put("/p/a.txt", "fp", "hello")
+            .unwrap();
+
+        let health = check_cache(&cache);
+        assert!(health.writable, "{:?}", health.error);
+        let stats = health.stats.unwrap();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.size_bytes, 5);
+        // The probe file is gone again; only the entry and its fingerprint remain
+        assert_eq!(
+            fs::read_dir(temp_dir.path().join("cache")).unwrap().count(),
+            2
+        );
+
+        fs::remove_dir_all(temp_dir.path().join("cache")).unwrap();
+        let health = check_cache(&cache);
+        assert!(!health.writable);
+        assert!(health.error.is_some());
+    }
+}
@@ -1,20 +1,30 @@
+use super::settings::get_setting_with_conn;
 use crate::cache::TextCache;
 use crate::db::DbConnection;
-use crate::error::{AppError, AppResult};
+use crate::error::{AppError, AppResult, CommandError, CommandResult};
 use chardetng::EncodingDetector;
 use encoding_rs::Encoding;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fs;
+use std::io::{self, Read};
 use std::path::Path;
 use std::sync::Mutex;
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Event emitted while `extract_text` reads a file
+pub const EXTRACTION_PROGRESS_EVENT: &str = "extraction-progress";
+
+/// Bytes read between progress reports
+const EXTRACTION_CHUNK_SIZE: usize = 1024 * 1024;
 
 /// Progress event for extraction
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractionProgress {
     pub path: String,
     pub status: String,
+    /// Fraction of the file read so far, from 0.0 to 1.0
     pub progress: f32,
 }
 
@@ -24,68 +34,149 @@ pub struct ExtractionResult {
     pub text: String,
     pub encoding: Option<String>,
     pub error: Option<String>,
+    /// Content type, e.g. `text/markdown`; `None` when extraction failed
+    #[serde(default)]
+    pub mime_type: Option<String>,
+}
+
+/// Text decoded from a file along with what was learned while decoding it
+#[derive(Debug)]
+pub(crate) struct ExtractedText {
+    pub text: String,
+    pub encoding: String,
+    pub mime_type: String,
 }
 
 /// Extract text from a file
 /// Supports: plain text, source code, markdown, and other text-based files
 /// PDF and DOCX extraction should be done from the frontend using pdfjs-dist and mammoth
+///
+/// Files are read on a blocking thread, emitting `extraction-progress` events as they are read.
 #[tauri::command]
 pub async fn extract_text(
     path: String,
+    app: AppHandle,
     db: State<'_, DbConnection>,
     cache: State<'_, Mutex<TextCache>>,
-) -> Result<ExtractionResult, String> {
+) -> CommandResult<ExtractionResult> {
     log::info!("Extracting text from: {}", path);
 
     // Get file metadata from database to get fingerprint
-    let conn = db.lock().map_err(|e| format!("Failed to lock database: {}", e))?;
-
-    let (fingerprint, is_dir): (Option<String>, bool) = conn
-        .query_row(
-            "SELECT fingerprint, is_dir FROM files WHERE path = ?",
-            params![&path],
-            |row| Ok((row.get(0)?, row.get::<_, i32>(1)? != 0)),
-        )
-        .map_err(|e| format!("Failed to get file metadata: {}", e))?;
-
-    if is_dir {
-        return Err("Cannot extract text from directory".to_string());
-    }
-
-    let fingerprint = fingerprint.ok_or_else(|| "File fingerprint not found".to_string())?;
+    let fingerprint = {
+        let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+        indexed_file_fingerprint(&conn, &path)?
+    };
 
-    // Check cache first
-    let mut cache_guard = cache
+    // Check cache first; the lock is released before reading the file
+    let cached = cache
         .lock()
-        .map_err(|e| format!("Failed to lock cache: {}", e))?;
+        .map_err(|e| CommandError::lock("cache", e))?
+        .get(&path, &fingerprint);
 
-    if let Ok(Some(cached_text)) = cache_guard.get(&path, &fingerprint) {
+    if let Ok(Some(cached_text)) = cached {
         log::info!("Using cached text for: {}", path);
+        let mime_type = detect_mime_type(Path::new(&path), cached_text.as_bytes());
         return Ok(ExtractionResult {
             text: cached_text,
             encoding: Some("utf-8".to_string()),
             error: None,
+            mime_type: Some(mime_type.to_string()),
         });
     }
 
-    drop(cache_guard); // Release lock while reading file
+    tauri::async_runtime::spawn_blocking(move || {
+        let cache = app.state::<Mutex<TextCache>>();
+        let on_progress = progress_emitter(&app, &path);
+        extract_and_cache(&path, &fingerprint, None, &cache, on_progress)
+    })
+    .await
+    .map_err(|e| {
+        CommandError::new(
+            CommandError::UNKNOWN,
+            format!("Extraction task failed: {}", e),
+        )
+    })
+}
+
+/// Progress callback that emits `ExtractionProgress` for `path`, once per whole percent
+fn progress_emitter(app: &AppHandle, path: &str) -> impl FnMut(f32) {
+    let app = app.clone();
+    let path = path.to_string();
+    let mut last_percent = None;
+
+    move |progress| {
+        let percent = (progress * 100.0) as u32;
+        if last_percent == Some(percent) {
+            return;
+        }
+        last_percent = Some(percent);
+
+        let event = ExtractionProgress {
+            path: path.clone(),
+            status: if percent < 100 { "reading" } else { "complete" }.to_string(),
+            progress,
+        };
+        if let Err(e) = app.emit(EXTRACTION_PROGRESS_EVENT, &event) {
+            log::warn!("Failed to emit extraction progress: {}", e);
+        }
+    }
+}
+
+/// Look up the fingerprint of an indexed file, rejecting directories and unknown paths
+fn indexed_file_fingerprint(conn: &rusqlite::Connection, path: &str) -> CommandResult<String> {
+    let (fingerprint, is_dir): (Option<String>, bool) = conn
+        .query_row(
+            "SELECT fingerprint, is_dir FROM files WHERE path = ?",
+            params![&path],
+            |row| Ok((row.get(0)?, row.get::<_, i32>(1)? != 0)),
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => CommandError::new(
+                CommandError::NOT_FOUND,
+                format!("File not in index: {}", path),
+            ),
+            e => CommandError::new(
+                CommandError::DB,
+                format!("Failed to get file metadata: {}", e),
+            ),
+        })?;
+
+    if is_dir {
+        return Err(CommandError::new(
+            CommandError::INVALID_ARGUMENT,
+            "Cannot extract text from directory",
+        ));
+    }
+
+    fingerprint
+        .ok_or_else(|| CommandError::new(CommandError::NOT_FOUND, "File fingerprint not found"))
+}
 
-    // Extract text based on file type
-    let result = match extract_text_from_file(&path) {
-        Ok((text, encoding)) => {
+/// Extract a file's text and cache it under `fingerprint`; failures are reported in the result
+fn extract_and_cache(
+    path: &str,
+    fingerprint: &str,
+    forced_encoding: Option<&'static Encoding>,
+    cache: &Mutex<TextCache>,
+    on_progress: impl FnMut(f32),
+) -> ExtractionResult {
+    match extract_text_with_progress(path, forced_encoding, EXTRACTION_CHUNK_SIZE, on_progress) {
+        Ok(extracted) => {
             // Cache the extracted text
-            let mut cache_guard = cache
-                .lock()
-                .map_err(|e| format!("Failed to lock cache: {}", e))?;
-            
-            if let Err(e) = cache_guard.put(&path, &fingerprint, &text) {
-                log::warn!("Failed to cache text for {}: {}", path, e);
+            match cache.lock() {
+                Ok(mut cache_guard) => {
+                    if let Err(e) = cache_guard.put(path, fingerprint, &extracted.text) {
+                        log::warn!("Failed to cache text for {}: {}", path, e);
+                    }
+                }
+                Err(e) => log::warn!("Failed to lock text cache: {}", e),
             }
 
             ExtractionResult {
-                text,
-                encoding: Some(encoding),
+                text: extracted.text,
+                encoding: Some(extracted.encoding),
                 error: None,
+                mime_type: Some(extracted.mime_type),
             }
         }
         Err(e) => {
@@ -94,15 +185,104 @@ pub async fn extract_text(
                 text: String::new(),
                 encoding: None,
                 error: Some(e.to_string()),
+                mime_type: None,
             }
         }
+    }
+}
+
+/// Drop the cached text for a path so the next extraction reads the file again
+#[tauri::command]
+pub async fn invalidate_cache_entry(
+    path: String,
+    cache: State<'_, Mutex<TextCache>>,
+) -> CommandResult<bool> {
+    let mut cache_guard = cache.lock().map_err(|e| CommandError::lock("cache", e))?;
+    Ok(cache_guard.invalidate(&path)?)
+}
+
+/// Internal function to clear a file's cached text and extract it again
+///
+/// With `encoding` (any WHATWG label, e.g. "windows-1252") the file is decoded with that
+/// encoding instead of the detected one, fixing misdetected files.
+fn reextract_text_internal(
+    cache: &Mutex<TextCache>,
+    path: &str,
+    fingerprint: &str,
+    encoding: Option<&str>,
+) -> CommandResult<ExtractionResult> {
+    let forced_encoding = encoding
+        .map(|label| {
+            Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| {
+                CommandError::new(
+                    CommandError::INVALID_ARGUMENT,
+                    format!("Unknown encoding: {}", label),
+                )
+            })
+        })
+        .transpose()?;
+
+    cache
+        .lock()
+        .map_err(|e| CommandError::lock("cache", e))?
+        .invalidate(path)?;
+
+    Ok(extract_and_cache(
+        path,
+        fingerprint,
+        forced_encoding,
+        cache,
+        |_| {},
+    ))
+}
+
+/// Re-extract a file's text, bypassing and replacing its cached copy
+///
+/// Like `extract_text`, the file is read on a blocking thread after the database lock is
+/// released.
+#[tauri::command]
+pub async fn reextract_text(
+    path: String,
+    encoding: Option<String>,
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+) -> CommandResult<ExtractionResult> {
+    log::info!("Re-extracting text from: {}", path);
+
+    let fingerprint = {
+        let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+        indexed_file_fingerprint(&conn, &path)?
     };
 
-    Ok(result)
+    tauri::async_runtime::spawn_blocking(move || {
+        let cache = app.state::<Mutex<TextCache>>();
+        reextract_text_internal(&cache, &path, &fingerprint, encoding.as_deref())
+    })
+    .await
+    .map_err(|e| {
+        CommandError::new(
+            CommandError::UNKNOWN,
+            format!("Extraction task failed: {}", e),
+        )
+    })?
 }
 
-/// Extract text from a plain text file with encoding detection
-fn extract_text_from_file(path: &str) -> AppResult<(String, String)> {
+/// Extract text from a plain text file, detecting its encoding unless one is forced
+pub(crate) fn extract_text_from_file(
+    path: &str,
+    forced_encoding: Option<&'static Encoding>,
+) -> AppResult<ExtractedText> {
+    extract_text_with_progress(path, forced_encoding, EXTRACTION_CHUNK_SIZE, |_| {})
+}
+
+/// `extract_text_from_file`, reading `chunk_size` bytes at a time and reporting the fraction
+/// read after each chunk. Encoding detection runs once the whole file is in memory.
+fn extract_text_with_progress(
+    path: &str,
+    forced_encoding: Option<&'static Encoding>,
+    chunk_size: usize,
+    on_progress: impl FnMut(f32),
+) -> AppResult<ExtractedText> {
     let path_obj = Path::new(path);
 
     if !path_obj.exists() {
@@ -110,14 +290,22 @@ fn extract_text_from_file(path: &str) -> AppResult<(String, String)> {
     }
 
     // Read file as bytes
-    let bytes = fs::read(path_obj)?;
+    let bytes = read_with_progress(path_obj, chunk_size, on_progress)?;
+    let mime_type = detect_mime_type(path_obj, &bytes).to_string();
 
     if bytes.is_empty() {
-        return Ok((String::new(), "utf-8".to_string()));
+        return Ok(ExtractedText {
+            text: String::new(),
+            encoding: "utf-8".to_string(),
+            mime_type,
+        });
     }
 
     // Detect encoding
-    let (encoding, text) = detect_encoding_and_decode(&bytes)?;
+    let (encoding, text) = match forced_encoding {
+        Some(encoding) => decode_with(&bytes, encoding),
+        None => detect_encoding_and_decode(&bytes)?,
+    };
 
     log::debug!(
         "Extracted {} chars from {} (encoding: {})",
@@ -126,7 +314,37 @@ fn extract_text_from_file(path: &str) -> AppResult<(String, String)> {
         encoding
     );
 
-    Ok((text, encoding))
+    Ok(ExtractedText {
+        text,
+        encoding,
+        mime_type,
+    })
+}
+
+/// Read a whole file in chunks, calling `on_progress` with the fraction read after each one
+fn read_with_progress(
+    path: &Path,
+    chunk_size: usize,
+    mut on_progress: impl FnMut(f32),
+) -> io::Result<Vec<u8>> {
+    let mut file = fs::File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut bytes = Vec::with_capacity(size as usize);
+    let mut chunk = vec![0; chunk_size.max(1)];
+
+    loop {
+        let read = match file.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        bytes.extend_from_slice(&chunk[..read]);
+        // The file may grow while it is read; never report more than done
+        on_progress((bytes.len() as f64 / size.max(1) as f64).min(1.0) as f32);
+    }
+
+    Ok(bytes)
 }
 
 /// Detect encoding and decode bytes to string
@@ -153,12 +371,178 @@ fn detect_encoding_and_decode(bytes: &[u8]) -> AppResult<(String, String)> {
     Ok((encoding.name().to_string(), decoded.to_string()))
 }
 
+/// Decode bytes with a known encoding
+fn decode_with(bytes: &[u8], encoding: &'static Encoding) -> (String, String) {
+    let (decoded, encoding_used, had_errors) = encoding.decode(bytes);
+
+    if had_errors {
+        log::warn!(
+            "Decoding as {} had errors, some characters may be replaced",
+            encoding_used.name()
+        );
+    }
+
+    (encoding_used.name().to_string(), decoded.into_owned())
+}
+
+/// Bytes inspected when sniffing the content type of a file without a known extension
+const MIME_SNIFF_LEN: usize = 8192;
+
+/// Content type of a file, from its extension or, failing that, from its content
+///
+/// Content with a NUL byte in its first few kilobytes is treated as binary, anything else
+/// as plain text.
+fn detect_mime_type(path: &Path, bytes: &[u8]) -> &'static str {
+    let by_extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(|ext| mime_type_for_extension(&ext.to_lowercase()));
+    if let Some(mime_type) = by_extension {
+        return mime_type;
+    }
+
+    let sample = &bytes[..bytes.len().min(MIME_SNIFF_LEN)];
+    if sample.contains(&0) {
+        "application/octet-stream"
+    } else {
+        "text/plain"
+    }
+}
+
+/// Content type for a lowercase file extension, if it is one we know
+fn mime_type_for_extension(ext: &str) -> Option<&'static str> {
+    let mime_type = match ext {
+        "txt" | "text" | "log" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "rst" => "text/x-rst",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "csv" => "text/csv",
+        "tsv" => "text/tab-separated-values",
+        "js" | "mjs" | "cjs" | "jsx" => "text/javascript",
+        "ts" | "tsx" => "text/typescript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "yaml" | "yml" => "application/yaml",
+        "toml" => "application/toml",
+        "sql" => "application/sql",
+        "rtf" => "application/rtf",
+        "sh" | "bash" | "zsh" => "text/x-shellscript",
+        "py" => "text/x-python",
+        "rs" => "text/x-rust",
+        "c" | "h" => "text/x-c",
+        "cpp" | "hpp" => "text/x-c++",
+        "java" => "text/x-java",
+        "go" => "text/x-go",
+        "pdf" => "application/pdf",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        _ => return None,
+    };
+    Some(mime_type)
+}
+
+/// The user's additions to and removals from the files `is_text_file` accepts, from the
+/// `text_extensions`, `text_file_names` and `blocked_text_extensions` settings
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextFileOverrides {
+    /// Lowercase, without the leading dot
+    extensions: HashSet<String>,
+    file_names: HashSet<String>,
+    /// Lowercase, without the leading dot
+    blocked_extensions: HashSet<String>,
+}
+
+impl TextFileOverrides {
+    pub fn new(
+        extensions: &[String],
+        file_names: &[String],
+        blocked_extensions: &[String],
+    ) -> Self {
+        let normalize_extensions = |list: &[String]| {
+            list.iter()
+                .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+                .filter(|ext| !ext.is_empty())
+                .collect()
+        };
+        Self {
+            extensions: normalize_extensions(extensions),
+            file_names: file_names
+                .iter()
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect(),
+            blocked_extensions: normalize_extensions(blocked_extensions),
+        }
+    }
+
+    /// Read the overrides with an already locked connection
+    ///
+    /// A list that is missing or fails to parse counts as empty.
+    pub(crate) fn load(conn: &rusqlite::Connection) -> Self {
+        let list = |key: &str| -> Vec<String> {
+            let value = match get_setting_with_conn(conn, key) {
+                Ok(value) => value,
+                Err(e) => {
+                    log::warn!("Failed to read setting {}: {}", key, e);
+                    None
+                }
+            };
+            value
+                .and_then(|value| match serde_json::from_str(&value) {
+                    Ok(list) => Some(list),
+                    Err(e) => {
+                        log::warn!(
+                            "Ignoring invalid value '{}' for setting {}: {}",
+                            value,
+                            key,
+                            e
+                        );
+                        None
+                    }
+                })
+                .unwrap_or_default()
+        };
+
+        Self::new(
+            &list("text_extensions"),
+            &list("text_file_names"),
+            &list("blocked_text_extensions"),
+        )
+    }
+
+    /// Whether `path` has an extension the user excluded from being treated as text
+    pub fn blocks(&self, path: &str) -> bool {
+        Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| self.blocked_extensions.contains(&ext.to_lowercase()))
+    }
+}
+
 /// Check if a file is likely a text file based on extension
-pub fn is_text_file(path: &str) -> bool {
+///
+/// The user's file names and extensions in `overrides` count as text as well; a blocked
+/// extension never does.
+pub fn is_text_file(path: &str, overrides: &TextFileOverrides) -> bool {
     let path_obj = Path::new(path);
+    let file_name = path_obj.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
+    if overrides.file_names.contains(file_name) {
+        return true;
+    }
     
     if let Some(ext) = path_obj.extension().and_then(|e| e.to_str()) {
         let ext_lower = ext.to_lowercase();
+        if overrides.blocked_extensions.contains(&ext_lower) {
+            return false;
+        }
+        if overrides.extensions.contains(&ext_lower) {
+            return true;
+        }
         
         // Common text file extensions
         matches!(
@@ -180,7 +564,6 @@ pub fn is_text_file(path: &str) -> bool {
         )
     } else {
         // No extension - check if it's a common text file
-        let file_name = path_obj.file_name().and_then(|n| n.to_str()).unwrap_or("");
         matches!(
             file_name,
             "README" | "LICENSE" | "Makefile" | "Dockerfile" | "Cargo.lock" |
@@ -190,9 +573,20 @@ pub fn is_text_file(path: &str) -> bool {
 }
 
 /// Get list of supported file types for extraction
+///
+/// Includes the user's extra text extensions and leaves out the blocked ones.
 #[tauri::command]
-pub async fn get_supported_file_types() -> Result<Vec<String>, String> {
-    Ok(vec![
+pub async fn get_supported_file_types(db: State<'_, DbConnection>) -> CommandResult<Vec<String>> {
+    let overrides = {
+        let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+        TextFileOverrides::load(&conn)
+    };
+    Ok(supported_file_types(&overrides))
+}
+
+/// Internal function to apply `overrides` to the built-in list of supported file types
+fn supported_file_types(overrides: &TextFileOverrides) -> Vec<String> {
+    let mut types = vec![
         "txt".to_string(),
         "md".to_string(),
         "markdown".to_string(),
@@ -225,7 +619,17 @@ pub async fn get_supported_file_types() -> Result<Vec<String>, String> {
         "bash".to_string(),
         "csv".to_string(),
         "log".to_string(),
-    ])
+    ];
+    types.retain(|ext| !overrides.blocked_extensions.contains(ext));
+
+    let mut extra: Vec<&String> = overrides
+        .extensions
+        .iter()
+        .filter(|ext| !types.contains(ext))
+        .collect();
+    extra.sort();
+    types.extend(extra.into_iter().cloned());
+    types
 }
 
 #[cfg(test)]
@@ -234,17 +638,71 @@ mod tests {
 
     #[test]
     fn test_is_text_file() {
-        assert!(is_text_file("file.txt"));
-        assert!(is_text_file("file.md"));
-        assert!(is_text_file("file.rs"));
-        assert!(is_text_file("file.py"));
-        assert!(is_text_file("README"));
-        assert!(is_text_file("Makefile"));
+        let defaults = TextFileOverrides::default();
+        assert!(is_text_file("file.txt", &defaults));
+        assert!(is_text_file("file.md", &defaults));
+        assert!(is_text_file("file.rs", &defaults));
+        assert!(is_text_file("file.py", &defaults));
+        assert!(is_text_file("README", &defaults));
+        assert!(is_text_file("Makefile", &defaults));
         
-        assert!(!is_text_file("file.pdf"));
-        assert!(!is_text_file("file.docx"));
-        assert!(!is_text_file("file.exe"));
-        assert!(!is_text_file("file.png"));
+        assert!(!is_text_file("file.pdf", &defaults));
+        assert!(!is_text_file("file.docx", &defaults));
+        assert!(!is_text_file("file.exe", &defaults));
+        assert!(!is_text_file("file.png", &defaults));
+    }
+
+    #[test]
+    fn test_text_file_overrides() {
+        let overrides = TextFileOverrides::new(
+            &[".conf2".to_string(), "RS".to_string()],
+            &[".env.local".to_string()],
+            &["log".to_string(), ".MD".to_string()],
+        );
+
+        // User additions become text
+        assert!(!is_text_file("/p/app.conf2", &TextFileOverrides::default()));
+        assert!(is_text_file("/p/app.conf2", &overrides));
+        assert!(is_text_file("/p/APP.CONF2", &overrides));
+        assert!(is_text_file("/p/.env.local", &overrides));
+        assert!(!is_text_file("/p/.env.production", &overrides));
+
+        // Blocked defaults no longer are
+        assert!(!is_text_file("/p/server.log", &overrides));
+        assert!(!is_text_file("/p/README.md", &overrides));
+        assert!(overrides.blocks("/p/server.log"));
+        assert!(!overrides.blocks("/p/main.rs"));
+        assert!(is_text_file("/p/main.rs", &overrides));
+
+        let types = supported_file_types(&overrides);
+        assert!(types.contains(&"conf2".to_string()));
+        assert!(!types.contains(&"log".to_string()));
+        assert!(!types.contains(&"md".to_string()));
+        assert_eq!(types.iter().filter(|ext| *ext == "rs").count(), 1);
+    }
+
+    #[test]
+    fn test_load_text_file_overrides() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::schema::init_database(&conn).unwrap();
+        assert_eq!(TextFileOverrides::load(&conn), TextFileOverrides::default());
+
+        for (key, value) in [
+            ("text_extensions", r#"[".conf2"]"#),
+            ("blocked_text_extensions", "not json"),
+        ] {
+            conn.execute(
+                "INSERT INTO settings (key, value) VALUES (?, ?)",
+                params![key, value],
+            )
+            .unwrap();
+        }
+
+        let overrides = TextFileOverrides::load(&conn);
+        assert_eq!(
+            overrides,
+            TextFileOverrides::new(&[".conf2".to_string()], &[], &[])
+        );
     }
 
     #[test]
@@ -266,4 +724,90 @@ mod tests {
         let (encoding, decoded) = detect_encoding_and_decode(bytes).unwrap();
         assert_eq!(decoded, text);
     }
+
+    #[test]
+    fn test_reextract_replaces_cached_text() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("latin1.txt");
+        // "café crème" in windows-1252
+        fs::write(&file_path, b"caf\xe9 cr\xe8me").unwrap();
+        let path = file_path.to_str().unwrap().to_string();
+
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::schema::init_database(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO files (path, name, is_dir, fingerprint) VALUES (?, 'latin1.txt', 0, 'fp')",
+            params![path],
+        )
+        .unwrap();
+
+        let cache = Mutex::new(TextCache::new(temp_dir.path().join("cache")).unwrap());
+        // A misdetected decode was cached earlier
+        cache
+            .lock()
+            .unwrap()
+            .put(&path, "fp", "caf\u{fffd} cr\u{fffd}me")
+            .unwrap();
+
+        let fingerprint = indexed_file_fingerprint(&conn, &path).unwrap();
+        let result =
+            reextract_text_internal(&cache, &path, &fingerprint, Some("windows-1252")).unwrap();
+        assert_eq!(result.text, "café crème");
+        assert_eq!(result.encoding.as_deref(), Some("windows-1252"));
+        assert_eq!(
+            cache.lock().unwrap().get(&path, "fp").unwrap(),
+            Some("café crème".to_string())
+        );
+
+        let err = reextract_text_internal(&cache, &path, &fingerprint, Some("not-an-encoding"))
+            .unwrap_err();
+        assert_eq!(err.code, CommandError::INVALID_ARGUMENT);
+
+        let err = indexed_file_fingerprint(&conn, "/missing.txt").unwrap_err();
+        assert_eq!(err.code, CommandError::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_extract_text_reports_read_progress() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("large.txt");
+        let text = "the quick brown fox jumps over the lazy dog\n".repeat(5_000);
+        fs::write(&path, &text).unwrap();
+        let path = path.to_str().unwrap();
+
+        let mut progress = Vec::new();
+        let extracted =
+            extract_text_with_progress(path, None, 16 * 1024, |p| progress.push(p)).unwrap();
+
+        assert_eq!(extracted.text, fs::read_to_string(path).unwrap());
+        assert_eq!(extracted.encoding, "utf-8");
+        assert_eq!(progress.len(), text.len().div_ceil(16 * 1024));
+        assert!(progress.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(progress.last().copied(), Some(1.0));
+    }
+
+    #[test]
+    fn test_extract_text_detects_mime_type() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cases = [
+            ("notes.md", "# Title\n", "text/markdown"),
+            ("data.json", "{\"a\": 1}", "application/json"),
+            ("CHANGES", "plain words\n", "text/plain"),
+        ];
+        for (name, content, expected) in cases {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, content).unwrap();
+            let extracted = extract_text_from_file(path.to_str().unwrap(), None).unwrap();
+            assert_eq!(extracted.mime_type, expected, "{}", name);
+        }
+
+        assert_eq!(
+            detect_mime_type(Path::new("blob"), b"\x7fELF\x02\x01\x00\x00"),
+            "application/octet-stream"
+        );
+        assert_eq!(
+            detect_mime_type(Path::new("README.MD"), b""),
+            "text/markdown"
+        );
+    }
 }
@@ -0,0 +1,209 @@
+use crate::error::{CommandError, CommandResult};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// One entry of `git status --porcelain`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GitChange {
+    /// Two-letter status code, such as ` M`, `A ` or `??`
+    pub status: String,
+    /// Path relative to the repository root
+    pub path: String,
+    /// Path a renamed or copied file came from
+    pub original_path: Option<String>,
+}
+
+/// Uncommitted changes of the repository an indexed folder belongs to
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GitChanges {
+    /// Whether the folder is inside a git work tree; everything else is empty when it isn't
+    pub is_repository: bool,
+    /// Changed and untracked files under the folder
+    pub files: Vec<GitChange>,
+    /// `git diff HEAD` under the folder: staged and unstaged changes to tracked files
+    pub diff: String,
+}
+
+impl GitChanges {
+    /// Text for the `{{git_diff}}` placeholder: the changed files, then the diff
+    ///
+    /// Empty outside a git repository.
+    pub fn to_prompt_section(&self, root: &str) -> String {
+        if !self.is_repository {
+            return String::new();
+        }
+        if self.files.is_empty() {
+            return format!("No uncommitted changes in {}.", root);
+        }
+
+        let mut section = format!("Uncommitted changes in {}:\n", root);
+        for change in &self.files {
+            section.push_str(&change.status);
+            section.push(' ');
+            if let Some(original_path) = &change.original_path {
+                section.push_str(original_path);
+                section.push_str(" -> ");
+            }
+            section.push_str(&change.path);
+            section.push('\n');
+        }
+        if !self.diff.is_empty() {
+            section.push_str("\n```diff\n");
+            section.push_str(self.diff.trim_end());
+            section.push_str("\n```");
+        }
+        section.trim_end().to_string()
+    }
+}
+
+/// Parse the NUL-separated output of `git status --porcelain -z`
+///
+/// Renames and copies are followed by an extra field holding the path they came from.
+pub(crate) fn parse_porcelain(output: &str) -> Vec<GitChange> {
+    let mut changes = Vec::new();
+    let mut fields = output.split('\0').filter(|field| !field.is_empty());
+
+    while let Some(field) = fields.next() {
+        let (Some(status), Some(path)) = (field.get(..2), field.get(3..)) else {
+            log::warn!("Skipping malformed git status entry: {:?}", field);
+            continue;
+        };
+        let original_path = if status.contains(['R', 'C']) {
+            fields.next().map(str::to_string)
+        } else {
+            None
+        };
+
+        changes.push(GitChange {
+            status: status.to_string(),
+            path: path.to_string(),
+            original_path,
+        });
+    }
+    changes
+}
+
+/// Run git in `root`, returning its stdout, or `None` if it failed or isn't installed
+fn run_git(root: &Path, args: &[&str]) -> Option<String> {
+    match Command::new("git").arg("-C").arg(root).args(args).output() {
+        Ok(output) if output.status.success() => {
+            Some(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+        Ok(output) => {
+            log::debug!(
+                "git {} failed in {:?}: {}",
+                args.join(" "),
+                root,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            None
+        }
+        Err(e) => {
+            log::warn!("Failed to run git: {}", e);
+            None
+        }
+    }
+}
+
+/// Internal function to read the uncommitted changes under `root`
+///
+/// Only runs git once `root` is known to be inside a work tree, so folders that aren't
+/// repositories, or machines without git, get empty changes rather than an error. A single
+/// indexed file is looked up in its directory and limited to itself.
+pub(crate) fn git_changes(root: &Path) -> GitChanges {
+    let (dir, pathspec) = match (root.is_file(), root.parent(), root.file_name()) {
+        (true, Some(parent), Some(name)) => (parent, name.to_string_lossy().into_owned()),
+        _ => (root, ".".to_string()),
+    };
+    let pathspec = pathspec.as_str();
+
+    let inside_work_tree = run_git(dir, &["rev-parse", "--is-inside-work-tree"])
+        .is_some_and(|output| output.trim() == "true");
+    if !inside_work_tree {
+        return GitChanges::default();
+    }
+
+    let files = run_git(dir, &["status", "--porcelain", "-z", "--", pathspec])
+        .map(|output| parse_porcelain(&output))
+        .unwrap_or_default();
+    // Fails in a repository without commits yet, which has no diff against HEAD either
+    let diff = run_git(dir, &["diff", "HEAD", "--", pathspec]).unwrap_or_default();
+
+    GitChanges {
+        is_repository: true,
+        files,
+        diff,
+    }
+}
+
+/// List the uncommitted changes and diff of the git repository containing `root`
+///
+/// Returns empty changes with `is_repository` unset when `root` isn't inside one.
+#[tauri::command]
+pub async fn get_git_changes(root: String) -> CommandResult<GitChanges> {
+    let root_path = PathBuf::from(&root);
+    if !root_path.exists() {
+        return Err(CommandError::new(
+            CommandError::NOT_FOUND,
+            format!("Path does not exist: {}", root),
+        ));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || git_changes(&root_path))
+        .await
+        .map_err(|e| CommandError::new(CommandError::UNKNOWN, format!("Git task failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_porcelain() {
+        let output = " M src/lib.rs\0A  docs/new file.md\0R  src/new.rs\0src/old.rs\0?? notes/\0";
+
+        let changes = parse_porcelain(output);
+        assert_eq!(changes.len(), 4);
+        assert_eq!(changes[0].status, " M");
+        assert_eq!(changes[0].path, "src/lib.rs");
+        assert_eq!(changes[1].path, "docs/new file.md");
+        assert_eq!(changes[2].status, "R ");
+        assert_eq!(changes[2].path, "src/new.rs");
+        assert_eq!(changes[2].original_path.as_deref(), Some("src/old.rs"));
+        assert_eq!(changes[3].status, "??");
+        assert_eq!(changes[3].original_path, None);
+
+        assert!(parse_porcelain("").is_empty());
+        assert_eq!(parse_porcelain("x\0 M a.rs\0").len(), 1);
+    }
+
+    #[test]
+    fn test_prompt_section() {
+        let changes = GitChanges {
+            is_repository: true,
+            files: parse_porcelain(" M src/lib.rs\0R  b.rs\0a.rs\0"),
+            diff: "diff --git a/src/lib.rs b/src/lib.rs\n-old\n+new\n".to_string(),
+        };
+        assert_eq!(
+            changes.to_prompt_section("/repo"),
+            "Uncommitted changes in /repo:\n M src/lib.rs\nR  a.rs -> b.rs\n\n```diff\ndiff --git a/src/lib.rs b/src/lib.rs\n-old\n+new\n```"
+        );
+
+        let clean = GitChanges {
+            is_repository: true,
+            ..GitChanges::default()
+        };
+        assert_eq!(
+            clean.to_prompt_section("/repo"),
+            "No uncommitted changes in /repo."
+        );
+        assert_eq!(GitChanges::default().to_prompt_section("/repo"), "");
+    }
+
+    #[test]
+    fn test_git_changes_outside_repository() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(git_changes(temp_dir.path()), GitChanges::default());
+    }
+}
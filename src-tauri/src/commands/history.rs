@@ -1,6 +1,7 @@
+use super::settings::{get_setting_typed, AppSettings};
 use crate::db::DbConnection;
 use crate::error::AppError;
-use rusqlite::params;
+use rusqlite::{params, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -23,25 +24,71 @@ pub struct ValidationResult {
 }
 
 /// Internal function to save history
+///
+/// With `dedupe_consecutive`, saving the same session as the most recent entry only bumps
+/// that entry's `created_at` and returns its id instead of inserting a duplicate.
 fn save_history_internal(
     db: &DbConnection,
     root_paths: &[String],
     selected_paths: &[String],
     template_id: Option<&str>,
     custom_prompt: Option<&str>,
+    dedupe_consecutive: bool,
 ) -> Result<i64, String> {
     let conn = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
 
+    let created_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+
+    let root_paths_json = serde_json::to_string(&root_paths)
+        .map_err(|e| format!("Failed to serialize root_paths: {}", e))?;
+    let selected_paths_json = serde_json::to_string(&selected_paths)
+        .map_err(|e| format!("Failed to serialize selected_paths: {}", e))?;
+
+    if dedupe_consecutive {
+        // Only the most recent entry counts; IS compares the nullable columns
+        let duplicate_id: Option<i64> = conn
+            .query_row(
+                "SELECT id FROM (
+                    SELECT id, root_paths, selected_paths, template_id, custom_prompt
+                    FROM history ORDER BY created_at DESC, id DESC LIMIT 1
+                 )
+                 WHERE root_paths = ?1 AND selected_paths = ?2
+                   AND template_id IS ?3 AND custom_prompt IS ?4",
+                params![
+                    root_paths_json,
+                    selected_paths_json,
+                    template_id,
+                    custom_prompt
+                ],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to query latest history entry: {}", e))?;
+
+        if let Some(id) = duplicate_id {
+            conn.execute(
+                "UPDATE history SET created_at = ?1 WHERE id = ?2",
+                params![created_at, id],
+            )
+            .map_err(|e| format!("Failed to update history entry: {}", e))?;
+            return Ok(id);
+        }
+    }
+
     // Get current count
     let count: i64 = conn
         .query_row("SELECT COUNT(*) FROM history", [], |row| row.get(0))
         .map_err(|e| format!("Failed to count history entries: {}", e))?;
 
-    // If we have 10 or more entries, delete the oldest
+    // If we have 10 or more entries, delete the oldest. Timestamps have second resolution,
+    // so ties are broken by id, which grows with every insert
     if count >= 10 {
         conn.execute(
             "DELETE FROM history WHERE id IN (
-                SELECT id FROM history ORDER BY created_at ASC LIMIT ?1
+                SELECT id FROM history ORDER BY created_at ASC, id ASC LIMIT ?1
             )",
             params![count - 9],
         )
@@ -49,16 +96,6 @@ fn save_history_internal(
     }
 
     // Insert new entry
-    let created_at = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64;
-
-    let root_paths_json = serde_json::to_string(&root_paths)
-        .map_err(|e| format!("Failed to serialize root_paths: {}", e))?;
-    let selected_paths_json = serde_json::to_string(&selected_paths)
-        .map_err(|e| format!("Failed to serialize selected_paths: {}", e))?;
-
     conn.execute(
         "INSERT INTO history (created_at, root_paths, selected_paths, template_id, custom_prompt)
          VALUES (?1, ?2, ?3, ?4, ?5)",
@@ -77,6 +114,7 @@ fn save_history_internal(
 
 /// Save a new history entry to the database
 /// Maintains a maximum of 10 entries (FIFO eviction)
+/// When auto-save is on, an identical consecutive save refreshes the latest entry instead
 #[tauri::command]
 pub async fn save_history(
     db: tauri::State<'_, DbConnection>,
@@ -85,12 +123,18 @@ pub async fn save_history(
     template_id: Option<String>,
     custom_prompt: Option<String>,
 ) -> Result<i64, String> {
+    let auto_save = get_setting_typed::<bool>(&db, "auto_save_history")
+        .ok()
+        .flatten()
+        .unwrap_or(AppSettings::default().auto_save_history);
+
     save_history_internal(
         &db,
         &root_paths,
         &selected_paths,
         template_id.as_deref(),
         custom_prompt.as_deref(),
+        auto_save,
     )
 }
 
@@ -99,7 +143,7 @@ fn load_history_internal(db: &DbConnection) -> Result<Vec<HistoryEntry>, String>
     let conn = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
 
     let mut stmt = conn
-        .prepare("SELECT id, created_at, root_paths, selected_paths, template_id, custom_prompt FROM history ORDER BY created_at DESC")
+        .prepare("SELECT id, created_at, root_paths, selected_paths, template_id, custom_prompt FROM history ORDER BY created_at DESC, id DESC")
         .map_err(|e| format!("Failed to prepare statement: {}", e))?;
 
     let entries = stmt
@@ -217,6 +261,7 @@ mod tests {
             &selected_paths,
             template_id,
             custom_prompt,
+            true,
         )
         .unwrap();
 
@@ -234,43 +279,91 @@ mod tests {
     fn test_history_fifo_eviction() {
         let db = setup_test_db();
 
-        // Add 12 entries
-        for i in 0..12 {
+        let save = |i: usize| {
             save_history_internal(
                 &db,
                 &[format!("/test/path{}", i)],
                 &[format!("/test/path{}/file.txt", i)],
                 None,
                 None,
+                true,
             )
+            .unwrap()
+        };
+
+        // Fill the history with entries sharing one timestamp, then add two more
+        let mut ids: Vec<i64> = (0..10).map(&save).collect();
+        db.lock()
+            .unwrap()
+            .execute("UPDATE history SET created_at = 1000", [])
             .unwrap();
-        }
+        ids.extend((10..12).map(&save));
 
-        // Should only have 10 entries
+        // The two oldest entries are evicted; the rest load newest first, ties broken by id
         let history = load_history_internal(&db).unwrap();
-        assert_eq!(history.len(), 10);
-
-        // Should have the 10 most recent entries (paths 2-11)
-        // The oldest entries (0 and 1) should be evicted
-        // Note: We check the set of paths rather than order, as entries created
-        // at the same timestamp may have undefined order
-        let paths: std::collections::HashSet<_> = history
-            .iter()
-            .map(|h| h.root_paths[0].clone())
-            .collect();
-
-        // Paths 0 and 1 should be evicted
-        assert!(!paths.contains("/test/path0"), "path0 should be evicted");
-        assert!(!paths.contains("/test/path1"), "path1 should be evicted");
-
-        // Paths 2-11 should remain
-        for i in 2..12 {
-            assert!(
-                paths.contains(&format!("/test/path{}", i)),
-                "path{} should remain",
-                i
-            );
-        }
+        let surviving: Vec<i64> = history.iter().map(|h| h.id.unwrap()).collect();
+        let expected: Vec<i64> = ids[2..].iter().rev().copied().collect();
+        assert_eq!(surviving, expected);
+    }
+
+    #[test]
+    fn test_identical_consecutive_saves_are_deduplicated() {
+        let db = setup_test_db();
+        let root_paths = vec!["/test/path".to_string()];
+        let selected_paths = vec!["/test/path/file.txt".to_string()];
+
+        let first =
+            save_history_internal(&db, &root_paths, &selected_paths, Some("agent"), None, true)
+                .unwrap();
+        // Backdate the first save so the refreshed timestamp is observable
+        db.lock()
+            .unwrap()
+            .execute(
+                "UPDATE history SET created_at = 1000 WHERE id = ?1",
+                params![first],
+            )
+            .unwrap();
+
+        let second =
+            save_history_internal(&db, &root_paths, &selected_paths, Some("agent"), None, true)
+                .unwrap();
+
+        assert_eq!(first, second);
+        let history = load_history_internal(&db).unwrap();
+        assert_eq!(history.len(), 1);
+        assert!(history[0].created_at > 1000);
+    }
+
+    #[test]
+    fn test_distinct_or_undeduplicated_saves_insert_new_entries() {
+        let db = setup_test_db();
+        let root_paths = vec!["/test/path".to_string()];
+        let selected_paths = vec!["/test/path/file.txt".to_string()];
+
+        save_history_internal(&db, &root_paths, &selected_paths, Some("agent"), None, true)
+            .unwrap();
+        // Different template is a different session
+        save_history_internal(
+            &db,
+            &root_paths,
+            &selected_paths,
+            Some("planner"),
+            None,
+            true,
+        )
+        .unwrap();
+        // Without dedupe (auto-save off) identical saves are kept
+        save_history_internal(
+            &db,
+            &root_paths,
+            &selected_paths,
+            Some("planner"),
+            None,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(load_history_internal(&db).unwrap().len(), 3);
     }
 
     #[tokio::test]
@@ -302,6 +395,7 @@ mod tests {
             &["/test/path/file.txt".to_string()],
             None,
             None,
+            true,
         )
         .unwrap();
 
@@ -323,6 +417,7 @@ mod tests {
                 &[format!("/test/path{}/file.txt", i)],
                 None,
                 None,
+                true,
             )
             .unwrap();
         }
@@ -1,19 +1,24 @@
+use crate::dates::days_from_civil;
 use crate::db::DbConnection;
-use crate::error::{AppError, AppResult};
-use crate::gitignore::GitignoreManager;
+use crate::error::{AppError, AppResult, CommandError, CommandResult};
+use crate::gitignore::{GitignoreManager, IgnoreMatch};
+use crate::language::detect_language;
+use crate::paths::{get_parent_path, is_within, normalize_path};
+use crate::project_config::ProjectConfig;
 use rayon::prelude::*;
 use rusqlite::{params, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant, SystemTime};
 use tauri::{AppHandle, Emitter};
 use walkdir::WalkDir;
 // NOTE: Race condition fixed by always storing true parent_path and updating orphaned children when parent is indexed.
 
-use super::settings::load_settings_internal;
+use super::settings::{get_setting_typed, AppSettings, INDEX_BATCH_SIZE_RANGE};
 
 /// Progress information for indexing operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +29,34 @@ pub struct IndexProgress {
     pub errors: u64,
 }
 
+/// Maximum number of failures listed individually in an `IndexSummary`
+pub const MAX_INDEX_ERRORS: usize = 100;
+
+/// An entry that could not be indexed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexError {
+    /// Path of the entry; empty when the failure has no path
+    pub path: String,
+    pub message: String,
+}
+
+/// Outcome of an `index_folder` run
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct IndexSummary {
+    /// Entries that were not in the index before
+    pub inserted: u64,
+    /// Existing entries whose metadata changed
+    pub updated: u64,
+    /// Entries skipped by ignore rules
+    pub ignored: u64,
+    /// .gitignore files found in the tree and applied
+    pub gitignore_files: u64,
+    /// Total failures, which may exceed `errors.len()`
+    pub error_count: u64,
+    /// The first `MAX_INDEX_ERRORS` failures
+    pub errors: Vec<IndexError>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileEntry {
     pub path: String,
@@ -35,9 +68,17 @@ pub struct FileEntry {
     pub token_count: Option<i64>,
     pub fingerprint: Option<String>,
     pub child_count: Option<i64>,
+    /// Resolved target path when the entry was reached through a symlink
+    pub symlink_target: Option<String>,
+    /// Directory sits at the index depth limit, so its contents were not indexed
+    pub truncated_at_depth: bool,
+    /// Language detected from the file name or extension; `None` for directories
+    pub language: Option<String>,
+    /// Path was not valid UTF-8 and is stored with invalid bytes replaced by U+FFFD
+    pub lossy_path: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
     pub path: String,
     pub parent_path: Option<String>,
@@ -48,12 +89,38 @@ pub struct SearchResult {
     pub token_count: Option<i64>,
     pub fingerprint: Option<String>,
     pub child_count: Option<i64>,
+    pub language: Option<String>,
     pub score: i32,
+    /// Byte ranges `(start, end)` into `path` where the query matched. Ranges fall inside the
+    /// trailing `name` when the name matched, otherwise anywhere in the path.
+    #[serde(default)]
+    pub match_ranges: Vec<(usize, usize)>,
 }
 
-/// Normalize path separators to forward slashes for cross-platform consistency
-fn normalize_path(path: &str) -> String {
-    path.replace('\\', "/")
+/// Normalized string form of a path for the index
+///
+/// Non-UTF-8 paths (arbitrary bytes on Linux) are converted lossily instead of being rejected,
+/// so their files stay indexed; the returned flag tells whether anything was replaced.
+fn lossy_path_string(path: &Path) -> (String, bool) {
+    match path.to_str() {
+        Some(path) => (normalize_path(path), false),
+        None => (normalize_path(&path.to_string_lossy()), true),
+    }
+}
+
+/// Language of a file for the index; directories have none
+fn detect_file_language(path: &Path, is_dir: bool) -> Option<String> {
+    if is_dir {
+        return None;
+    }
+    detect_language(path).map(str::to_string)
+}
+
+/// Resolve the final target of a symlink, normalized like every other stored path
+fn resolve_symlink_target(path: &Path) -> Option<String> {
+    fs::canonicalize(path)
+        .ok()
+        .and_then(|target| target.to_str().map(normalize_path))
 }
 
 impl FileEntry {
@@ -61,15 +128,10 @@ impl FileEntry {
         let metadata = fs::metadata(path)?;
         let name = path
             .file_name()
-            .and_then(|n| n.to_str())
-            .ok_or_else(|| AppError::Path("Invalid file name".to_string()))?
-            .to_string();
-
-        let path_str = normalize_path(
-            path
-                .to_str()
-                .ok_or_else(|| AppError::Path("Invalid path".to_string()))?
-        );
+            .map(|n| n.to_string_lossy().into_owned())
+            .ok_or_else(|| AppError::Path("Invalid file name".to_string()))?;
+
+        let (path_str, lossy_path) = lossy_path_string(path);
 
         // Normalize parent_path for consistency
         let normalized_parent = parent_path.map(|p| normalize_path(&p));
@@ -88,6 +150,15 @@ impl FileEntry {
 
         let fingerprint = size.and_then(|s| mtime.map(|m| format!("{}_{}", m, s)));
 
+        let is_symlink = fs::symlink_metadata(path)
+            .map(|m| m.file_type().is_symlink())
+            .unwrap_or(false);
+        let symlink_target = if is_symlink {
+            resolve_symlink_target(path)
+        } else {
+            None
+        };
+
         Ok(FileEntry {
             path: path_str,
             parent_path: normalized_parent,
@@ -98,9 +169,21 @@ impl FileEntry {
             token_count: None,
             fingerprint,
             child_count: None,
+            symlink_target,
+            truncated_at_depth: false,
+            language: detect_file_language(path, metadata.is_dir()),
+            lossy_path,
         })
     }
 
+    /// Create FileEntry from an entry yielded by `filtered_walk` with the given depth limit
+    fn from_walk_entry(entry: &walkdir::DirEntry, max_depth: usize) -> AppResult<Self> {
+        let mut file_entry = Self::from_dir_entry(entry)?;
+        file_entry.truncated_at_depth =
+            max_depth > 0 && file_entry.is_dir && entry.depth() == max_depth;
+        Ok(file_entry)
+    }
+
     /// Create FileEntry from walkdir::DirEntry
     fn from_dir_entry(entry: &walkdir::DirEntry) -> AppResult<Self> {
         let path = entry.path();
@@ -108,21 +191,13 @@ impl FileEntry {
 
         let name = path
             .file_name()
-            .and_then(|n| n.to_str())
-            .ok_or_else(|| AppError::Path("Invalid file name".to_string()))?
-            .to_string();
-
-        let path_str = normalize_path(
-            path
-                .to_str()
-                .ok_or_else(|| AppError::Path("Invalid path".to_string()))?
-        );
+            .map(|n| n.to_string_lossy().into_owned())
+            .ok_or_else(|| AppError::Path("Invalid file name".to_string()))?;
+
+        let (path_str, lossy_path) = lossy_path_string(path);
 
-        // Compute parent path from the file path and normalize it
-        let parent_path = path
-            .parent()
-            .and_then(|p| p.to_str())
-            .map(|s| normalize_path(s));
+        // Compute parent path from the normalized file path
+        let parent_path = get_parent_path(&path_str);
 
         let size = if metadata.is_file() {
             Some(metadata.len() as i64)
@@ -138,6 +213,12 @@ impl FileEntry {
 
         let fingerprint = size.and_then(|s| mtime.map(|m| format!("{}_{}", m, s)));
 
+        let symlink_target = if entry.path_is_symlink() {
+            resolve_symlink_target(path)
+        } else {
+            None
+        };
+
         Ok(FileEntry {
             path: path_str,
             parent_path,
@@ -148,99 +229,642 @@ impl FileEntry {
             token_count: None,
             fingerprint,
             child_count: None,
+            symlink_target,
+            truncated_at_depth: false,
+            language: detect_file_language(path, metadata.is_dir()),
+            lossy_path,
         })
     }
 }
 
+/// Tauri-managed flag that is set while an `index_folder` call is running.
+///
+/// Overlapping runs would race on the database and on orphan reconciliation, so a second
+/// call is rejected instead of interleaving with the first.
+#[derive(Debug, Default)]
+pub struct IndexingState {
+    active: AtomicBool,
+}
+
+impl IndexingState {
+    /// Claim the flag for one indexing run; fails with `BUSY` if another run holds it
+    fn try_begin(&self) -> CommandResult<IndexingGuard<'_>> {
+        self.active
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .map_err(|_| {
+                CommandError::new(CommandError::BUSY, "Indexing is already in progress")
+            })?;
+        Ok(IndexingGuard { state: self })
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Acquire)
+    }
+}
+
+/// Releases the indexing flag when dropped, so errors and panics clear it too
+struct IndexingGuard<'a> {
+    state: &'a IndexingState,
+}
+
+impl Drop for IndexingGuard<'_> {
+    fn drop(&mut self) {
+        self.state.active.store(false, Ordering::Release);
+    }
+}
+
+/// Whether an indexing run is in progress, so the UI can disable indexing actions
+#[tauri::command]
+pub async fn is_indexing(indexing: tauri::State<'_, IndexingState>) -> CommandResult<bool> {
+    Ok(indexing.is_active())
+}
+
 /// Index a folder and its contents into the database with parallel processing
+///
+/// A single file can be indexed too; it becomes a root of its own. Entries that can't be
+/// read don't fail the run; they are listed in the summary instead.
+/// Fails with `BUSY` while another `index_folder` call is still running.
 #[tauri::command]
 pub async fn index_folder(
     path: String,
     app: AppHandle,
     db: tauri::State<'_, DbConnection>,
-) -> Result<u64, String> {
+    indexing: tauri::State<'_, IndexingState>,
+    search_cache: tauri::State<'_, SearchCache>,
+) -> CommandResult<IndexSummary> {
     log::info!("Indexing folder: {}", path);
 
     let path_buf = PathBuf::from(&path);
     if !path_buf.exists() {
-        return Err(format!("Path does not exist: {}", path));
+        return Err(CommandError::new(
+            CommandError::NOT_FOUND,
+            format!("Path does not exist: {}", path),
+        ));
     }
 
+    let _guard = indexing.try_begin()?;
+
     // Use parallel traversal and batch inserts
-    let count = parallel_index_folder(&path_buf, &app, &db)
-        .map_err(|e| format!("Failed to index folder: {}", e))?;
+    let summary = parallel_index_folder(&path_buf, &app, &db, &search_cache)
+        .map_err(|e| CommandError::new(e.code(), format!("Failed to index folder: {}", e)))?;
 
-    log::info!("Indexed {} entries from {}", count, path);
-    Ok(count)
+    log::info!(
+        "Indexed {} new and {} changed entries from {}",
+        summary.inserted,
+        summary.updated,
+        path
+    );
+    Ok(summary)
+}
+
+/// Column the file tree can be sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortKey {
+    #[default]
+    Name,
+    Size,
+    Mtime,
+    TokenCount,
+}
+
+impl SortKey {
+    /// Column name for this key; the only way sort input reaches SQL
+    fn column(self) -> &'static str {
+        match self {
+            SortKey::Name => "name",
+            SortKey::Size => "size",
+            SortKey::Mtime => "mtime",
+            SortKey::TokenCount => "token_count",
+        }
+    }
+}
+
+/// Build the ORDER BY clause for child listings.
+///
+/// Name sorting keeps directories first; other keys fall back to directories-first and name
+/// so entries with equal values keep a stable order. The unique path is always the final
+/// tie-breaker so pagination is deterministic across calls.
+fn children_order_by(sort_by: SortKey, descending: bool) -> String {
+    let direction = if descending { "DESC" } else { "ASC" };
+
+    match sort_by {
+        SortKey::Name => format!("is_dir DESC, name {}, path ASC", direction),
+        key => format!(
+            "{} {}, is_dir DESC, name ASC, path ASC",
+            key.column(),
+            direction
+        ),
+    }
+}
+
+/// One page of a node's children together with the total number of children
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChildrenPage {
+    pub entries: Vec<FileEntry>,
+    pub total: i64,
+    pub offset: u32,
+}
+
+/// SQL filter selecting the children of `parent_path`.
+///
+/// For root queries (parent_path IS NULL), return every folder passed to `index_folder`, so a
+/// root stays at the top level even after an ancestor is indexed too. Orphaned entries whose
+/// parent_path points to a non-existent path in the database are also included.
+/// This ensures files indexed before their parent folder still appear at root level.
+fn children_filter(parent_path: Option<&str>) -> &'static str {
+    if parent_path.is_none() {
+        "WHERE is_root = 1
+            OR parent_path IS NULL
+            OR (parent_path IS NOT NULL AND NOT EXISTS (SELECT 1 FROM files f2 WHERE f2.path = files.parent_path))"
+    } else {
+        "WHERE parent_path = ?"
+    }
+}
+
+/// Paths passed to `index_folder`
+///
+/// Folders indexed before roots were flagged have no flag, so unflagged top-level entries
+/// count as roots too unless they lie inside a flagged root. That keeps an upgraded index
+/// whole after only some of its folders are indexed again.
+pub(crate) fn indexed_roots(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT path, is_root FROM files {}",
+        children_filter(None)
+    ))?;
+    let top_level = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)? != 0))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let flagged: Vec<&str> = top_level
+        .iter()
+        .filter(|(_, is_root)| *is_root)
+        .map(|(path, _)| path.as_str())
+        .collect();
+    let roots = top_level
+        .iter()
+        .filter(|(path, is_root)| *is_root || !flagged.iter().any(|root| is_within(path, root)))
+        .map(|(path, _)| path.clone())
+        .collect();
+    Ok(roots)
+}
+
+fn file_entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<FileEntry> {
+    Ok(FileEntry {
+        path: row.get(0)?,
+        parent_path: row.get(1)?,
+        name: row.get(2)?,
+        size: row.get(3)?,
+        mtime: row.get(4)?,
+        is_dir: row.get::<_, i32>(5)? != 0,
+        token_count: row.get(6)?,
+        fingerprint: row.get(7)?,
+        child_count: row.get(8)?,
+        symlink_target: row.get(9)?,
+        truncated_at_depth: row.get::<_, Option<i32>>(10)?.unwrap_or(0) != 0,
+        language: row.get(11)?,
+        lossy_path: row.get::<_, Option<i32>>(12)?.unwrap_or(0) != 0,
+    })
+}
+
+/// Internal function to list the children of a node, optionally limited to `(offset, limit)`
+fn get_children_internal(
+    conn: &rusqlite::Connection,
+    parent_path: Option<&str>,
+    sort_by: SortKey,
+    descending: bool,
+    page: Option<(u32, u32)>,
+) -> rusqlite::Result<Vec<FileEntry>> {
+    let mut query = format!(
+        "SELECT path, parent_path, name, size, mtime, is_dir, token_count, fingerprint,
+         (SELECT COUNT(*) FROM files f2 WHERE f2.parent_path = files.path) as child_count,
+         symlink_target, truncated_at_depth, language, lossy_path
+         FROM files
+         {}
+         ORDER BY {}",
+        children_filter(parent_path),
+        children_order_by(sort_by, descending)
+    );
+
+    if let Some((offset, limit)) = page {
+        query.push_str(&format!(" LIMIT {} OFFSET {}", limit, offset));
+    }
+
+    let mut stmt = conn.prepare(&query)?;
+
+    let entries = match parent_path {
+        None => stmt.query_map([], file_entry_from_row)?,
+        Some(parent) => stmt.query_map(params![parent], file_entry_from_row)?,
+    }
+    .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(entries)
+}
+
+/// Internal function to count the children of a node
+fn count_children_internal(
+    conn: &rusqlite::Connection,
+    parent_path: Option<&str>,
+) -> rusqlite::Result<i64> {
+    let query = format!(
+        "SELECT COUNT(*) FROM files {}",
+        children_filter(parent_path)
+    );
+
+    match parent_path {
+        None => conn.query_row(&query, [], |row| row.get(0)),
+        Some(parent) => conn.query_row(&query, params![parent], |row| row.get(0)),
+    }
 }
 
 /// Get children of a specific node by parent path
+///
+/// Sorted by name with directories first unless `sort_by` / `descending` are given.
 #[tauri::command]
 pub async fn get_children(
     parent_path: Option<String>,
+    sort_by: Option<SortKey>,
+    descending: Option<bool>,
     db: tauri::State<'_, DbConnection>,
-) -> Result<Vec<FileEntry>, String> {
+) -> CommandResult<Vec<FileEntry>> {
     log::debug!("Getting children for parent_path: {:?}", parent_path);
 
-    let conn = db.lock().map_err(|e| format!("Failed to lock database: {}", e))?;
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+
+    Ok(get_children_internal(
+        &conn,
+        parent_path.as_deref(),
+        sort_by.unwrap_or_default(),
+        descending.unwrap_or(false),
+        None,
+    )?)
+}
+
+/// Get one page of a node's children, for virtualizing very large directories
+///
+/// Uses the same ordering as `get_children`, with the path as a final tie-breaker so
+/// consecutive pages never overlap or skip entries.
+#[tauri::command]
+pub async fn get_children_page(
+    parent_path: Option<String>,
+    offset: u32,
+    limit: u32,
+    sort_by: Option<SortKey>,
+    descending: Option<bool>,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<ChildrenPage> {
+    log::debug!(
+        "Getting children page for parent_path: {:?} (offset {}, limit {})",
+        parent_path,
+        offset,
+        limit
+    );
+
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+
+    let entries = get_children_internal(
+        &conn,
+        parent_path.as_deref(),
+        sort_by.unwrap_or_default(),
+        descending.unwrap_or(false),
+        Some((offset, limit)),
+    )?;
+    let total = count_children_internal(&conn, parent_path.as_deref())?;
+
+    Ok(ChildrenPage {
+        entries,
+        total,
+        offset,
+    })
+}
 
-    // For root queries (parent_path IS NULL), also include orphaned entries
-    // whose parent_path points to a non-existent path in the database.
-    // This ensures files indexed before their parent folder still appear at root level.
-    let query = if parent_path.is_none() {
+/// Internal function to list the most recently modified indexed files, newest first
+fn get_recent_files_internal(
+    conn: &rusqlite::Connection,
+    limit: usize,
+) -> rusqlite::Result<Vec<FileEntry>> {
+    let mut stmt = conn.prepare(
         "SELECT path, parent_path, name, size, mtime, is_dir, token_count, fingerprint,
-         (SELECT COUNT(*) FROM files f2 WHERE f2.parent_path = files.path) as child_count
+         NULL as child_count, symlink_target, truncated_at_depth, language, lossy_path
          FROM files
-         WHERE parent_path IS NULL
-            OR (parent_path IS NOT NULL AND NOT EXISTS (SELECT 1 FROM files f2 WHERE f2.path = files.parent_path))
-         ORDER BY is_dir DESC, name ASC"
-    } else {
-        "SELECT path, parent_path, name, size, mtime, is_dir, token_count, fingerprint,
-         (SELECT COUNT(*) FROM files f2 WHERE f2.parent_path = files.path) as child_count
+         WHERE is_dir = 0 AND mtime IS NOT NULL
+         ORDER BY mtime DESC, path
+         LIMIT ?",
+    )?;
+
+    let entries = stmt
+        .query_map(params![limit as i64], file_entry_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(entries)
+}
+
+/// Get the most recently modified indexed files, across every indexed folder
+#[tauri::command]
+pub async fn get_recent_files(
+    limit: usize,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<Vec<FileEntry>> {
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+    Ok(get_recent_files_internal(&conn, limit)?)
+}
+
+/// Files suggested to fit a token budget
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileSuggestion {
+    /// Chosen files, in selection order
+    pub paths: Vec<String>,
+    /// Projected tokens of the chosen files
+    pub total_tokens: i64,
+}
+
+/// Tokens a file is expected to take: its counted `token_count`, else estimated from its size
+fn projected_tokens(token_count: Option<i64>, size: Option<i64>) -> Option<i64> {
+    token_count.or_else(|| size.map(|size| (size + 3) / 4))
+}
+
+/// Internal function to greedily pick files under `dir` that fit in `token_budget`
+///
+/// Source files (those with a detected language) come first, then smaller files, so the
+/// budget goes to as many relevant files as possible. Files too big for what is left are
+/// skipped and smaller ones still considered. Files with neither a token count nor a size
+/// are left out.
+fn suggest_files_internal(
+    conn: &rusqlite::Connection,
+    dir: &str,
+    token_budget: i64,
+) -> rusqlite::Result<FileSuggestion> {
+    let dir = normalize_path(dir);
+    let mut stmt = conn.prepare(
+        "WITH RECURSIVE subtree(path) AS (
+             SELECT path FROM files WHERE path = ?1
+             UNION
+             SELECT files.path FROM files JOIN subtree ON files.parent_path = subtree.path
+         )
+         SELECT path, token_count, size, language IS NOT NULL
          FROM files
-         WHERE parent_path = ?
-         ORDER BY is_dir DESC, name ASC"
-    };
+         WHERE is_dir = 0 AND path IN (SELECT path FROM subtree)",
+    )?;
 
-    let mut stmt = conn.prepare(query).map_err(|e| e.to_string())?;
-
-    let entries = if parent_path.is_none() {
-        stmt.query_map([], |row| {
-            Ok(FileEntry {
-                path: row.get(0)?,
-                parent_path: row.get(1)?,
-                name: row.get(2)?,
-                size: row.get(3)?,
-                mtime: row.get(4)?,
-                is_dir: row.get::<_, i32>(5)? != 0,
-                token_count: row.get(6)?,
-                fingerprint: row.get(7)?,
-                child_count: row.get(8)?,
-            })
-        })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?
-    } else {
-        stmt.query_map(params![parent_path], |row| {
-            Ok(FileEntry {
-                path: row.get(0)?,
-                parent_path: row.get(1)?,
-                name: row.get(2)?,
-                size: row.get(3)?,
-                mtime: row.get(4)?,
-                is_dir: row.get::<_, i32>(5)? != 0,
-                token_count: row.get(6)?,
-                fingerprint: row.get(7)?,
-                child_count: row.get(8)?,
-            })
+    let mut candidates: Vec<(String, i64, bool)> = stmt
+        .query_map(params![dir], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                projected_tokens(row.get(1)?, row.get(2)?),
+                row.get::<_, bool>(3)?,
+            ))
+        })?
+        .filter_map(|row| match row {
+            Ok((path, Some(tokens), is_source)) => Some(Ok((path, tokens, is_source))),
+            Ok(_) => None,
+            Err(e) => Some(Err(e)),
         })
-        .map_err(|e| e.to_string())?
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()?;
+
+    candidates.sort_by(
+        |(a_path, a_tokens, a_source), (b_path, b_tokens, b_source)| {
+            b_source
+                .cmp(a_source)
+                .then(a_tokens.cmp(b_tokens))
+                .then(a_path.cmp(b_path))
+        },
+    );
+
+    let mut suggestion = FileSuggestion {
+        paths: Vec::new(),
+        total_tokens: 0,
     };
+    for (path, tokens, _) in candidates {
+        if suggestion.total_tokens + tokens <= token_budget {
+            suggestion.total_tokens += tokens;
+            suggestion.paths.push(path);
+        }
+    }
+    Ok(suggestion)
+}
 
-    Ok(entries)
+/// Suggest indexed files under `dir` to include within `token_budget` tokens
+#[tauri::command]
+pub async fn suggest_files(
+    dir: String,
+    token_budget: i64,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<FileSuggestion> {
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+    Ok(suggest_files_internal(&conn, &dir, token_budget)?)
+}
+
+/// Token count of one selected path: the file itself, or every file below a folder
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PathTokens {
+    pub path: String,
+    pub tokens: i64,
+    /// Files counted for this path; 0 when the path isn't indexed
+    pub file_count: i64,
+    /// Files without a token count yet, which contribute nothing to `tokens`
+    pub uncounted_files: i64,
+}
+
+/// Token count of a whole selection
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SelectionTokens {
+    /// Total over the distinct files selected; a file reached through several selected
+    /// paths counts once
+    pub total_tokens: i64,
+    pub file_count: i64,
+    pub uncounted_files: i64,
+    /// One entry per selected path, in request order; these totals may overlap
+    pub paths: Vec<PathTokens>,
+}
+
+/// Indexed files at or below `path` with their token counts
+fn subtree_file_tokens(
+    conn: &rusqlite::Connection,
+    path: &str,
+) -> rusqlite::Result<Vec<(String, Option<i64>)>> {
+    let mut stmt = conn.prepare_cached(
+        "WITH RECURSIVE subtree(path) AS (
+             SELECT path FROM files WHERE path = ?1
+             UNION
+             SELECT files.path FROM files JOIN subtree ON files.parent_path = subtree.path
+         )
+         SELECT path, token_count
+         FROM files
+         WHERE is_dir = 0 AND path IN (SELECT path FROM subtree)",
+    )?;
+    let files = stmt
+        .query_map(params![path], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect();
+    files
+}
+
+/// Internal function to total the token counts of the selected files and folders
+fn count_selection_tokens_internal(
+    conn: &rusqlite::Connection,
+    paths: &[String],
+) -> rusqlite::Result<SelectionTokens> {
+    let mut selected: HashMap<String, Option<i64>> = HashMap::new();
+    let mut breakdown = Vec::with_capacity(paths.len());
+
+    for path in paths {
+        let path = normalize_path(path);
+        let files = subtree_file_tokens(conn, &path)?;
+
+        breakdown.push(PathTokens {
+            tokens: files.iter().filter_map(|(_, tokens)| *tokens).sum(),
+            file_count: files.len() as i64,
+            uncounted_files: files.iter().filter(|(_, tokens)| tokens.is_none()).count() as i64,
+            path,
+        });
+        selected.extend(files);
+    }
+
+    Ok(SelectionTokens {
+        total_tokens: selected.values().flatten().sum(),
+        file_count: selected.len() as i64,
+        uncounted_files: selected.values().filter(|tokens| tokens.is_none()).count() as i64,
+        paths: breakdown,
+    })
+}
+
+/// Total token count of a selection of files and folders, with a per-path breakdown
+#[tauri::command]
+pub async fn count_selection_tokens(
+    paths: Vec<String>,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<SelectionTokens> {
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+    Ok(count_selection_tokens_internal(&conn, &paths)?)
+}
+
+/// Detail shown after each file in a rendered tree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TreeAnnotation {
+    #[default]
+    None,
+    Size,
+    Tokens,
+}
+
+/// Human-readable byte count, e.g. `512 B` or `1.5 KB`
+fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Append the children of `parent` to `out` as tree lines below `prefix`
+fn render_tree_children(
+    conn: &rusqlite::Connection,
+    parent: &str,
+    prefix: &str,
+    depth: usize,
+    max_depth: Option<usize>,
+    annotation: TreeAnnotation,
+    out: &mut String,
+) -> rusqlite::Result<()> {
+    if max_depth.is_some_and(|max_depth| depth >= max_depth) {
+        return Ok(());
+    }
+
+    let children = get_children_internal(conn, Some(parent), SortKey::Name, false, None)?;
+
+    let count = children.len();
+    for (i, child) in children.into_iter().enumerate() {
+        let last = i + 1 == count;
+        out.push_str(prefix);
+        out.push_str(if last { "└── " } else { "├── " });
+        out.push_str(&child.name);
+        if child.is_dir {
+            out.push('/');
+        } else {
+            match (annotation, child.size, child.token_count) {
+                (TreeAnnotation::Size, Some(size), _) => {
+                    out.push_str(&format!(" ({})", format_bytes(size)))
+                }
+                (TreeAnnotation::Tokens, _, Some(tokens)) => {
+                    out.push_str(&format!(" ({} tokens)", tokens))
+                }
+                _ => {}
+            }
+        }
+        out.push('\n');
+
+        if child.is_dir {
+            let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            render_tree_children(
+                conn,
+                &child.path,
+                &child_prefix,
+                depth + 1,
+                max_depth,
+                annotation,
+                out,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Internal function to render the indexed tree under `root` as text
+///
+/// The tree comes from the index, not the filesystem, so it shows exactly what is
+/// indexed. `max_depth` limits how many levels below `root` are listed. Returns `None`
+/// when `root` isn't indexed.
+fn render_file_tree_internal(
+    conn: &rusqlite::Connection,
+    root: &str,
+    max_depth: Option<usize>,
+    annotation: TreeAnnotation,
+) -> rusqlite::Result<Option<String>> {
+    let root = normalize_path(root);
+    let root_entry: Option<(String, bool)> = conn
+        .query_row(
+            "SELECT name, is_dir FROM files WHERE path = ?",
+            params![root],
+            |row| Ok((row.get(0)?, row.get::<_, i32>(1)? != 0)),
+        )
+        .optional()?;
+    let Some((name, is_dir)) = root_entry else {
+        return Ok(None);
+    };
+
+    let mut out = name;
+    if is_dir {
+        out.push('/');
+    }
+    out.push('\n');
+    render_tree_children(conn, &root, "", 0, max_depth, annotation, &mut out)?;
+    Ok(Some(out))
+}
+
+/// Render the indexed tree under `root` as an ASCII tree for pasting into a chat
+#[tauri::command]
+pub async fn render_file_tree(
+    root: String,
+    max_depth: Option<usize>,
+    annotation: Option<TreeAnnotation>,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<String> {
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+    render_file_tree_internal(&conn, &root, max_depth, annotation.unwrap_or_default())?.ok_or_else(
+        || {
+            CommandError::new(
+                CommandError::NOT_FOUND,
+                format!("Path is not indexed: {}", root),
+            )
+        },
+    )
 }
 
 /// Parsed search filters for advanced query syntax
@@ -250,10 +874,166 @@ struct SearchFilter {
     directory_name: Option<String>,
     regex_pattern: Option<regex::Regex>,
     plain_text: Option<String>,
+    size_range: Option<RangeFilter>,
+    mtime_range: Option<RangeFilter>,
+}
+
+/// Inclusive bounds from a `size:` (bytes) or `modified:` (Unix seconds) filter; `None`
+/// leaves that side open
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct RangeFilter {
+    min: Option<i64>,
+    max: Option<i64>,
+}
+
+/// Parse a human-readable size such as `512`, `100kb` or `1.5mb` into bytes (1kb = 1024 bytes)
+fn parse_size(value: &str) -> Option<i64> {
+    let value = value.trim().to_lowercase();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let multiplier: f64 = match unit {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1024.0,
+        "m" | "mb" => 1024.0 * 1024.0,
+        "g" | "gb" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    let number: f64 = number.parse().ok()?;
+    Some((number * multiplier).round() as i64)
+}
+
+/// Parse the expression after `size:`: `>1mb`, `>=1mb`, `<100kb`, `<=100kb`, `10kb..1mb` or an
+/// exact size
+fn parse_size_range(expr: &str) -> Option<RangeFilter> {
+    if let Some((min, max)) = expr.split_once("..") {
+        return Some(RangeFilter {
+            min: Some(parse_size(min)?),
+            max: Some(parse_size(max)?),
+        });
+    }
+
+    if let Some(rest) = expr.strip_prefix(">=") {
+        Some(RangeFilter {
+            min: Some(parse_size(rest)?),
+            max: None,
+        })
+    } else if let Some(rest) = expr.strip_prefix('>') {
+        Some(RangeFilter {
+            min: Some(parse_size(rest)? + 1),
+            max: None,
+        })
+    } else if let Some(rest) = expr.strip_prefix("<=") {
+        Some(RangeFilter {
+            min: None,
+            max: Some(parse_size(rest)?),
+        })
+    } else if let Some(rest) = expr.strip_prefix('<') {
+        Some(RangeFilter {
+            min: None,
+            max: Some(parse_size(rest)? - 1),
+        })
+    } else {
+        let size = parse_size(expr)?;
+        Some(RangeFilter {
+            min: Some(size),
+            max: Some(size),
+        })
+    }
+}
+
+/// Parse a relative duration such as `24h`, `7d` or `2w` into seconds
+fn parse_duration(value: &str) -> Option<i64> {
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let (number, unit) = value.split_at(split);
+    let multiplier = match unit {
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    number.parse::<i64>().ok()?.checked_mul(multiplier)
+}
+
+/// Parse an ISO `YYYY-MM-DD` date into the Unix time of its first second (UTC)
+fn parse_date(value: &str) -> Option<i64> {
+    let mut parts = value.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || day < 1 || i64::from(day) > days_in_month(year, month) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day) * 24 * 60 * 60)
+}
+
+/// Number of days in the given month
+fn days_in_month(year: i64, month: u32) -> i64 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    days_from_civil(next_year, next_month, 1) - days_from_civil(year, month, 1)
+}
+
+/// Parse the expression after `modified:` into Unix-second bounds.
+///
+/// Dates compare against whole days: `>2024-01-01` means from 2024-01-02 on, `<2024-01-01`
+/// means up to the end of 2023-12-31, and a bare date matches that day. Durations are ages
+/// relative to `now`: `<7d` is modified within the last 7 days, `>7d` longer ago than that.
+fn parse_modified_range(expr: &str, now: i64) -> Option<RangeFilter> {
+    const DAY: i64 = 24 * 60 * 60;
+
+    let (op, value) = [">=", "<=", ">", "<"]
+        .iter()
+        .find_map(|op| expr.strip_prefix(op).map(|rest| (*op, rest)))
+        .unwrap_or(("", expr));
+
+    if let Some(age) = parse_duration(value) {
+        let cutoff = now - age;
+        return match op {
+            ">" | ">=" => Some(RangeFilter {
+                min: None,
+                max: Some(cutoff),
+            }),
+            _ => Some(RangeFilter {
+                min: Some(cutoff),
+                max: None,
+            }),
+        };
+    }
+
+    let day_start = parse_date(value)?;
+    let day_end = day_start + DAY - 1;
+    Some(match op {
+        ">" => RangeFilter {
+            min: Some(day_end + 1),
+            max: None,
+        },
+        ">=" => RangeFilter {
+            min: Some(day_start),
+            max: None,
+        },
+        "<" => RangeFilter {
+            min: None,
+            max: Some(day_start - 1),
+        },
+        "<=" => RangeFilter {
+            min: None,
+            max: Some(day_end),
+        },
+        _ => RangeFilter {
+            min: Some(day_start),
+            max: Some(day_end),
+        },
+    })
 }
 
 /// Parse a search query into structured filters.
-/// Supports: file:<name>, dir:<name>, regex (auto-detected), plain text
+/// Supports: file:<name>, dir:<name>, size:<range>, modified:<range>, regex (auto-detected),
+/// plain text
 fn parse_search_query(query: &str) -> SearchFilter {
     let trimmed = query.trim();
     if trimmed.is_empty() {
@@ -263,7 +1043,13 @@ fn parse_search_query(query: &str) -> SearchFilter {
     let parts: Vec<&str> = trimmed.split_whitespace().collect();
     let mut file_name = None;
     let mut directory_name = None;
+    let mut size_range = None;
+    let mut mtime_range = None;
     let mut remaining_parts = Vec::new();
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
 
     for part in &parts {
         let lower = part.to_lowercase();
@@ -271,6 +1057,13 @@ fn parse_search_query(query: &str) -> SearchFilter {
             file_name = Some(part[5..].to_string());
         } else if lower.starts_with("dir:") && part.len() > 4 {
             directory_name = Some(part[4..].to_string());
+        } else if let Some(range) = lower.strip_prefix("size:").and_then(parse_size_range) {
+            size_range = Some(range);
+        } else if let Some(range) = lower
+            .strip_prefix("modified:")
+            .and_then(|expr| parse_modified_range(expr, now))
+        {
+            mtime_range = Some(range);
         } else {
             remaining_parts.push(*part);
         }
@@ -297,6 +1090,8 @@ fn parse_search_query(query: &str) -> SearchFilter {
         directory_name,
         regex_pattern,
         plain_text,
+        size_range,
+        mtime_range,
     }
 }
 
@@ -306,7 +1101,19 @@ fn parse_search_query(query: &str) -> SearchFilter {
 /// - Name starts with query: +50
 /// - Name contains query: +30
 /// - Path contains query: +10
-fn compute_score(name: &str, path: &str, query: &str, is_dir: bool) -> i32 {
+///
+/// Matches are then nudged by where they live: -1 per directory level below the shallowest
+/// result (at most -5), and up to +3 for entries modified in the last month. Both adjustments
+/// together stay smaller than the gap between tiers, so they only reorder equal-tier matches.
+fn compute_score(
+    name: &str,
+    path: &str,
+    query: &str,
+    is_dir: bool,
+    depth: usize,
+    mtime: Option<i64>,
+    now: i64,
+) -> i32 {
     if query.is_empty() {
         return 0;
     }
@@ -337,43 +1144,274 @@ fn compute_score(name: &str, path: &str, query: &str, is_dir: bool) -> i32 {
         score += 1;
     }
 
+    if score > 0 {
+        score -= depth.min(MAX_DEPTH_PENALTY) as i32;
+        score += recency_bonus(mtime, now);
+        score = score.max(1);
+    }
+
     score
 }
 
-/// Internal search function that operates on a raw connection (testable without Tauri state).
-fn search_db(conn: &rusqlite::Connection, pattern: &str) -> Result<Vec<SearchResult>, String> {
-    let filters = parse_search_query(pattern);
-
-    // Empty query returns nothing
-    if filters.file_name.is_none()
-        && filters.directory_name.is_none()
-        && filters.regex_pattern.is_none()
-        && filters.plain_text.is_none()
-    {
-        return Ok(Vec::new());
+/// Cap on the depth penalty applied by `compute_score`
+const MAX_DEPTH_PENALTY: usize = 5;
+
+/// Directories whose contents are vendored or build output rather than project source
+const GENERATED_DIRS: &[&str] = &["dist", "vendor"];
+
+/// Score nudge that favors project source over generated files: -3 for minified files,
+/// source maps, lockfiles and anything under `dist/` or `vendor/`, +2 for files in a
+/// recognized language.
+///
+/// Like the depth and recency adjustments it is smaller than the gap between score tiers,
+/// so a better name match always wins.
+fn source_adjustment(name: &str, path: &str, is_dir: bool, language: Option<&str>) -> i32 {
+    let name = name.to_lowercase();
+    let generated = name.contains(".min.")
+        || name.ends_with(".map")
+        || name.ends_with(".lock")
+        || name.contains("-lock.")
+        || path
+            .to_lowercase()
+            .split('/')
+            .any(|component| GENERATED_DIRS.contains(&component));
+
+    if generated {
+        -3
+    } else if !is_dir && language.is_some() {
+        2
+    } else {
+        0
     }
+}
 
-    // Build SQL conditions based on parsed filters
-    let mut conditions: Vec<String> = Vec::new();
-    let mut param_values: Vec<String> = Vec::new();
+/// Bonus for recently modified entries: +3 within a day, +2 within a week, +1 within 30 days
+fn recency_bonus(mtime: Option<i64>, now: i64) -> i32 {
+    const DAY: i64 = 24 * 60 * 60;
 
-    if let Some(ref file_name) = filters.file_name {
-        // file: matches FILES by name
-        conditions.push("(is_dir = 0 AND LOWER(name) LIKE ?)".to_string());
-        param_values.push(format!("%{}%", file_name.to_lowercase()));
+    match mtime.map(|mtime| now.saturating_sub(mtime)) {
+        Some(age) if age < DAY => 3,
+        Some(age) if age < 7 * DAY => 2,
+        Some(age) if age < 30 * DAY => 1,
+        _ => 0,
     }
+}
 
-    if let Some(ref dir_name) = filters.directory_name {
-        // dir: matches DIRECTORIES by name
-        conditions.push("(is_dir = 1 AND LOWER(name) LIKE ?)".to_string());
-        param_values.push(format!("%{}%", dir_name.to_lowercase()));
+/// Number of components in a normalized path
+fn path_depth(path: &str) -> usize {
+    path.split('/')
+        .filter(|component| !component.is_empty())
+        .count()
+}
+
+/// Find every non-overlapping, case-insensitive occurrence of `needle` in `haystack`.
+///
+/// Characters are compared after lowercasing but offsets are taken from `haystack` itself,
+/// so ranges stay on char boundaries even where lowercasing changes byte lengths.
+fn find_case_insensitive(haystack: &str, needle: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    if needle.is_empty() {
+        return ranges;
     }
 
-    // When both file: and dir: are present, we need files matching file: that are inside dirs matching dir:
-    if filters.file_name.is_some() && filters.directory_name.is_some() {
-        // Override: find files with name matching file: AND path containing dir:
-        conditions.clear();
-        param_values.clear();
+    let mut search_from = 0;
+    for (start, _) in haystack.char_indices() {
+        if start < search_from {
+            continue;
+        }
+
+        let mut rest = haystack[start..].char_indices();
+        let mut end = None;
+        let mut needle_chars = needle.chars();
+        loop {
+            let Some(n) = needle_chars.next() else {
+                end = Some(rest.next().map_or(haystack.len(), |(i, _)| start + i));
+                break;
+            };
+            match rest.next() {
+                Some((_, h)) if h.to_lowercase().eq(n.to_lowercase()) => {}
+                _ => break,
+            }
+        }
+
+        if let Some(end) = end {
+            ranges.push((start, end));
+            search_from = end;
+        }
+    }
+    ranges
+}
+
+/// Score of each query character matched in fuzzy search
+const FUZZY_MATCH: i32 = 16;
+/// Extra score for a character matched at a word start: the start of the name, after a
+/// separator such as `.` or `_`, or at a camelCase hump
+const FUZZY_BOUNDARY_BONUS: i32 = 8;
+/// Extra score for a character matched right after the previous one
+const FUZZY_CONSECUTIVE_BONUS: i32 = 8;
+/// Penalty for skipping characters between two matches, plus 1 per skipped character
+const FUZZY_GAP_PENALTY: i32 = 5;
+/// Highest score a fuzzy-only match reaches; with fuzzy matching on, substring matches are
+/// raised above it
+const FUZZY_MAX_SCORE: i32 = 25;
+/// Cap on the rows fetched as fuzzy candidates before ranking
+const FUZZY_CANDIDATE_LIMIT: usize = 5000;
+
+/// A subsequence match of a query against a name
+#[derive(Debug, PartialEq)]
+struct FuzzyMatch {
+    score: i32,
+    /// Byte range of each matched character
+    ranges: Vec<(usize, usize)>,
+}
+
+/// Match `query` as a case-insensitive subsequence of `text`, choosing the alignment that
+/// favors consecutive runs and word starts and penalizes gaps, like skim's matcher.
+///
+/// Returns `None` when some query character can't be matched in order.
+fn fuzzy_match(text: &str, query: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    if query.is_empty() || query.len() > chars.len() {
+        return None;
+    }
+
+    let eq = |a: char, b: char| a.to_lowercase().eq(b.to_lowercase());
+    let char_score = |j: usize| {
+        let c = chars[j].1;
+        let boundary = match j.checked_sub(1).map(|i| chars[i].1) {
+            None => true,
+            Some(prev) => !prev.is_alphanumeric() || (prev.is_lowercase() && c.is_uppercase()),
+        };
+        if boundary {
+            FUZZY_MATCH + FUZZY_BOUNDARY_BONUS
+        } else {
+            FUZZY_MATCH
+        }
+    };
+
+    // best[i][j]: best score with query[..=i] matched and query[i] at chars[j], along with
+    // the position query[i - 1] was matched at
+    let mut best: Vec<Vec<Option<(i32, usize)>>> = vec![vec![None; chars.len()]; query.len()];
+    for (j, &(_, c)) in chars.iter().enumerate() {
+        if eq(c, query[0]) {
+            best[0][j] = Some((char_score(j), 0));
+        }
+    }
+    for i in 1..query.len() {
+        // Best predecessor at least one character back, with the gap penalty applied so far
+        let mut gapped: Option<(i32, usize)> = None;
+        for j in i..chars.len() {
+            // Every character skipped costs one more
+            gapped = gapped.map(|(score, k)| (score - 1, k));
+            if let Some((score, _)) = j.checked_sub(2).and_then(|k| best[i - 1][k]) {
+                let candidate = score - FUZZY_GAP_PENALTY - 1;
+                if gapped.is_none_or(|(g, _)| candidate > g) {
+                    gapped = Some((candidate, j - 2));
+                }
+            }
+            if !eq(chars[j].1, query[i]) {
+                continue;
+            }
+
+            let consecutive =
+                best[i - 1][j - 1].map(|(score, _)| (score + FUZZY_CONSECUTIVE_BONUS, j - 1));
+            let previous = match (consecutive, gapped) {
+                (Some(c), Some(g)) => Some(if c.0 >= g.0 { c } else { g }),
+                (c, g) => c.or(g),
+            };
+            best[i][j] = previous.map(|(score, k)| (score + char_score(j), k));
+        }
+    }
+
+    let last = query.len() - 1;
+    let (mut j, score) = best[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, entry)| entry.map(|(score, _)| (j, score)))
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))?;
+
+    let mut ranges = vec![(0, 0); query.len()];
+    for i in (0..query.len()).rev() {
+        let (start, c) = chars[j];
+        ranges[i] = (start, start + c.len_utf8());
+        if let Some((_, previous)) = best[i][j] {
+            j = previous;
+        }
+    }
+
+    Some(FuzzyMatch { score, ranges })
+}
+
+/// Relevance score for a name matched only as a subsequence of the query, scaled to
+/// 1..=`FUZZY_MAX_SCORE` and adjusted for depth and recency like `compute_score`
+fn fuzzy_score(name: &str, query: &str, depth: usize, mtime: Option<i64>, now: i64) -> i32 {
+    let Some(matched) = fuzzy_match(name, query) else {
+        return 0;
+    };
+
+    let query_len = matched.ranges.len() as i32;
+    let best_possible = query_len * (FUZZY_MATCH + FUZZY_BOUNDARY_BONUS + FUZZY_CONSECUTIVE_BONUS);
+    let scaled = (matched.score.max(0) * FUZZY_MAX_SCORE / best_possible).clamp(1, FUZZY_MAX_SCORE);
+
+    (scaled - depth.min(MAX_DEPTH_PENALTY) as i32 + recency_bonus(mtime, now)).max(1)
+}
+
+/// `LIKE` pattern matching names that contain `query` as a subsequence
+fn subsequence_like_pattern(query: &str) -> String {
+    let mut pattern = String::from("%");
+    for c in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        pattern.push(c);
+        pattern.push('%');
+    }
+    pattern
+}
+
+/// Locate a match within a search result, preferring the name over the rest of the path.
+///
+/// `find` returns byte ranges into the text it is given; name ranges are shifted so every
+/// returned range indexes into `path`.
+fn match_ranges<F>(name: &str, path: &str, find: F) -> Vec<(usize, usize)>
+where
+    F: Fn(&str) -> Vec<(usize, usize)>,
+{
+    if path.ends_with(name) {
+        let name_start = path.len() - name.len();
+        let ranges = find(name);
+        if !ranges.is_empty() {
+            return ranges
+                .into_iter()
+                .map(|(start, end)| (name_start + start, name_start + end))
+                .collect();
+        }
+    }
+    find(path)
+}
+
+/// Build the SQL WHERE clause and its parameters for the parsed filters.
+///
+/// Regex filters are applied afterwards in Rust and contribute no condition here.
+fn build_where_clause(filters: &SearchFilter) -> (String, Vec<String>) {
+    let mut conditions: Vec<String> = Vec::new();
+    let mut param_values: Vec<String> = Vec::new();
+
+    if let Some(ref file_name) = filters.file_name {
+        // file: matches FILES by name
+        conditions.push("(is_dir = 0 AND LOWER(name) LIKE ?)".to_string());
+        param_values.push(format!("%{}%", file_name.to_lowercase()));
+    }
+
+    if let Some(ref dir_name) = filters.directory_name {
+        // dir: matches DIRECTORIES by name
+        conditions.push("(is_dir = 1 AND LOWER(name) LIKE ?)".to_string());
+        param_values.push(format!("%{}%", dir_name.to_lowercase()));
+    }
+
+    // When both file: and dir: are present, we need files matching file: that are inside dirs matching dir:
+    if filters.file_name.is_some() && filters.directory_name.is_some() {
+        // Override: find files with name matching file: AND path containing dir:
+        conditions.clear();
+        param_values.clear();
         let file_name = filters.file_name.as_ref().unwrap();
         let dir_name = filters.directory_name.as_ref().unwrap();
         conditions.push("(is_dir = 0 AND LOWER(name) LIKE ? AND LOWER(path) LIKE ?)".to_string());
@@ -387,6 +1425,29 @@ fn search_db(conn: &rusqlite::Connection, pattern: &str) -> Result<Vec<SearchRes
         param_values.push(format!("%{}%", plain_text.to_lowercase()));
     }
 
+    // Sizes are stored only for files, so a size filter never matches directories
+    if let Some(range) = filters.size_range {
+        if let Some(min) = range.min {
+            conditions.push("(size IS NOT NULL AND size >= ?)".to_string());
+            param_values.push(min.to_string());
+        }
+        if let Some(max) = range.max {
+            conditions.push("(size IS NOT NULL AND size <= ?)".to_string());
+            param_values.push(max.to_string());
+        }
+    }
+
+    if let Some(range) = filters.mtime_range {
+        if let Some(min) = range.min {
+            conditions.push("(mtime IS NOT NULL AND mtime >= ?)".to_string());
+            param_values.push(min.to_string());
+        }
+        if let Some(max) = range.max {
+            conditions.push("(mtime IS NOT NULL AND mtime <= ?)".to_string());
+            param_values.push(max.to_string());
+        }
+    }
+
     // For regex, we fetch broadly and filter in Rust
     if filters.regex_pattern.is_some() && conditions.is_empty() {
         // No SQL filters, query everything (limited)
@@ -398,13 +1459,59 @@ fn search_db(conn: &rusqlite::Connection, pattern: &str) -> Result<Vec<SearchRes
         conditions.join(" AND ")
     };
 
+    (where_clause, param_values)
+}
+
+/// Internal search function that operates on a raw connection (testable without Tauri state).
+///
+/// With `prefer_source`, plain-text matches are adjusted by `source_adjustment`. With `fuzzy`,
+/// plain text also matches names that contain it as a subsequence (`appjs` finds `app.js`);
+/// those rank below every substring match.
+fn search_db(
+    conn: &rusqlite::Connection,
+    pattern: &str,
+    prefer_source: bool,
+    fuzzy: bool,
+) -> Result<Vec<SearchResult>, String> {
+    let mut filters = parse_search_query(pattern);
+
+    // Empty query returns nothing
+    if filters.file_name.is_none()
+        && filters.directory_name.is_none()
+        && filters.regex_pattern.is_none()
+        && filters.plain_text.is_none()
+        && filters.size_range.is_none()
+        && filters.mtime_range.is_none()
+    {
+        return Ok(Vec::new());
+    }
+
+    // Fuzzy matching only applies to plain text; a regex already says exactly what to match
+    let fuzzy_text = if fuzzy && filters.regex_pattern.is_none() {
+        filters.plain_text.take()
+    } else {
+        None
+    };
+
+    let (mut where_clause, mut param_values) = build_where_clause(&filters);
+    let mut limit = 500;
+    if let Some(ref text) = fuzzy_text {
+        // Candidates are narrowed in SQL, then ranked in Rust
+        where_clause.push_str(" AND (LOWER(name) LIKE ? OR LOWER(path) LIKE ?)");
+        param_values.push(subsequence_like_pattern(text));
+        param_values.push(format!("%{}%", text.to_lowercase()));
+        limit = FUZZY_CANDIDATE_LIMIT;
+        filters.plain_text = fuzzy_text.clone();
+    }
+
     let query = format!(
         "SELECT path, parent_path, name, size, mtime, is_dir, token_count, fingerprint,
-         (SELECT COUNT(*) FROM files f2 WHERE f2.parent_path = files.path) as child_count
+         (SELECT COUNT(*) FROM files f2 WHERE f2.parent_path = files.path) as child_count,
+         language
          FROM files
          WHERE {}
-         LIMIT 500",
-        where_clause
+         LIMIT {}",
+        where_clause, limit
     );
 
     let mut stmt = conn.prepare(&query).map_err(|e| e.to_string())?;
@@ -414,7 +1521,18 @@ fn search_db(conn: &rusqlite::Connection, pattern: &str) -> Result<Vec<SearchRes
         .map(|p| p as &dyn rusqlite::types::ToSql)
         .collect();
 
-    let entries: Vec<(String, Option<String>, String, Option<i64>, Option<i64>, bool, Option<i64>, Option<String>, Option<i64>)> = stmt
+    let entries: Vec<(
+        String,
+        Option<String>,
+        String,
+        Option<i64>,
+        Option<i64>,
+        bool,
+        Option<i64>,
+        Option<String>,
+        Option<i64>,
+        Option<String>,
+    )> = stmt
         .query_map(rusqlite::params_from_iter(param_refs), |row| {
             Ok((
                 row.get::<_, String>(0)?,
@@ -426,6 +1544,7 @@ fn search_db(conn: &rusqlite::Connection, pattern: &str) -> Result<Vec<SearchRes
                 row.get::<_, Option<i64>>(6)?,
                 row.get::<_, Option<String>>(7)?,
                 row.get::<_, Option<i64>>(8)?,
+                row.get::<_, Option<String>>(9)?,
             ))
         })
         .map_err(|e| e.to_string())?
@@ -443,10 +1562,37 @@ fn search_db(conn: &rusqlite::Connection, pattern: &str) -> Result<Vec<SearchRes
         String::new()
     };
 
+    // Depth is measured from the shallowest candidate so absolute root depth doesn't matter
+    let min_depth = entries
+        .iter()
+        .map(|entry| path_depth(&entry.0))
+        .min()
+        .unwrap_or(0);
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
     let mut results: Vec<SearchResult> = entries
         .into_iter()
-        .map(|(path, parent_path, name, size, mtime, is_dir, token_count, fingerprint, child_count)| {
-            let score = compute_score(&name, &path, &score_query, is_dir);
+        .map(|(path, parent_path, name, size, mtime, is_dir, token_count, fingerprint, child_count, language)| {
+            let depth = path_depth(&path) - min_depth;
+            let mut score = compute_score(&name, &path, &score_query, is_dir, depth, mtime, now);
+            let substring_match = score > 0;
+            if !substring_match && fuzzy_text.is_some() {
+                score = fuzzy_score(&name, &score_query, depth, mtime, now);
+            }
+            if prefer_source && score > 0 {
+                score = (score + source_adjustment(&name, &path, is_dir, language.as_deref())).max(1);
+            }
+            // Fuzzy matches get a tier of their own below even the weakest path-only match
+            if fuzzy_text.is_some() && score > 0 {
+                score = if substring_match {
+                    score + FUZZY_MAX_SCORE
+                } else {
+                    score.min(FUZZY_MAX_SCORE)
+                };
+            }
             SearchResult {
                 path,
                 parent_path,
@@ -457,7 +1603,9 @@ fn search_db(conn: &rusqlite::Connection, pattern: &str) -> Result<Vec<SearchRes
                 token_count,
                 fingerprint,
                 child_count,
+                language,
                 score,
+                match_ranges: Vec::new(),
             }
         })
         .collect();
@@ -471,8 +1619,31 @@ fn search_db(conn: &rusqlite::Connection, pattern: &str) -> Result<Vec<SearchRes
         }
     }
 
-    // Filter out zero-score results (no match)
-    results.retain(|r| r.score > 0);
+    // Filter out zero-score results (no match). A size-only query has nothing to score
+    // against, so everything the size condition let through is kept.
+    if !score_query.is_empty() || filters.regex_pattern.is_some() {
+        results.retain(|r| r.score > 0);
+    }
+
+    // Record where the query matched so the UI can highlight it
+    if let Some(ref re) = filters.regex_pattern {
+        for r in &mut results {
+            r.match_ranges = match_ranges(&r.name, &r.path, |text| {
+                re.find_iter(text).map(|m| (m.start(), m.end())).collect()
+            });
+        }
+    } else if let Some(ref plain_text) = filters.plain_text {
+        for r in &mut results {
+            r.match_ranges = match_ranges(&r.name, &r.path, |text| {
+                let ranges = find_case_insensitive(text, plain_text);
+                if ranges.is_empty() && fuzzy_text.is_some() {
+                    fuzzy_match(text, plain_text).map_or(ranges, |m| m.ranges)
+                } else {
+                    ranges
+                }
+            });
+        }
+    }
 
     // Sort by score DESC, then name ASC
     results.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
@@ -480,240 +1651,840 @@ fn search_db(conn: &rusqlite::Connection, pattern: &str) -> Result<Vec<SearchRes
     Ok(results)
 }
 
-/// Search for files by path pattern with advanced filter support.
-/// Supports: file:<name>, dir:<name>, regex patterns, plain text
-/// Returns results with relevance scores, sorted by score DESC.
-#[tauri::command]
-pub async fn search_path(
-    pattern: String,
-    db: tauri::State<'_, DbConnection>,
-) -> Result<Vec<SearchResult>, String> {
-    log::debug!("Searching for pattern: {}", pattern);
-    let conn = db.lock().map_err(|e| format!("Failed to lock database: {}", e))?;
-    search_db(&conn, &pattern)
+/// Search results that share a parent directory
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchGroup {
+    /// Parent path of every result in the group; `None` for top-level entries
+    pub dir: Option<String>,
+    pub match_count: usize,
+    pub best_score: i32,
+    pub results: Vec<SearchResult>,
 }
 
-/// Clear the file index
-#[tauri::command]
-pub async fn clear_index(
-    db: tauri::State<'_, DbConnection>,
-) -> Result<(), String> {
-    log::info!("Clearing file index");
-
-    let conn = db.lock().map_err(|e| format!("Failed to lock database: {}", e))?;
+/// Search results, either as a flat list or grouped by directory
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SearchResponse {
+    Flat(Vec<SearchResult>),
+    Grouped(Vec<SearchGroup>),
+}
 
-    conn.execute("DELETE FROM files", [])
-        .map_err(|e| format!("Failed to clear index: {}", e))?;
+/// Group search results under their parent directory.
+///
+/// Results keep their relative order within a group. Groups are ordered by their best
+/// score DESC, then by directory.
+fn group_results_by_dir(results: Vec<SearchResult>) -> Vec<SearchGroup> {
+    let mut groups: Vec<SearchGroup> = Vec::new();
+    let mut group_index: HashMap<Option<String>, usize> = HashMap::new();
+
+    for result in results {
+        let index = *group_index
+            .entry(result.parent_path.clone())
+            .or_insert_with(|| {
+                groups.push(SearchGroup {
+                    dir: result.parent_path.clone(),
+                    match_count: 0,
+                    best_score: result.score,
+                    results: Vec::new(),
+                });
+                groups.len() - 1
+            });
+
+        let group = &mut groups[index];
+        group.match_count += 1;
+        group.best_score = group.best_score.max(result.score);
+        group.results.push(result);
+    }
 
-    Ok(())
+    groups.sort_by(|a, b| b.best_score.cmp(&a.best_score).then_with(|| a.dir.cmp(&b.dir)));
+    groups
 }
 
-/// Internal function to recursively traverse and insert files
-fn traverse_and_insert(
-    conn: &rusqlite::Connection,
-    path: &Path,
-    parent_path: Option<String>,
-) -> AppResult<u64> {
-    let mut count = 0u64;
+/// Number of distinct queries `SearchCache` keeps results for
+const SEARCH_CACHE_CAPACITY: usize = 32;
 
-    let entry = FileEntry::from_path(path, parent_path)?;
+/// How long cached search results are served; relative `modified:` ranges and on-disk
+/// ignore rules can change the answer without the index changing
+const SEARCH_CACHE_TTL: Duration = Duration::from_secs(30);
 
-    // Check if entry already exists with same fingerprint
-    let existing_fingerprint: Option<Option<String>> = conn
-        .query_row(
-            "SELECT fingerprint FROM files WHERE path = ?",
-            params![&entry.path],
-            |row| row.get(0),
-        )
-        .optional()?;
+/// Query and options that together determine a search's results
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SearchKey {
+    pattern: String,
+    prefer_source: bool,
+    fuzzy: bool,
+    exclude_ignored: bool,
+}
 
-    if let Some(existing_fp) = existing_fingerprint {
-        // Entry exists - check if we need to update
-        if existing_fp != entry.fingerprint {
-            conn.execute(
-                "UPDATE files SET size = ?, mtime = ?, fingerprint = ?, name = ?, parent_path = ? WHERE path = ?",
-                params![entry.size, entry.mtime, entry.fingerprint, entry.name, entry.parent_path, entry.path],
-            )?;
+/// Tauri-managed cache of recent `search_path` results.
+///
+/// Typing and backspacing repeat the same queries within seconds, so the last few result
+/// lists are kept, least recently used first, and served until they expire. Everything
+/// that changes the index clears it.
+#[derive(Debug, Default)]
+pub struct SearchCache {
+    entries: Mutex<VecDeque<(SearchKey, Instant, Vec<SearchResult>)>>,
+}
+
+impl SearchCache {
+    /// Results for `key` if they were cached within the TTL, marking them recently used
+    fn get(&self, key: &SearchKey) -> Option<Vec<SearchResult>> {
+        let mut entries = self.entries.lock().ok()?;
+        let index = entries.iter().position(|(cached, _, _)| cached == key)?;
+        let entry = entries.remove(index)?;
+        if entry.1.elapsed() > SEARCH_CACHE_TTL {
+            return None;
         }
-    } else {
-        // Insert new entry
-        conn.execute(
-            "INSERT INTO files (path, parent_path, name, size, mtime, is_dir, fingerprint)
-             VALUES (?, ?, ?, ?, ?, ?, ?)",
-            params![
-                entry.path,
-                entry.parent_path,
-                entry.name,
-                entry.size,
-                entry.mtime,
-                entry.is_dir as i32,
-                entry.fingerprint,
-            ],
-        )?;
+        let results = entry.2.clone();
+        entries.push_back(entry);
+        Some(results)
     }
 
-    count += 1;
-
-    // If it's a directory, recursively index children
-    if entry.is_dir {
-        match fs::read_dir(path) {
-            Ok(entries) => {
-                for dir_entry in entries.filter_map(|e| e.ok()) {
-                    let child_path = dir_entry.path();
-                    match traverse_and_insert(conn, &child_path, Some(entry.path.clone())) {
-                        Ok(child_count) => count += child_count,
-                        Err(e) => {
-                            log::warn!("Failed to index {:?}: {}", child_path, e);
-                            // Continue with other entries even if one fails
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                log::warn!("Failed to read directory {:?}: {}", path, e);
-            }
+    /// Cache results for `key`, evicting the least recently used entry when full
+    fn insert(&self, key: SearchKey, results: Vec<SearchResult>) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        entries.retain(|(cached, _, _)| *cached != key);
+        if entries.len() >= SEARCH_CACHE_CAPACITY {
+            entries.pop_front();
         }
+        entries.push_back((key, Instant::now(), results));
     }
 
-    Ok(count)
+    /// Drop every cached result; called whenever the index changes
+    pub fn clear(&self) {
+        match self.entries.lock() {
+            Ok(mut entries) => entries.clear(),
+            Err(e) => log::warn!("Failed to lock search cache: {}", e),
+        }
+    }
 }
 
-/// Parallel file system traversal with progress reporting and batch inserts
-fn parallel_index_folder(
-    root: &Path,
-    app: &AppHandle,
+/// Internal function to search the index, answering repeated queries from `cache`
+fn search_path_internal(
     db: &DbConnection,
-) -> AppResult<u64> {
-    log::info!("Starting parallel traversal of {:?}", root);
-
-    // Load settings to check respect_gitignore flag
-    let settings = load_settings_internal(db).unwrap_or_default();
-    let respect_gitignore = settings.respect_gitignore;
-    log::info!("Gitignore support: {}", if respect_gitignore { "enabled" } else { "disabled" });
+    cache: &SearchCache,
+    pattern: &str,
+    prefer_source: bool,
+    fuzzy: bool,
+    exclude_ignored: bool,
+) -> CommandResult<Vec<SearchResult>> {
+    let key = SearchKey {
+        pattern: pattern.trim().to_string(),
+        prefer_source,
+        fuzzy,
+        exclude_ignored,
+    };
+    if let Some(results) = cache.get(&key) {
+        log::debug!("Search cache hit for pattern: {}", key.pattern);
+        return Ok(results);
+    }
 
-    // Create gitignore manager and discover .gitignore files if enabled
-    let gitignore_manager: Option<Arc<GitignoreManager>> = if respect_gitignore {
-        let mut manager = GitignoreManager::new(root);
-        match manager.discover_gitignores(root) {
-            Ok(count) => log::info!("Loaded {} .gitignore files", count),
-            Err(e) => log::warn!("Error discovering .gitignore files: {}", e),
-        }
-        Some(Arc::new(manager))
-    } else {
-        None
+    let (mut results, roots) = {
+        let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+        let results = search_db(&conn, &key.pattern, prefer_source, fuzzy)?;
+        let roots = if exclude_ignored {
+            indexed_roots(&conn)?
+        } else {
+            Vec::new()
+        };
+        (results, roots)
     };
 
+    // Loading the ignore rules reads settings, so the database lock is released first
+    if exclude_ignored {
+        retain_unignored(&mut results, &roots, |root| {
+            let project = ProjectConfig::load_or_default(root);
+            let manager = gitignore_manager_from_settings(root, db, &project);
+            (manager, project)
+        });
+    }
 
-    // First pass: collect all entries using parallel walkdir
-    let processed_count = Arc::new(AtomicU64::new(0));
-    let error_count = Arc::new(AtomicU64::new(0));
-    let ignored_count = Arc::new(AtomicU64::new(0));
-    let last_progress_time = Arc::new(Mutex::new(Instant::now()));
+    cache.insert(key, results.clone());
+    Ok(results)
+}
 
-    // Clone Arc for closure
-    let gitignore_manager_for_filter: Option<Arc<GitignoreManager>> = gitignore_manager.clone();
+/// Search for files by path pattern with advanced filter support.
+/// Supports: file:<name>, dir:<name>, size:<range>, modified:<range>, regex patterns, plain text
+/// Returns results with relevance scores, sorted by score DESC, or grouped by parent
+/// directory when `group_by_dir` is set. `prefer_source` (default on) ranks source files
+/// above minified, lock and vendored files that match equally well. `fuzzy` (default off)
+/// adds subsequence matches on the name. `exclude_ignored` (default off) leaves out entries
+/// that the current gitignore rules or project excluded extensions would no longer index.
+/// Repeated queries are answered from `SearchCache` until the index changes.
+#[tauri::command]
+pub async fn search_path(
+    pattern: String,
+    group_by_dir: Option<bool>,
+    prefer_source: Option<bool>,
+    fuzzy: Option<bool>,
+    exclude_ignored: Option<bool>,
+    db: tauri::State<'_, DbConnection>,
+    search_cache: tauri::State<'_, SearchCache>,
+) -> CommandResult<SearchResponse> {
+    log::debug!("Searching for pattern: {}", pattern);
+    let results = search_path_internal(
+        &db,
+        &search_cache,
+        &pattern,
+        prefer_source.unwrap_or(true),
+        fuzzy.unwrap_or(false),
+        exclude_ignored.unwrap_or(false),
+    )?;
+
+    if group_by_dir.unwrap_or(false) {
+        Ok(SearchResponse::Grouped(group_results_by_dir(results)))
+    } else {
+        Ok(SearchResponse::Flat(results))
+    }
+}
 
+/// Size and composition of the file index
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct IndexStats {
+    pub total_entries: i64,
+    pub file_count: i64,
+    pub dir_count: i64,
+    pub total_bytes: i64,
+    /// Sum of token counts computed so far; files without a count contribute nothing
+    pub total_tokens: i64,
+    /// Entries shown at the top level of the tree (no parent, or an unindexed parent)
+    pub root_count: i64,
+}
 
-    // Collect entries with parallel iteration
-    let entries: Vec<FileEntry> = WalkDir::new(root)
-        .follow_links(false) // Don't follow symlinks to avoid cycles
-        .into_iter()
-        .filter_entry(|entry| {
-            if entry.path_is_symlink() {
-                return false;
-            }
+/// Internal function to aggregate index statistics
+fn get_index_stats_internal(conn: &rusqlite::Connection) -> rusqlite::Result<IndexStats> {
+    let (total_entries, file_count, dir_count, total_bytes, total_tokens) = conn.query_row(
+        "SELECT COUNT(*),
+                COALESCE(SUM(CASE WHEN is_dir = 0 THEN 1 ELSE 0 END), 0),
+                COALESCE(SUM(CASE WHEN is_dir != 0 THEN 1 ELSE 0 END), 0),
+                COALESCE(SUM(CASE WHEN is_dir = 0 THEN size ELSE 0 END), 0),
+                COALESCE(SUM(token_count), 0)
+         FROM files",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+    )?;
+
+    Ok(IndexStats {
+        total_entries,
+        file_count,
+        dir_count,
+        total_bytes,
+        total_tokens,
+        root_count: count_children_internal(conn, None)?,
+    })
+}
 
-            if let Some(ref manager) = gitignore_manager_for_filter {
-                let is_dir = entry.file_type().is_dir();
-                if manager.is_ignored_with_type(entry.path(), is_dir) {
-                    ignored_count.fetch_add(1, Ordering::Relaxed);
-                    return false;
-                }
-            }
+/// Get the size and composition of the file index
+#[tauri::command]
+pub async fn get_index_stats(db: tauri::State<'_, DbConnection>) -> CommandResult<IndexStats> {
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+    Ok(get_index_stats_internal(&conn)?)
+}
 
-            true
-        })
-        .par_bridge() // Enable parallel processing
-        .filter_map(|entry_result| {
-            let count = processed_count.fetch_add(1, Ordering::Relaxed);
+/// A top-level entry of the index with totals for everything below it
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct IndexRoot {
+    pub path: String,
+    pub name: String,
+    pub is_dir: bool,
+    /// Direct children only
+    pub child_count: i64,
+    /// Files at any depth below the root, or 1 for a file indexed on its own
+    pub file_count: i64,
+    pub total_bytes: i64,
+}
 
-            // Throttle progress events to max 10 per second
-            let should_emit = {
-                let mut last_time = last_progress_time.lock().unwrap();
-                let now = Instant::now();
-                if now.duration_since(*last_time) > Duration::from_millis(100) {
-                    *last_time = now;
-                    true
-                } else {
-                    false
-                }
-            };
+/// Internal function to list the top-level entries, the same ones `get_children` returns
+/// for the root, sorted by name
+fn get_roots_internal(conn: &rusqlite::Connection) -> rusqlite::Result<Vec<IndexRoot>> {
+    let mut totals = conn.prepare_cached(
+        "WITH RECURSIVE subtree(path) AS (
+             SELECT path FROM files WHERE path = ?1
+             UNION
+             SELECT files.path FROM files JOIN subtree ON files.parent_path = subtree.path
+         )
+         SELECT COUNT(*), COALESCE(SUM(size), 0)
+         FROM files
+         WHERE is_dir = 0 AND path IN (SELECT path FROM subtree)",
+    )?;
 
-            if should_emit {
-                let current_path = entry_result
-                    .as_ref()
-                    .map(|e| e.path().to_string_lossy().to_string())
-                    .unwrap_or_else(|_| "Unknown".to_string());
+    get_children_internal(conn, None, SortKey::Name, false, None)?
+        .into_iter()
+        .map(|entry| {
+            let (file_count, total_bytes) =
+                totals.query_row(params![entry.path], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            Ok(IndexRoot {
+                path: entry.path,
+                name: entry.name,
+                is_dir: entry.is_dir,
+                child_count: entry.child_count.unwrap_or(0),
+                file_count,
+                total_bytes,
+            })
+        })
+        .collect()
+}
 
-                let progress = IndexProgress {
-                    processed: count,
-                    total_estimate: count + 100, // Rough estimate
-                    current_path,
-                    errors: error_count.load(Ordering::Relaxed),
-                };
+/// List the folders and files at the top level of the index, with their sizes
+#[tauri::command]
+pub async fn get_roots(db: tauri::State<'_, DbConnection>) -> CommandResult<Vec<IndexRoot>> {
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+    Ok(get_roots_internal(&conn)?)
+}
 
-                if let Err(e) = app.emit("indexing-progress", &progress) {
-                    log::warn!("Failed to emit progress event: {}", e);
-                }
-            }
+/// Clear the file index
+#[tauri::command]
+pub async fn clear_index(
+    db: tauri::State<'_, DbConnection>,
+    search_cache: tauri::State<'_, SearchCache>,
+) -> CommandResult<()> {
+    log::info!("Clearing file index");
 
-            match entry_result {
-                Ok(entry) => {
-                    match FileEntry::from_dir_entry(&entry) {
-                        Ok(file_entry) => Some(file_entry),
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+    Ok(clear_index_internal(&conn, &search_cache)?)
+}
+
+/// Internal function to delete every indexed entry, dropping cached search results with them
+fn clear_index_internal(
+    conn: &rusqlite::Connection,
+    search_cache: &SearchCache,
+) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM files", [])?;
+    search_cache.clear();
+    Ok(())
+}
+
+/// Internal function to delete a path and everything indexed below it
+///
+/// Returns the number of rows removed. Cached search results are dropped, since they may list
+/// removed rows.
+fn remove_from_index_internal(
+    conn: &mut rusqlite::Connection,
+    search_cache: &SearchCache,
+    path: &str,
+) -> rusqlite::Result<u64> {
+    let path = normalize_path(path);
+    let tx = conn.transaction()?;
+
+    let removed = tx.execute(
+        "WITH RECURSIVE subtree(path) AS (
+             SELECT path FROM files WHERE path = ?1
+             UNION
+             SELECT files.path FROM files JOIN subtree ON files.parent_path = subtree.path
+         )
+         DELETE FROM files WHERE path IN (SELECT path FROM subtree)",
+        params![path],
+    )?;
+
+    tx.commit()?;
+    search_cache.clear();
+    Ok(removed as u64)
+}
+
+/// Remove a single file or folder subtree from the index, leaving everything else in place
+#[tauri::command]
+pub async fn remove_from_index(
+    path: String,
+    db: tauri::State<'_, DbConnection>,
+    search_cache: tauri::State<'_, SearchCache>,
+) -> CommandResult<u64> {
+    log::info!("Removing from index: {}", path);
+
+    let mut conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+    Ok(remove_from_index_internal(&mut conn, &search_cache, &path)?)
+}
+
+/// Internal function to check that `path` is indexed and still on disk, returning it
+/// normalized
+fn reveal_target(conn: &rusqlite::Connection, path: &str) -> CommandResult<String> {
+    let path = normalize_path(path);
+    let indexed = conn
+        .query_row("SELECT 1 FROM files WHERE path = ?", params![path], |_| {
+            Ok(())
+        })
+        .optional()?
+        .is_some();
+    if !indexed {
+        return Err(CommandError::new(
+            CommandError::NOT_FOUND,
+            format!("Path not found in index: {}", path),
+        ));
+    }
+    if !Path::new(&path).exists() {
+        return Err(CommandError::new(
+            CommandError::NOT_FOUND,
+            format!("Path no longer exists on disk: {}", path),
+        ));
+    }
+    Ok(path)
+}
+
+/// Show an indexed file or folder in Explorer, Finder or the desktop's file manager,
+/// selecting it where the platform supports that
+#[tauri::command]
+pub async fn reveal_in_file_manager(
+    path: String,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<()> {
+    let path = {
+        let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+        reveal_target(&conn, &path)?
+    };
+
+    log::info!("Revealing in file manager: {}", path);
+    tauri_plugin_opener::reveal_item_in_dir(&path).map_err(|e| {
+        CommandError::new(
+            CommandError::IO,
+            format!("Failed to reveal {}: {}", path, e),
+        )
+    })
+}
+
+/// Maximum number of paths returned in each list of an [`IgnorePreview`]
+const PREVIEW_LIST_LIMIT: usize = 500;
+
+/// What indexing a folder would pick up and what gitignore rules would leave out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IgnorePreview {
+    /// Paths that would be indexed, capped at `PREVIEW_LIST_LIMIT`
+    pub indexed: Vec<String>,
+    /// Paths that would be skipped, capped at `PREVIEW_LIST_LIMIT`
+    pub ignored: Vec<String>,
+    pub indexed_count: u64,
+    /// Ignored entries; an ignored directory counts once, its contents are never visited
+    pub ignored_count: u64,
+}
+
+/// Internal function to walk `root` and sort every entry into indexed or ignored
+fn preview_ignored_internal(
+    root: &Path,
+    gitignore_manager: Option<GitignoreManager>,
+    limit: usize,
+) -> AppResult<IgnorePreview> {
+    let mut gitignore_manager = gitignore_manager;
+    if let Some(manager) = gitignore_manager.as_mut() {
+        let count = manager.discover_gitignores(root).map_err(AppError::Path)?;
+        log::debug!("Discovered {} .gitignore files for preview", count);
+    }
+
+    let mut preview = IgnorePreview {
+        indexed: Vec::new(),
+        ignored: Vec::new(),
+        indexed_count: 0,
+        ignored_count: 0,
+    };
+
+    let walker = WalkDir::new(root).follow_links(false).min_depth(1);
+    let entries = walker.into_iter().filter_entry(|entry| {
+        if entry.path_is_symlink() {
+            return false;
+        }
+
+        let is_ignored = gitignore_manager.as_ref().is_some_and(|manager| {
+            manager.is_ignored_with_type(entry.path(), entry.file_type().is_dir())
+        });
+        if is_ignored {
+            if preview.ignored.len() < limit {
+                preview
+                    .ignored
+                    .push(normalize_path(&entry.path().to_string_lossy()));
+            }
+            preview.ignored_count += 1;
+        }
+        !is_ignored
+    });
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        if preview.indexed.len() < limit {
+            preview
+                .indexed
+                .push(normalize_path(&entry.path().to_string_lossy()));
+        }
+        preview.indexed_count += 1;
+    }
+
+    Ok(preview)
+}
+
+/// Preview which paths under a folder gitignore rules would exclude, without indexing it
+#[tauri::command]
+pub async fn preview_ignored(
+    root: String,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<IgnorePreview> {
+    let root_path = PathBuf::from(&root);
+    if !root_path.exists() {
+        return Err(CommandError::new(
+            CommandError::NOT_FOUND,
+            format!("Path does not exist: {}", root),
+        ));
+    }
+
+    let project = ProjectConfig::load_or_default(&root_path);
+    let gitignore_manager = gitignore_manager_from_settings(&root_path, &db, &project);
+    Ok(preview_ignored_internal(
+        &root_path,
+        gitignore_manager,
+        PREVIEW_LIST_LIMIT,
+    )?)
+}
+
+/// The gitignore rule that excludes a path from indexing
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IgnoreExplanation {
+    /// The .gitignore or exclude file containing the rule
+    pub gitignore: Option<String>,
+    /// The rule as written in that file
+    pub pattern: String,
+    /// Path the rule matched; an ancestor directory when the whole directory is ignored
+    pub matched_path: String,
+}
+
+impl From<IgnoreMatch> for IgnoreExplanation {
+    fn from(m: IgnoreMatch) -> Self {
+        Self {
+            gitignore: m
+                .source
+                .map(|source| normalize_path(&source.to_string_lossy())),
+            pattern: m.pattern,
+            matched_path: normalize_path(&m.matched_path.to_string_lossy()),
+        }
+    }
+}
+
+/// Internal function to find the rule that ignores `path` under `root`
+///
+/// Loads `.git/info/exclude` and the .gitignore of every directory between `root` and the
+/// path, mirroring what indexing sees by the time it reaches the path.
+fn explain_ignore_internal(
+    root: &Path,
+    path: &Path,
+    gitignore_manager: Option<GitignoreManager>,
+) -> Option<IgnoreExplanation> {
+    let mut manager = gitignore_manager?;
+    manager.load_info_exclude(root);
+
+    let mut dirs: Vec<&Path> = path
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(root))
+        .collect();
+    dirs.reverse();
+    for dir in dirs {
+        manager.load_dir_gitignore(dir);
+    }
+
+    manager
+        .explain_ignored(path, path.is_dir())
+        .map(IgnoreExplanation::from)
+}
+
+/// Explain which gitignore rule keeps `path` out of the index of `root`
+///
+/// `path` may be absolute or relative to `root`. Returns `None` when the path is not
+/// ignored, including when gitignore support is turned off.
+#[tauri::command]
+pub async fn explain_ignore(
+    root: String,
+    path: String,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<Option<IgnoreExplanation>> {
+    let root_path = PathBuf::from(&root);
+    if !root_path.exists() {
+        return Err(CommandError::new(
+            CommandError::NOT_FOUND,
+            format!("Path does not exist: {}", root),
+        ));
+    }
+
+    let project = ProjectConfig::load_or_default(&root_path);
+    let gitignore_manager = gitignore_manager_from_settings(&root_path, &db, &project);
+    Ok(explain_ignore_internal(
+        &root_path,
+        &root_path.join(&path),
+        gitignore_manager,
+    ))
+}
+
+/// Internal function to drop search results that the current ignore rules exclude
+///
+/// Entries indexed before a rule existed, such as one from a .gitignore added later, stay in
+/// the index until their folder is indexed again. Each result is checked under the innermost
+/// indexed root containing it, loading the .gitignore files between that root and the result
+/// the way `explain_ignore_internal` does, along with the root's project excluded extensions.
+/// `rules_for` is called once per folder root. Results outside every root, or whose innermost
+/// root is a single indexed file, are kept.
+fn retain_unignored(
+    results: &mut Vec<SearchResult>,
+    roots: &[String],
+    mut rules_for: impl FnMut(&Path) -> (Option<GitignoreManager>, ProjectConfig),
+) {
+    let mut rules: HashMap<&str, (Option<GitignoreManager>, ProjectConfig)> = HashMap::new();
+
+    results.retain(|result| {
+        let Some(root) = roots
+            .iter()
+            .filter(|root| is_within(&result.path, root))
+            .max_by_key(|root| root.len())
+        else {
+            return true;
+        };
+        let root_path = Path::new(root.as_str());
+        let (manager, project) = rules.entry(root.as_str()).or_insert_with(|| {
+            let (mut manager, project) = if root_path.is_file() {
+                (None, ProjectConfig::default())
+            } else {
+                rules_for(root_path)
+            };
+            if let Some(manager) = manager.as_mut() {
+                manager.load_info_exclude(root_path);
+            }
+            (manager, project)
+        });
+
+        let path = Path::new(&result.path);
+        if !result.is_dir && project.excludes_extension(path) {
+            return false;
+        }
+        let Some(manager) = manager.as_mut() else {
+            return true;
+        };
+
+        let mut dirs: Vec<&Path> = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| dir.starts_with(root_path))
+            .collect();
+        dirs.reverse();
+        for dir in dirs {
+            manager.load_dir_gitignore(dir);
+        }
+        !manager.is_ignored_with_type(path, result.is_dir)
+    });
+}
+
+/// Internal function to recursively traverse and insert files
+fn traverse_and_insert(
+    conn: &rusqlite::Connection,
+    path: &Path,
+    parent_path: Option<String>,
+) -> AppResult<u64> {
+    let mut count = 0u64;
+
+    let entry = FileEntry::from_path(path, parent_path)?;
+
+    // Check if entry already exists with same fingerprint
+    let existing: Option<(Option<String>, Option<String>)> = conn
+        .query_row(
+            "SELECT fingerprint, language FROM files WHERE path = ?",
+            params![&entry.path],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    if let Some((existing_fp, existing_language)) = existing {
+        // Entry exists - check if we need to update.
+        // Rows indexed before language detection existed are backfilled here as well.
+        if existing_fp != entry.fingerprint || existing_language != entry.language {
+            conn.execute(
+                "UPDATE files SET size = ?, mtime = ?, fingerprint = ?, name = ?, parent_path = ?, symlink_target = ?, language = ? WHERE path = ?",
+                params![entry.size, entry.mtime, entry.fingerprint, entry.name, entry.parent_path, entry.symlink_target, entry.language, entry.path],
+            )?;
+        }
+    } else {
+        // Insert new entry
+        conn.execute(
+            "INSERT INTO files (path, parent_path, name, size, mtime, is_dir, fingerprint, symlink_target, language, lossy_path)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![
+                entry.path,
+                entry.parent_path,
+                entry.name,
+                entry.size,
+                entry.mtime,
+                entry.is_dir as i32,
+                entry.fingerprint,
+                entry.symlink_target,
+                entry.language,
+                entry.lossy_path as i32,
+            ],
+        )?;
+    }
+
+    count += 1;
+
+    // If it's a directory, recursively index children
+    if entry.is_dir {
+        match fs::read_dir(path) {
+            Ok(entries) => {
+                for dir_entry in entries.filter_map(|e| e.ok()) {
+                    let child_path = dir_entry.path();
+                    match traverse_and_insert(conn, &child_path, Some(entry.path.clone())) {
+                        Ok(child_count) => count += child_count,
                         Err(e) => {
-                            error_count.fetch_add(1, Ordering::Relaxed);
-                            log::warn!("Failed to process entry {:?}: {}", entry.path(), e);
-                            None
+                            log::warn!("Failed to index {:?}: {}", child_path, e);
+                            // Continue with other entries even if one fails
                         }
                     }
                 }
+            }
+            Err(e) => {
+                log::warn!("Failed to read directory {:?}: {}", path, e);
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+/// Counters a `filtered_walk` updates as it goes
+#[derive(Debug, Default)]
+struct WalkStats {
+    /// Entries pruned by ignore rules; their contents are never visited
+    ignored: AtomicU64,
+    /// .gitignore files loaded as the walk entered their directories
+    gitignore_files: AtomicU64,
+}
+
+/// Walk `root`, skipping entries ignored by gitignore rules.
+///
+/// Instead of a separate discovery pass, each directory's .gitignore is loaded as the walk
+/// enters it. `filter_entry` sees a directory before any of its children, so the patterns
+/// are always in place by the time those children are checked.
+///
+/// A non-zero `max_depth` stops descent below that depth; directories at the limit are still
+/// yielded.
+///
+/// Unless `include_hidden` is set, entries whose name starts with a dot are pruned like
+/// ignored ones; the root itself is always kept. Each directory's .gitignore is read
+/// directly rather than through the walk, so its rules apply either way.
+///
+/// Symlinks are dropped unless `follow_symlinks` is set. When following them, every
+/// directory's canonical path is tracked and a directory that resolves to one already
+/// visited is skipped, which both breaks cycles and keeps a linked tree from being indexed
+/// twice. Real entries are visited before symlinks in the same directory so the real path
+/// wins when both lead to the same place.
+fn filtered_walk(
+    root: &Path,
+    mut gitignore_manager: Option<GitignoreManager>,
+    follow_symlinks: bool,
+    include_hidden: bool,
+    max_depth: usize,
+    stats: Arc<WalkStats>,
+) -> impl Iterator<Item = walkdir::Result<walkdir::DirEntry>> + Send {
+    if let Some(manager) = gitignore_manager.as_mut() {
+        manager.load_info_exclude(root);
+    }
+
+    let mut walker = WalkDir::new(root).follow_links(follow_symlinks);
+    if max_depth > 0 {
+        walker = walker.max_depth(max_depth);
+    }
+    if follow_symlinks {
+        walker = walker.sort_by(|a, b| {
+            a.path_is_symlink()
+                .cmp(&b.path_is_symlink())
+                .then_with(|| a.file_name().cmp(b.file_name()))
+        });
+    }
+    let mut visited_dirs: HashSet<PathBuf> = HashSet::new();
+
+    walker.into_iter().filter_entry(move |entry| {
+        if entry.path_is_symlink() && !follow_symlinks {
+            return false;
+        }
+
+        if !include_hidden && entry.depth() > 0 && is_hidden(entry.file_name()) {
+            stats.ignored.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+
+        if follow_symlinks && entry.file_type().is_dir() {
+            match fs::canonicalize(entry.path()) {
+                Ok(canonical) => {
+                    if !visited_dirs.insert(canonical) {
+                        log::debug!("Skipping already visited directory {:?}", entry.path());
+                        return false;
+                    }
+                }
                 Err(e) => {
-                    error_count.fetch_add(1, Ordering::Relaxed);
-                    log::warn!("Error during traversal: {}", e);
-                    None
+                    log::warn!("Failed to resolve {:?}: {}", entry.path(), e);
+                    return false;
                 }
             }
-        })
-        .collect();
+        }
 
-    let ignored = ignored_count.load(Ordering::Relaxed);
-    if ignored > 0 {
-        log::info!("Ignored {} entries due to .gitignore patterns", ignored);
-    }
+        if let Some(manager) = gitignore_manager.as_mut() {
+            let is_dir = entry.file_type().is_dir();
+            if manager.is_ignored_with_type(entry.path(), is_dir) {
+                stats.ignored.fetch_add(1, Ordering::Relaxed);
+                return false;
+            }
 
-    let total_entries = entries.len();
-    log::info!(
-        "Collected {} entries, now inserting into database",
-        total_entries
-    );
+            if is_dir && manager.load_dir_gitignore(entry.path()) {
+                stats.gitignore_files.fetch_add(1, Ordering::Relaxed);
+            }
+        }
 
-    // Second pass: batch insert into database
-    let mut conn = db
-        .lock()
-        .map_err(|e| AppError::Unknown(format!("Failed to lock database: {}", e)))?;
+        true
+    })
+}
+
+/// Whether a file name marks a hidden entry on Unix, like `.env` or `.github`
+fn is_hidden(name: &std::ffi::OsStr) -> bool {
+    name.to_string_lossy().starts_with('.')
+}
+
+/// Entries `filtered_walk` yields for `root`, minus files with an extension the project
+/// excludes, which count as ignored
+fn index_walk<'a>(
+    root: &Path,
+    gitignore_manager: Option<GitignoreManager>,
+    follow_symlinks: bool,
+    include_hidden: bool,
+    max_depth: usize,
+    project: &'a ProjectConfig,
+    stats: Arc<WalkStats>,
+) -> impl Iterator<Item = walkdir::Result<walkdir::DirEntry>> + Send + 'a {
+    let walk = filtered_walk(
+        root,
+        gitignore_manager,
+        follow_symlinks,
+        include_hidden,
+        max_depth,
+        stats.clone(),
+    );
+    walk.filter(move |entry| match entry {
+        Ok(entry) if !entry.file_type().is_dir() && project.excludes_extension(entry.path()) => {
+            stats.ignored.fetch_add(1, Ordering::Relaxed);
+            false
+        }
+        _ => true,
+    })
+}
 
+/// Insert or update collected entries in batches of `batch_size`, parents before children.
+///
+/// Everything runs in one transaction, so a failure in any batch rolls the whole insert
+/// back instead of leaving a partial index. Each batch is its own savepoint.
+/// Only the insert and update counts of the returned summary are filled in.
+fn insert_entries(
+    conn: &mut rusqlite::Connection,
+    entries: Vec<FileEntry>,
+    batch_size: usize,
+) -> AppResult<IndexSummary> {
     // Sort entries by path depth to ensure parents are processed before children
     // Use Path::components() for reliable cross-platform depth calculation
     let mut sorted_entries = entries;
     sorted_entries.sort_by_key(|entry| Path::new(&entry.path).components().count());
 
-    // Insert in batches of 1000
-    const BATCH_SIZE: usize = 1000;
-    let mut total_inserted = 0u64;
+    let mut summary = IndexSummary::default();
+    let mut outer = conn.transaction()?;
 
-    for (batch_idx, chunk) in sorted_entries.chunks(BATCH_SIZE).enumerate() {
-        let tx = conn.transaction()?;
+    for (batch_idx, chunk) in sorted_entries.chunks(batch_size.max(1)).enumerate() {
+        let tx = outer.savepoint()?;
 
         for entry in chunk {
             // Always use the true parent_path from the file system.
@@ -722,27 +2493,33 @@ fn parallel_index_folder(
             let parent_path = &entry.parent_path;
 
             // Check if entry already exists
-            let existing_fingerprint: Option<Option<String>> = tx
+            let existing: Option<(Option<String>, bool, Option<String>)> = tx
                 .query_row(
-                    "SELECT fingerprint FROM files WHERE path = ?",
+                    "SELECT fingerprint, COALESCE(truncated_at_depth, 0), language FROM files WHERE path = ?",
                     params![&entry.path],
-                    |row| row.get(0),
+                    |row| Ok((row.get(0)?, row.get::<_, i32>(1)? != 0, row.get(2)?)),
                 )
                 .optional()?;
 
-            if let Some(existing_fp) = existing_fingerprint {
-                // Entry exists - check if we need to update
-                if existing_fp != entry.fingerprint {
+            if let Some((existing_fp, existing_truncated, existing_language)) = existing {
+                // Entry exists - check if we need to update.
+                // The depth limit can change between runs, so the truncation flag is compared too.
+                // Rows indexed before language detection existed are backfilled the same way.
+                if existing_fp != entry.fingerprint
+                    || existing_truncated != entry.truncated_at_depth
+                    || existing_language != entry.language
+                {
                     tx.execute(
-                        "UPDATE files SET size = ?, mtime = ?, fingerprint = ?, name = ?, parent_path = ? WHERE path = ?",
-                        params![entry.size, entry.mtime, entry.fingerprint, entry.name, parent_path, entry.path],
+                        "UPDATE files SET size = ?, mtime = ?, fingerprint = ?, name = ?, parent_path = ?, symlink_target = ?, truncated_at_depth = ?, language = ? WHERE path = ?",
+                        params![entry.size, entry.mtime, entry.fingerprint, entry.name, parent_path, entry.symlink_target, entry.truncated_at_depth as i32, entry.language, entry.path],
                     )?;
+                    summary.updated += 1;
                 }
             } else {
                 // Insert new entry
                 tx.execute(
-                    "INSERT INTO files (path, parent_path, name, size, mtime, is_dir, fingerprint)
-                     VALUES (?, ?, ?, ?, ?, ?, ?)",
+                    "INSERT INTO files (path, parent_path, name, size, mtime, is_dir, fingerprint, symlink_target, truncated_at_depth, language, lossy_path)
+                     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
                     params![
                         entry.path,
                         parent_path,
@@ -751,58 +2528,380 @@ fn parallel_index_folder(
                         entry.mtime,
                         entry.is_dir as i32,
                         entry.fingerprint,
+                        entry.symlink_target,
+                        entry.truncated_at_depth as i32,
+                        entry.language,
+                        entry.lossy_path as i32,
                     ],
                 )?;
+                summary.inserted += 1;
             }
         }
 
         tx.commit()?;
-        total_inserted += chunk.len() as u64;
-        log::debug!(
-            "Inserted batch {} ({} entries)",
-            batch_idx + 1,
-            total_inserted
+        log::debug!("Wrote batch {} ({} entries)", batch_idx + 1, chunk.len());
+    }
+
+    outer.commit()?;
+    Ok(summary)
+}
+
+/// Flag `root` as a folder the user indexed, keeping it at the top of the tree
+fn mark_index_root(conn: &rusqlite::Connection, root: &Path) -> rusqlite::Result<()> {
+    let root = normalize_path(&lossy_path_string(root).0);
+    conn.execute("UPDATE files SET is_root = 1 WHERE path = ?", params![root])?;
+    Ok(())
+}
+
+/// Create a gitignore manager for `root` according to the gitignore settings.
+///
+/// Returns `None` when `respect_gitignore` is off, in the project config or else the app
+/// settings. Only the global excludesfile and the root .dockerignore are loaded; .gitignore
+/// files in the tree are left to the caller.
+fn gitignore_manager_from_settings(
+    root: &Path,
+    db: &DbConnection,
+    project: &ProjectConfig,
+) -> Option<GitignoreManager> {
+    let respect_gitignore = project.respect_gitignore.unwrap_or_else(|| {
+        get_setting_typed::<bool>(db, "respect_gitignore")
+            .unwrap_or_else(|e| {
+                log::warn!("{}", e);
+                None
+            })
+            .unwrap_or(AppSettings::default().respect_gitignore)
+    });
+    log::info!("Gitignore support: {}", if respect_gitignore { "enabled" } else { "disabled" });
+
+    if !respect_gitignore {
+        return None;
+    }
+
+    let mut manager = GitignoreManager::new(root);
+    // The global excludesfile has the lowest precedence, so it is loaded first
+    let use_global_gitignore = get_setting_typed::<bool>(db, "use_global_gitignore")
+        .ok()
+        .flatten()
+        .unwrap_or(AppSettings::default().use_global_gitignore);
+    if use_global_gitignore && manager.load_global_gitignore() {
+        log::info!("Loaded global gitignore");
+    }
+    let respect_dockerignore = get_setting_typed::<bool>(db, "respect_dockerignore")
+        .ok()
+        .flatten()
+        .unwrap_or(AppSettings::default().respect_dockerignore);
+    if respect_dockerignore && manager.load_dockerignore() {
+        log::info!("Loaded .dockerignore");
+    }
+    Some(manager)
+}
+
+/// Parallel file system traversal with progress events and batch inserts
+fn parallel_index_folder(
+    root: &Path,
+    app: &AppHandle,
+    db: &DbConnection,
+    search_cache: &SearchCache,
+) -> AppResult<IndexSummary> {
+    index_folder_internal(root, db, search_cache, |progress| {
+        if let Err(e) = app.emit("indexing-progress", progress) {
+            log::warn!("Failed to emit progress event: {}", e);
+        }
+    })
+}
+
+/// Internal function to index `root`, passing progress to `emit_progress`
+///
+/// Cached search results are dropped once anything may have been written, even if the run
+/// then fails.
+fn index_folder_internal(
+    root: &Path,
+    db: &DbConnection,
+    search_cache: &SearchCache,
+    emit_progress: impl Fn(&IndexProgress) + Sync,
+) -> AppResult<IndexSummary> {
+    log::info!("Starting parallel traversal of {:?}", root);
+
+    // A single file was picked explicitly, so no project config or ignore rule applies to it
+    let is_file_root = root.is_file();
+
+    // Values from the root's .ai-context.toml win over the app settings
+    let project = if is_file_root {
+        ProjectConfig::default()
+    } else {
+        ProjectConfig::load_or_default(root)
+    };
+
+    // Create gitignore manager if enabled; .gitignore files are loaded during the walk
+    let gitignore_manager = if is_file_root {
+        None
+    } else {
+        gitignore_manager_from_settings(root, db, &project)
+    };
+
+    let follow_symlinks = project.follow_symlinks.unwrap_or_else(|| {
+        get_setting_typed::<bool>(db, "follow_symlinks")
+            .ok()
+            .flatten()
+            .unwrap_or(AppSettings::default().follow_symlinks)
+    });
+    let include_hidden = get_setting_typed::<bool>(db, "include_hidden")
+        .ok()
+        .flatten()
+        .unwrap_or(AppSettings::default().include_hidden);
+    let max_depth = project.max_index_depth.unwrap_or_else(|| {
+        get_setting_typed::<u32>(db, "max_index_depth")
+            .ok()
+            .flatten()
+            .unwrap_or(AppSettings::default().max_index_depth)
+    }) as usize;
+    let batch_size = get_setting_typed::<u32>(db, "index_batch_size")
+        .ok()
+        .flatten()
+        .unwrap_or(AppSettings::default().index_batch_size)
+        .clamp(*INDEX_BATCH_SIZE_RANGE.start(), *INDEX_BATCH_SIZE_RANGE.end())
+        as usize;
+
+    // Single walk: list every entry first, so reading their metadata in parallel can report
+    // the real total. .gitignore files are discovered as the walk enters each directory.
+    let stats = Arc::new(WalkStats::default());
+    let walk = index_walk(
+        root,
+        gitignore_manager,
+        follow_symlinks,
+        include_hidden,
+        max_depth,
+        &project,
+        stats.clone(),
+    );
+    let listed = list_walk(walk, &emit_progress);
+    let collected = collect_entries(listed, max_depth, &emit_progress);
+
+    let ignored = stats.ignored.load(Ordering::Relaxed);
+    let gitignore_files = stats.gitignore_files.load(Ordering::Relaxed);
+    if ignored > 0 {
+        log::info!(
+            "Ignored {} entries using {} .gitignore files",
+            ignored,
+            gitignore_files
         );
     }
 
-    // Send final progress event
+    let total_entries = collected.entries.len();
+    log::info!(
+        "Collected {} entries, now inserting into database",
+        total_entries
+    );
+
+    // Second pass: batch insert into database
+    let mut conn = db
+        .lock()
+        .map_err(|e| AppError::Unknown(format!("Failed to lock database: {}", e)))?;
+
+    let written = insert_entries(&mut conn, collected.entries, batch_size).and_then(|summary| {
+        mark_index_root(&conn, root)?;
+        Ok(summary)
+    });
+    // A failed run may still have written some batches
+    search_cache.clear();
+    let mut summary = written?;
+    summary.ignored = ignored;
+    summary.gitignore_files = gitignore_files;
+    summary.error_count = collected.error_count;
+    summary.errors = collected.errors;
+
+    // Send final progress event.
+    // Failed entries were counted as processed too, so use the same counter as the
+    // intermediate events rather than the number of collected entries.
     let final_progress = IndexProgress {
-        processed: total_entries as u64,
-        total_estimate: total_entries as u64,
+        processed: collected.processed,
+        total_estimate: collected.processed,
         current_path: "Complete".to_string(),
-        errors: error_count.load(Ordering::Relaxed),
+        errors: collected.error_count,
     };
 
-    if let Err(e) = app.emit("indexing-progress", &final_progress) {
-        log::warn!("Failed to emit final progress event: {}", e);
-    }
+    emit_progress(&final_progress);
 
     log::info!(
-        "Parallel indexing complete: {} entries inserted, {} errors",
-        total_inserted,
-        error_count.load(Ordering::Relaxed)
+        "Parallel indexing complete: {} entries inserted, {} updated, {} errors",
+        summary.inserted,
+        summary.updated,
+        summary.error_count
     );
 
-    Ok(total_inserted)
+    Ok(summary)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use rusqlite::Connection;
-    use std::fs;
-    use std::path::PathBuf;
-    use tempfile::TempDir;
+/// Entries gathered by one indexing walk, with the failures met along the way
+struct CollectedEntries {
+    entries: Vec<FileEntry>,
+    /// Walk results seen, including failed ones
+    processed: u64,
+    error_count: u64,
+    /// The first `MAX_INDEX_ERRORS` failures
+    errors: Vec<IndexError>,
+}
 
-    fn create_test_db() -> Connection {
-        let conn = Connection::open_in_memory().unwrap();
-        crate::db::schema::init_database(&conn).unwrap();
-        conn
+/// Minimum time between two progress events
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Run the walk to completion, reporting throttled progress as entries are listed.
+///
+/// Listing only reads directories, so it is cheap next to reading every entry's metadata.
+/// While listing, `processed` stays at 0 and the total grows with the entries found so far,
+/// which never exceeds the final total.
+fn list_walk(
+    walk: impl Iterator<Item = walkdir::Result<walkdir::DirEntry>>,
+    emit_progress: impl Fn(&IndexProgress),
+) -> Vec<walkdir::Result<walkdir::DirEntry>> {
+    let mut listed = Vec::new();
+    let mut last_progress_time: Option<Instant> = None;
+    for entry_result in walk {
+        let now = Instant::now();
+        if last_progress_time.is_none_or(|last| now.duration_since(last) > PROGRESS_INTERVAL) {
+            last_progress_time = Some(now);
+            emit_progress(&IndexProgress {
+                processed: 0,
+                total_estimate: listed.len() as u64 + 1,
+                current_path: entry_result
+                    .as_ref()
+                    .map(|e| e.path().to_string_lossy().to_string())
+                    .unwrap_or_else(|_| "Unknown".to_string()),
+                errors: 0,
+            });
+        }
+        listed.push(entry_result);
     }
+    listed
+}
 
-    fn create_test_directory() -> TempDir {
-        let temp_dir = TempDir::new().unwrap();
-        let path = temp_dir.path();
+/// Turn listed walk results into entries in parallel, reporting throttled progress.
+///
+/// Every listed result counts as processed once, so the reported total is exact from the
+/// first event on. Entries that can't be read are skipped and recorded as errors instead of
+/// failing the run.
+fn collect_entries(
+    listed: Vec<walkdir::Result<walkdir::DirEntry>>,
+    max_depth: usize,
+    emit_progress: impl Fn(&IndexProgress) + Sync,
+) -> CollectedEntries {
+    let total = listed.len() as u64;
+    let processed_count = AtomicU64::new(0);
+    let error_count = AtomicU64::new(0);
+    let errors = Mutex::new(Vec::new());
+    let last_progress_time: Mutex<Option<Instant>> = Mutex::new(None);
+
+    let record_error = |path: Option<&Path>, message: String| {
+        error_count.fetch_add(1, Ordering::Relaxed);
+        let mut errors = errors.lock().unwrap();
+        if errors.len() < MAX_INDEX_ERRORS {
+            errors.push(IndexError {
+                path: path.map(|p| lossy_path_string(p).0).unwrap_or_default(),
+                message,
+            });
+        }
+    };
+
+    let entries: Vec<FileEntry> = listed
+        .into_par_iter()
+        .filter_map(|entry_result| {
+            processed_count.fetch_add(1, Ordering::Relaxed);
+
+            // Throttle progress events to max 10 per second.
+            // Counters are read and emitted under the lock so events never go backwards.
+            {
+                let mut last_time = last_progress_time.lock().unwrap();
+                let now = Instant::now();
+                if last_time.is_none_or(|last| now.duration_since(last) > PROGRESS_INTERVAL) {
+                    *last_time = Some(now);
+
+                    let current_path = entry_result
+                        .as_ref()
+                        .map(|e| e.path().to_string_lossy().to_string())
+                        .unwrap_or_else(|_| "Unknown".to_string());
+
+                    let processed = processed_count.load(Ordering::Relaxed);
+                    emit_progress(&IndexProgress {
+                        processed,
+                        total_estimate: total,
+                        current_path,
+                        errors: error_count.load(Ordering::Relaxed),
+                    });
+                }
+            }
+
+            match entry_result {
+                Ok(entry) => match FileEntry::from_walk_entry(&entry, max_depth) {
+                    Ok(file_entry) => Some(file_entry),
+                    Err(e) => {
+                        log::warn!("Failed to process entry {:?}: {}", entry.path(), e);
+                        record_error(Some(entry.path()), e.to_string());
+                        None
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Error during traversal: {}", e);
+                    record_error(e.path(), e.to_string());
+                    None
+                }
+            }
+        })
+        .collect();
+
+    CollectedEntries {
+        entries,
+        processed: processed_count.into_inner(),
+        error_count: error_count.into_inner(),
+        errors: errors.into_inner().unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_indexing_state_rejects_concurrent_run() {
+        let state = IndexingState::default();
+        assert!(!state.is_active());
+
+        let guard = state.try_begin().unwrap();
+        assert!(state.is_active());
+
+        let err = state.try_begin().err().unwrap();
+        assert_eq!(err.code, CommandError::BUSY);
+        assert!(err.message.contains("already in progress"));
+
+        drop(guard);
+        assert!(!state.is_active());
+        assert!(state.try_begin().is_ok());
+    }
+
+    #[test]
+    fn test_indexing_state_cleared_on_error_path() {
+        let state = IndexingState::default();
+        let run = |state: &IndexingState| -> CommandResult<()> {
+            let _guard = state.try_begin()?;
+            Err(CommandError::new(CommandError::IO, "disk went away"))
+        };
+
+        assert!(run(&state).is_err());
+        assert!(!state.is_active());
+    }
+
+    fn create_test_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_database(&conn).unwrap();
+        conn
+    }
+
+    fn create_test_directory() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path();
 
         // Create test directory structure
         fs::create_dir_all(path.join("folder1")).unwrap();
@@ -830,6 +2929,447 @@ mod tests {
         assert!(entry.fingerprint.is_some());
     }
 
+    #[test]
+    fn test_reveal_target_requires_indexed_existing_path() {
+        let temp_dir = create_test_directory();
+        let conn = create_test_db();
+        let file = normalize_path(&temp_dir.path().join("file1.txt").to_string_lossy());
+        let deleted = normalize_path(&temp_dir.path().join("folder1/file2.txt").to_string_lossy());
+        for path in [&file, &deleted] {
+            conn.execute(
+                "INSERT INTO files (path, name, is_dir) VALUES (?, ?, 0)",
+                params![path, path.rsplit('/').next().unwrap()],
+            )
+            .unwrap();
+        }
+        fs::remove_file(&deleted).unwrap();
+
+        assert_eq!(
+            reveal_target(&conn, &file.replace('/', "\\")).unwrap(),
+            file
+        );
+
+        let err = reveal_target(&conn, &deleted).unwrap_err();
+        assert_eq!(err.code, CommandError::NOT_FOUND);
+        assert!(err.message.contains("no longer exists"));
+
+        let unindexed = temp_dir.path().join("folder2/file3.txt");
+        let err = reveal_target(&conn, &unindexed.to_string_lossy()).unwrap_err();
+        assert_eq!(err.code, CommandError::NOT_FOUND);
+        assert!(err.message.contains("not found in index"));
+    }
+
+    #[test]
+    fn test_progress_total_with_uneven_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("a")).unwrap();
+        fs::create_dir_all(root.join("b")).unwrap();
+        for file in 0..10 {
+            fs::write(root.join("a").join(format!("{}.txt", file)), "x").unwrap();
+        }
+        fs::write(root.join("b/x.txt"), "x").unwrap();
+
+        let project = ProjectConfig::default();
+        let events = Mutex::new(Vec::new());
+        let record = |progress: &IndexProgress| {
+            events
+                .lock()
+                .unwrap()
+                .push((progress.processed, progress.total_estimate))
+        };
+        let walk = index_walk(root, None, false, true, 0, &project, Arc::default());
+        let listed = list_walk(walk, record);
+        let collected = collect_entries(listed, 0, record);
+        let final_total = collected.processed;
+        assert_eq!(final_total, 14);
+
+        // The first entry of each phase is always reported
+        let events = events.into_inner().unwrap();
+        assert_eq!(events.first(), Some(&(0, 1)));
+        assert!(events.iter().any(|&(processed, _)| processed > 0));
+
+        let mut previous = (0, 0);
+        for &(processed, total) in &events {
+            assert!(processed >= previous.0, "processed went backwards");
+            assert!(total >= previous.1, "total went backwards");
+            assert!(total <= final_total, "total {} overshot", total);
+            if processed > 0 {
+                assert_eq!(total, final_total);
+            }
+            previous = (processed, total);
+        }
+    }
+
+    #[test]
+    fn test_max_depth_keeps_boundary_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("a/b/c")).unwrap();
+        fs::write(root.join("a/top.txt"), "top").unwrap();
+        fs::write(root.join("a/b/mid.txt"), "mid").unwrap();
+        fs::write(root.join("a/b/c/deep.txt"), "deep").unwrap();
+
+        let entries: Vec<FileEntry> =
+            filtered_walk(root, None, false, true, 2, Arc::default())
+                .filter_map(|e| e.ok())
+                .map(|e| FileEntry::from_walk_entry(&e, 2).unwrap())
+                .collect();
+
+        let mut conn = create_test_db();
+        insert_entries(&mut conn, entries, 1000).unwrap();
+
+        let a = normalize_path(root.join("a").to_str().unwrap());
+        let children = get_children_internal(&conn, Some(&a), SortKey::Name, false, None).unwrap();
+        let names: Vec<&str> = children.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["b", "top.txt"]);
+
+        // The boundary directory is visible and flagged, but nothing below it was indexed
+        assert!(children[0].truncated_at_depth);
+        assert_eq!(children[0].child_count, Some(0));
+        assert!(!children[1].truncated_at_depth);
+
+        let deeper: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM files WHERE name IN ('mid.txt', 'c', 'deep.txt')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(deeper, 0);
+    }
+
+    fn synthetic_entries(count: usize) -> Vec<FileEntry> {
+        (0..count)
+            .map(|i| FileEntry {
+                path: format!("/batch/file{}.txt", i),
+                parent_path: Some("/batch".to_string()),
+                name: format!("file{}.txt", i),
+                size: Some(1),
+                mtime: Some(1),
+                is_dir: false,
+                token_count: None,
+                fingerprint: Some("1_1".to_string()),
+                child_count: None,
+                symlink_target: None,
+                truncated_at_depth: false,
+                language: None,
+                lossy_path: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_count_selection_tokens_deduplicates_overlaps() {
+        let conn = create_test_db();
+        let insert = |path: &str, parent: Option<&str>, is_dir: bool, token_count: Option<i64>| {
+            let name = path.rsplit('/').next().unwrap();
+            conn.execute(
+                "INSERT INTO files (path, parent_path, name, is_dir, token_count)
+                 VALUES (?, ?, ?, ?, ?)",
+                params![path, parent, name, is_dir as i32, token_count],
+            )
+            .unwrap();
+        };
+        insert("/proj", None, true, None);
+        insert("/proj/README.md", Some("/proj"), false, Some(30));
+        insert("/proj/src", Some("/proj"), true, None);
+        insert("/proj/src/main.rs", Some("/proj/src"), false, Some(100));
+        insert("/proj/src/lib.rs", Some("/proj/src"), false, Some(200));
+        insert("/proj/src/new.rs", Some("/proj/src"), false, None);
+
+        let selection = vec![
+            "/proj/src".to_string(),
+            "/proj/src/main.rs".to_string(),
+            "/proj".to_string(),
+            "/proj/missing.rs".to_string(),
+        ];
+        let counted = count_selection_tokens_internal(&conn, &selection).unwrap();
+
+        assert_eq!(counted.total_tokens, 330);
+        assert_eq!(counted.file_count, 4);
+        assert_eq!(counted.uncounted_files, 1);
+
+        let per_path: Vec<(&str, i64, i64)> = counted
+            .paths
+            .iter()
+            .map(|p| (p.path.as_str(), p.tokens, p.file_count))
+            .collect();
+        assert_eq!(
+            per_path,
+            vec![
+                ("/proj/src", 300, 3),
+                ("/proj/src/main.rs", 100, 1),
+                ("/proj", 330, 4),
+                ("/proj/missing.rs", 0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_file_tree() {
+        let temp_dir = create_test_directory();
+        let root = temp_dir.path();
+        let entries: Vec<FileEntry> =
+            filtered_walk(root, None, false, true, 0, Arc::default())
+                .filter_map(|e| e.ok())
+                .map(|e| FileEntry::from_walk_entry(&e, 0).unwrap())
+                .collect();
+        let mut conn = create_test_db();
+        insert_entries(&mut conn, entries, 1000).unwrap();
+        let root_str = normalize_path(root.to_str().unwrap());
+        let root_name = root.file_name().unwrap().to_str().unwrap();
+
+        let tree = render_file_tree_internal(&conn, &root_str, None, TreeAnnotation::None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            tree,
+            format!(
+                "{}/\n\
+                 ├── folder1/\n\
+                 │   └── file2.txt\n\
+                 ├── folder2/\n\
+                 │   ├── subfolder/\n\
+                 │   │   └── file4.txt\n\
+                 │   └── file3.txt\n\
+                 └── file1.txt\n",
+                root_name
+            )
+        );
+
+        let tree = render_file_tree_internal(&conn, &root_str, Some(1), TreeAnnotation::Size)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            tree,
+            format!(
+                "{}/\n├── folder1/\n├── folder2/\n└── file1.txt (8 B)\n",
+                root_name
+            )
+        );
+
+        assert_eq!(
+            render_file_tree_internal(&conn, "/not/indexed", None, TreeAnnotation::None).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MB");
+    }
+
+    #[test]
+    fn test_suggest_files_respects_budget() {
+        let conn = create_test_db();
+        let insert = |path: &str,
+                      parent: Option<&str>,
+                      is_dir: bool,
+                      token_count: Option<i64>,
+                      size: Option<i64>,
+                      language: Option<&str>| {
+            let name = path.rsplit('/').next().unwrap();
+            conn.execute(
+                "INSERT INTO files (path, parent_path, name, is_dir, token_count, size, language)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+                params![path, parent, name, is_dir as i32, token_count, size, language],
+            )
+            .unwrap();
+        };
+        insert("/proj", None, true, None, None, None);
+        insert("/proj/main.rs", Some("/proj"), false, Some(400), None, Some("rust"));
+        insert("/proj/lib.rs", Some("/proj"), false, Some(100), None, Some("rust"));
+        insert("/proj/big.rs", Some("/proj"), false, Some(5000), None, Some("rust"));
+        insert("/proj/notes.txt", Some("/proj"), false, Some(50), None, None);
+        insert("/proj/src", Some("/proj"), true, None, None, None);
+        // No token count yet: estimated from 800 bytes as 200 tokens
+        insert("/proj/src/util.rs", Some("/proj/src"), false, None, Some(800), Some("rust"));
+        insert("/other/outside.rs", Some("/other"), false, Some(1), None, Some("rust"));
+
+        let suggestion = suggest_files_internal(&conn, "/proj", 650).unwrap();
+        // Source files smallest first; main.rs (400) no longer fits after lib.rs and util.rs,
+        // but the smaller notes.txt still does
+        assert_eq!(
+            suggestion.paths,
+            vec!["/proj/lib.rs", "/proj/src/util.rs", "/proj/notes.txt"]
+        );
+        assert_eq!(suggestion.total_tokens, 350);
+
+        let suggestion = suggest_files_internal(&conn, "/proj", 10_000).unwrap();
+        assert_eq!(suggestion.paths.len(), 5);
+        assert_eq!(suggestion.total_tokens, 5750);
+
+        let suggestion = suggest_files_internal(&conn, "/proj", 10).unwrap();
+        assert!(suggestion.paths.is_empty());
+        assert_eq!(suggestion.total_tokens, 0);
+    }
+
+    #[test]
+    fn test_insert_entries_with_custom_batch_size() {
+        let mut conn = create_test_db();
+
+        let summary = insert_entries(&mut conn, synthetic_entries(253), 100).unwrap();
+
+        assert_eq!(summary.inserted, 253);
+        assert_eq!(summary.updated, 0);
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 253);
+    }
+
+    #[test]
+    fn test_insert_entries_rolls_back_on_failed_batch() {
+        let mut conn = create_test_db();
+        conn.execute_batch(
+            "CREATE TRIGGER fail_insert BEFORE INSERT ON files
+             WHEN NEW.name = 'file250.txt'
+             BEGIN SELECT RAISE(ABORT, 'boom'); END;",
+        )
+        .unwrap();
+
+        // The third batch fails after two have been written
+        assert!(insert_entries(&mut conn, synthetic_entries(253), 100).is_err());
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_collect_entries_records_unreadable_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("ok.txt"), "fine").unwrap();
+        // Following a dangling link fails, which stands in for an unreadable entry
+        std::os::unix::fs::symlink(root.join("missing"), root.join("dangling")).unwrap();
+
+        let walk = filtered_walk(root, None, true, true, 0, Arc::default());
+        let collected = collect_entries(walk.collect(), 0, |_| {});
+
+        let names: Vec<&str> = collected.entries.iter().map(|e| e.name.as_str()).collect();
+        assert!(names.contains(&"ok.txt"));
+        assert!(!names.contains(&"dangling"));
+        assert_eq!(collected.error_count, 1);
+        assert_eq!(collected.errors.len(), 1);
+        assert_eq!(
+            collected.errors[0].path,
+            normalize_path(root.join("dangling").to_str().unwrap())
+        );
+        assert!(!collected.errors[0].message.is_empty());
+        assert_eq!(collected.processed, collected.entries.len() as u64 + 1);
+    }
+
+    #[test]
+    fn test_nested_roots_stay_at_top_level() {
+        let temp_dir = TempDir::new().unwrap();
+        let outer = temp_dir.path().join("outer");
+        let inner = outer.join("inner");
+        fs::create_dir_all(&inner).unwrap();
+        fs::write(inner.join("a.txt"), "a").unwrap();
+        fs::write(outer.join("b.txt"), "b").unwrap();
+
+        let mut conn = create_test_db();
+        let index = |conn: &mut rusqlite::Connection, root: &Path| {
+            let walk = filtered_walk(root, None, false, true, 0, Arc::default());
+            let collected = collect_entries(walk.collect(), 0, |_| {});
+            insert_entries(conn, collected.entries, 100).unwrap();
+            mark_index_root(conn, root).unwrap();
+        };
+        let top_level = |conn: &rusqlite::Connection| -> Vec<String> {
+            get_children_internal(conn, None, SortKey::Name, false, None)
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.name)
+                .collect()
+        };
+
+        index(&mut conn, &inner);
+        assert_eq!(top_level(&conn), vec!["inner"]);
+
+        // Indexing the parent afterwards must not pull the first root out of the top level
+        index(&mut conn, &outer);
+        assert_eq!(top_level(&conn), vec!["inner", "outer"]);
+        let outer_children = get_children_internal(
+            &conn,
+            Some(&normalize_path(outer.to_str().unwrap())),
+            SortKey::Name,
+            false,
+            None,
+        )
+        .unwrap();
+        assert!(outer_children.iter().any(|entry| entry.name == "inner"));
+
+        // Re-indexing the nested root keeps the flag
+        index(&mut conn, &inner);
+        assert_eq!(top_level(&conn), vec!["inner", "outer"]);
+        assert_eq!(get_index_stats_internal(&conn).unwrap().root_count, 2);
+    }
+
+    #[test]
+    fn test_insert_entries_counts_updates() {
+        let mut conn = create_test_db();
+        insert_entries(&mut conn, synthetic_entries(10), 100).unwrap();
+
+        let mut entries = synthetic_entries(12);
+        entries[0].fingerprint = Some("changed".to_string());
+        let summary = insert_entries(&mut conn, entries, 100).unwrap();
+
+        assert_eq!(summary.inserted, 2);
+        assert_eq!(summary.updated, 1);
+    }
+
+    #[test]
+    fn test_indexing_stores_language() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("Dockerfile"), "FROM rust").unwrap();
+        fs::write(root.join("notes.txt"), "notes").unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+
+        let entries: Vec<FileEntry> =
+            filtered_walk(root, None, false, true, 0, Arc::default())
+                .filter_map(|e| e.ok())
+                .map(|e| FileEntry::from_walk_entry(&e, 0).unwrap())
+                .collect();
+
+        let mut conn = create_test_db();
+        // An unchanged row indexed before languages were detected still gets one
+        let main_rs = entries.iter().find(|e| e.name == "main.rs").unwrap();
+        conn.execute(
+            "INSERT INTO files (path, parent_path, name, is_dir, fingerprint) VALUES (?, ?, ?, 0, ?)",
+            params![main_rs.path, main_rs.parent_path, main_rs.name, main_rs.fingerprint],
+        )
+        .unwrap();
+        insert_entries(&mut conn, entries, 1000).unwrap();
+
+        let root_str = normalize_path(root.to_str().unwrap());
+        let children =
+            get_children_internal(&conn, Some(&root_str), SortKey::Name, false, None).unwrap();
+        let languages: Vec<(&str, Option<&str>)> = children
+            .iter()
+            .map(|e| (e.name.as_str(), e.language.as_deref()))
+            .collect();
+        assert_eq!(
+            languages,
+            vec![
+                ("src", None),
+                ("Dockerfile", Some("dockerfile")),
+                ("notes.txt", None),
+            ]
+        );
+
+        let results = search_db(&conn, "main", true, false).unwrap();
+        assert_eq!(results[0].language.as_deref(), Some("rust"));
+    }
+
     #[test]
     fn test_traverse_and_insert() {
         let temp_dir = create_test_directory();
@@ -1039,9 +3579,335 @@ mod tests {
         assert_eq!(folder2_children, 1, "folder2 should have 1 child");
     }
 
+    mod children_tests {
+        use super::*;
+
+        fn insert_entry(
+            conn: &Connection,
+            path: &str,
+            parent: Option<&str>,
+            is_dir: bool,
+            size: Option<i64>,
+            mtime: i64,
+        ) {
+            let name = path.rsplit('/').next().unwrap();
+            conn.execute(
+                "INSERT INTO files (path, parent_path, name, size, mtime, is_dir) VALUES (?, ?, ?, ?, ?, ?)",
+                params![path, parent, name, size, mtime, is_dir as i32],
+            )
+            .unwrap();
+        }
+
+        fn populate(conn: &Connection) {
+            insert_entry(conn, "/root", None, true, None, 100);
+            insert_entry(conn, "/root/b.txt", Some("/root"), false, Some(300), 30);
+            insert_entry(conn, "/root/a.txt", Some("/root"), false, Some(100), 20);
+            insert_entry(conn, "/root/c.txt", Some("/root"), false, Some(200), 10);
+            insert_entry(conn, "/root/zdir", Some("/root"), true, None, 40);
+        }
+
+        fn names(entries: &[FileEntry]) -> Vec<&str> {
+            entries.iter().map(|e| e.name.as_str()).collect()
+        }
+
+        #[test]
+        fn test_indexed_roots_keeps_legacy_roots_beside_flagged_ones() {
+            let conn = create_test_db();
+            // Indexed before roots were flagged
+            insert_entry(&conn, "/legacy", Some("/"), true, None, 10);
+            insert_entry(&conn, "/legacy/a.txt", Some("/legacy"), false, Some(1), 10);
+            // Indexed again since, so flagged
+            populate(&conn);
+            conn.execute("UPDATE files SET is_root = 1 WHERE path = '/root'", [])
+                .unwrap();
+            // An orphan inside the flagged root is not a root of its own
+            insert_entry(
+                &conn,
+                "/root/gone/x.txt",
+                Some("/root/gone"),
+                false,
+                Some(1),
+                10,
+            );
+
+            let mut roots = indexed_roots(&conn).unwrap();
+            roots.sort();
+            assert_eq!(roots, vec!["/legacy", "/root"]);
+
+            // Without any flag, every top-level entry counts
+            conn.execute("UPDATE files SET is_root = 0", []).unwrap();
+            let mut roots = indexed_roots(&conn).unwrap();
+            roots.sort();
+            assert_eq!(roots, vec!["/legacy", "/root", "/root/gone/x.txt"]);
+        }
+
+        #[test]
+        fn test_get_recent_files() {
+            let conn = create_test_db();
+            populate(&conn);
+            insert_entry(&conn, "/root/zdir/new.txt", Some("/root/zdir"), false, Some(50), 50);
+
+            let recent = get_recent_files_internal(&conn, 3).unwrap();
+            assert_eq!(names(&recent), vec!["new.txt", "b.txt", "a.txt"]);
+
+            // Directories are never listed, however recently they changed
+            let all = get_recent_files_internal(&conn, 10).unwrap();
+            assert_eq!(names(&all), vec!["new.txt", "b.txt", "a.txt", "c.txt"]);
+        }
+
+        #[test]
+        fn test_default_order_is_dirs_first_then_name() {
+            let conn = create_test_db();
+            populate(&conn);
+
+            let entries =
+                get_children_internal(&conn, Some("/root"), SortKey::Name, false, None).unwrap();
+            assert_eq!(names(&entries), vec!["zdir", "a.txt", "b.txt", "c.txt"]);
+
+            let entries =
+                get_children_internal(&conn, Some("/root"), SortKey::Name, true, None).unwrap();
+            assert_eq!(names(&entries), vec!["zdir", "c.txt", "b.txt", "a.txt"]);
+        }
+
+        #[test]
+        fn test_sort_by_size_descending() {
+            let conn = create_test_db();
+            populate(&conn);
+
+            let entries =
+                get_children_internal(&conn, Some("/root"), SortKey::Size, true, None).unwrap();
+            // The directory has no size, so it sorts last when descending
+            assert_eq!(names(&entries), vec!["b.txt", "c.txt", "a.txt", "zdir"]);
+        }
+
+        #[test]
+        fn test_sort_by_mtime_ascending() {
+            let conn = create_test_db();
+            populate(&conn);
+
+            let entries =
+                get_children_internal(&conn, Some("/root"), SortKey::Mtime, false, None).unwrap();
+            assert_eq!(names(&entries), vec!["c.txt", "a.txt", "b.txt", "zdir"]);
+        }
+
+        #[test]
+        fn test_root_children_include_orphans() {
+            let conn = create_test_db();
+            populate(&conn);
+            insert_entry(
+                &conn,
+                "/other/orphan.txt",
+                Some("/other"),
+                false,
+                Some(1),
+                1,
+            );
+
+            let entries = get_children_internal(&conn, None, SortKey::Name, false, None).unwrap();
+            assert_eq!(names(&entries), vec!["root", "orphan.txt"]);
+            assert_eq!(entries[0].child_count, Some(4));
+        }
+
+        #[test]
+        fn test_pagination_over_large_directory() {
+            let conn = create_test_db();
+            insert_entry(&conn, "/big", None, true, None, 0);
+            for i in 0..1000 {
+                let path = format!("/big/file{:04}.txt", i);
+                insert_entry(&conn, &path, Some("/big"), false, Some(i), i);
+            }
+
+            assert_eq!(count_children_internal(&conn, Some("/big")).unwrap(), 1000);
+            assert_eq!(count_children_internal(&conn, None).unwrap(), 1);
+
+            let first =
+                get_children_internal(&conn, Some("/big"), SortKey::Name, false, Some((0, 100)))
+                    .unwrap();
+            assert_eq!(first.len(), 100);
+            assert_eq!(first[0].name, "file0000.txt");
+            assert_eq!(first[99].name, "file0099.txt");
+
+            let second =
+                get_children_internal(&conn, Some("/big"), SortKey::Name, false, Some((100, 100)))
+                    .unwrap();
+            assert_eq!(second[0].name, "file0100.txt");
+
+            let last =
+                get_children_internal(&conn, Some("/big"), SortKey::Name, false, Some((950, 100)))
+                    .unwrap();
+            assert_eq!(last.len(), 50);
+            assert_eq!(last[49].name, "file0999.txt");
+
+            // Walking every page yields each child exactly once
+            let mut seen = Vec::new();
+            for offset in (0..1000).step_by(250) {
+                let page = get_children_internal(
+                    &conn,
+                    Some("/big"),
+                    SortKey::Size,
+                    true,
+                    Some((offset, 250)),
+                )
+                .unwrap();
+                seen.extend(page.into_iter().map(|e| e.path));
+            }
+            seen.sort();
+            seen.dedup();
+            assert_eq!(seen.len(), 1000);
+        }
+
+        #[test]
+        fn test_pagination_is_stable_for_equal_sort_values() {
+            let conn = create_test_db();
+            insert_entry(&conn, "/same", None, true, None, 0);
+            for i in 0..20 {
+                let path = format!("/same/f{:02}", i);
+                // Every child shares the same mtime
+                insert_entry(&conn, &path, Some("/same"), false, Some(1), 5);
+            }
+
+            let all =
+                get_children_internal(&conn, Some("/same"), SortKey::Mtime, false, None).unwrap();
+            let paged: Vec<_> = (0..4)
+                .flat_map(|page| {
+                    get_children_internal(
+                        &conn,
+                        Some("/same"),
+                        SortKey::Mtime,
+                        false,
+                        Some((page * 5, 5)),
+                    )
+                    .unwrap()
+                })
+                .collect();
+
+            assert_eq!(names(&all), names(&paged));
+        }
+
+        #[test]
+        fn test_sort_key_deserializes_from_snake_case() {
+            let key: SortKey = serde_json::from_str("\"token_count\"").unwrap();
+            assert_eq!(key, SortKey::TokenCount);
+        }
+    }
+
     mod search_filter_tests {
         use super::*;
 
+        const NOW: i64 = 1_700_000_000;
+        const DAY: i64 = 24 * 60 * 60;
+
+        #[test]
+        fn test_source_adjustment() {
+            assert_eq!(
+                source_adjustment("utils.ts", "/p/src/utils.ts", false, Some("typescript")),
+                2
+            );
+            assert_eq!(
+                source_adjustment("app.min.js", "/p/app.min.js", false, Some("javascript")),
+                -3
+            );
+            assert_eq!(
+                source_adjustment("app.js.map", "/p/app.js.map", false, None),
+                -3
+            );
+            assert_eq!(
+                source_adjustment("Cargo.lock", "/p/Cargo.lock", false, None),
+                -3
+            );
+            assert_eq!(
+                source_adjustment(
+                    "package-lock.json",
+                    "/p/package-lock.json",
+                    false,
+                    Some("json")
+                ),
+                -3
+            );
+            assert_eq!(
+                source_adjustment("lib.rs", "/p/vendor/x/lib.rs", false, Some("rust")),
+                -3
+            );
+            assert_eq!(source_adjustment("dist", "/p/dist", true, None), -3);
+            assert_eq!(
+                source_adjustment("notes.txt", "/p/notes.txt", false, None),
+                0
+            );
+            // Only whole path components count as generated directories
+            assert_eq!(
+                source_adjustment("a.rs", "/p/distro/a.rs", false, Some("rust")),
+                2
+            );
+        }
+
+        #[test]
+        fn test_compute_score_prefers_shallow_recent_match() {
+            let shallow_recent = compute_score(
+                "utils.rs",
+                "/p/src/utils.rs",
+                "utils",
+                false,
+                1,
+                Some(NOW - 60),
+                NOW,
+            );
+            let deep_old = compute_score(
+                "utils.rs",
+                "/p/vendor/lib/x/y/utils.rs",
+                "utils",
+                false,
+                5,
+                Some(NOW - 365 * DAY),
+                NOW,
+            );
+
+            assert!(shallow_recent > deep_old);
+        }
+
+        #[test]
+        fn test_compute_score_tiers_stay_dominant() {
+            // A deep, old exact match still beats a shallow, fresh prefix match
+            let deep_exact =
+                compute_score("app", "/p/a/b/c/d/e/f/app", "app", false, 10, None, NOW);
+            let shallow_prefix = compute_score(
+                "application",
+                "/p/application",
+                "app",
+                false,
+                0,
+                Some(NOW),
+                NOW,
+            );
+            assert!(deep_exact > shallow_prefix);
+
+            // Neither adjustment turns a non-match into a match
+            assert_eq!(
+                compute_score("main.rs", "/p/main.rs", "xyz", false, 0, Some(NOW), NOW),
+                0
+            );
+            // A deep path-only match is still kept
+            assert!(
+                compute_score(
+                    "mod.rs",
+                    "/p/query/a/b/c/d/mod.rs",
+                    "query",
+                    false,
+                    6,
+                    None,
+                    NOW
+                ) > 0
+            );
+        }
+
+        #[test]
+        fn test_recency_bonus() {
+            assert_eq!(recency_bonus(Some(NOW - 60), NOW), 3);
+            assert_eq!(recency_bonus(Some(NOW - 3 * DAY), NOW), 2);
+            assert_eq!(recency_bonus(Some(NOW - 20 * DAY), NOW), 1);
+            assert_eq!(recency_bonus(Some(NOW - 90 * DAY), NOW), 0);
+            assert_eq!(recency_bonus(None, NOW), 0);
+        }
+
         #[test]
         fn test_parse_empty_query() {
             let f = parse_search_query("");
@@ -1065,105 +3931,715 @@ mod tests {
             assert!(f.plain_text.is_none());
         }
 
-        #[test]
-        fn test_parse_dir_prefix() {
-            let f = parse_search_query("dir:src");
-            assert_eq!(f.directory_name.as_deref(), Some("src"));
-            assert!(f.plain_text.is_none());
+        #[test]
+        fn test_parse_dir_prefix() {
+            let f = parse_search_query("dir:src");
+            assert_eq!(f.directory_name.as_deref(), Some("src"));
+            assert!(f.plain_text.is_none());
+        }
+
+        #[test]
+        fn test_parse_combined_file_dir() {
+            let f = parse_search_query("file:App dir:src");
+            assert_eq!(f.file_name.as_deref(), Some("App"));
+            assert_eq!(f.directory_name.as_deref(), Some("src"));
+        }
+
+        #[test]
+        fn test_parse_plain_text() {
+            let f = parse_search_query("test");
+            assert_eq!(f.plain_text.as_deref(), Some("test"));
+            assert!(f.regex_pattern.is_none());
+        }
+
+        #[test]
+        fn test_parse_regex_pattern() {
+            let f = parse_search_query("\\.test\\.ts$");
+            assert!(f.regex_pattern.is_some());
+            assert!(f.plain_text.is_none());
+        }
+
+        #[test]
+        fn test_parse_invalid_regex_falls_back_to_plain() {
+            let f = parse_search_query("[invalid");
+            assert!(f.regex_pattern.is_none());
+            assert_eq!(f.plain_text.as_deref(), Some("[invalid"));
+        }
+
+        #[test]
+        fn test_parse_mixed_file_and_plain() {
+            let f = parse_search_query("file:App test");
+            assert_eq!(f.file_name.as_deref(), Some("App"));
+            assert_eq!(f.plain_text.as_deref(), Some("test"));
+        }
+
+        #[test]
+        fn test_parse_case_insensitive_prefix() {
+            let f = parse_search_query("FILE:App DIR:src");
+            assert_eq!(f.file_name.as_deref(), Some("App"));
+            assert_eq!(f.directory_name.as_deref(), Some("src"));
+        }
+
+        #[test]
+        fn test_parse_size_filters() {
+            const KB: i64 = 1024;
+            const MB: i64 = 1024 * 1024;
+
+            let f = parse_search_query("size:>1mb");
+            assert_eq!(
+                f.size_range,
+                Some(RangeFilter {
+                    min: Some(MB + 1),
+                    max: None
+                })
+            );
+            assert!(f.plain_text.is_none());
+
+            let f = parse_search_query("size:<100kb");
+            assert_eq!(
+                f.size_range,
+                Some(RangeFilter {
+                    min: None,
+                    max: Some(100 * KB - 1)
+                })
+            );
+
+            let f = parse_search_query("SIZE:>=2KB");
+            assert_eq!(
+                f.size_range,
+                Some(RangeFilter {
+                    min: Some(2 * KB),
+                    max: None
+                })
+            );
+
+            let f = parse_search_query("size:10kb..1mb report");
+            assert_eq!(
+                f.size_range,
+                Some(RangeFilter {
+                    min: Some(10 * KB),
+                    max: Some(MB)
+                })
+            );
+            assert_eq!(f.plain_text.as_deref(), Some("report"));
+
+            let f = parse_search_query("size:1.5mb");
+            assert_eq!(
+                f.size_range,
+                Some(RangeFilter {
+                    min: Some(MB * 3 / 2),
+                    max: Some(MB * 3 / 2)
+                })
+            );
+
+            // Unparseable size expressions are searched as text
+            let f = parse_search_query("size:huge");
+            assert!(f.size_range.is_none());
+            assert_eq!(f.plain_text.as_deref(), Some("size:huge"));
+        }
+
+        #[test]
+        fn test_parse_modified_absolute_dates() {
+            const DAY: i64 = 24 * 60 * 60;
+            // 2024-01-01T00:00:00Z
+            let jan1 = 1_704_067_200;
+
+            assert_eq!(
+                parse_modified_range(">2024-01-01", 0),
+                Some(RangeFilter {
+                    min: Some(jan1 + DAY),
+                    max: None
+                })
+            );
+            assert_eq!(
+                parse_modified_range(">=2024-01-01", 0),
+                Some(RangeFilter {
+                    min: Some(jan1),
+                    max: None
+                })
+            );
+            assert_eq!(
+                parse_modified_range("<2024-01-01", 0),
+                Some(RangeFilter {
+                    min: None,
+                    max: Some(jan1 - 1)
+                })
+            );
+            assert_eq!(
+                parse_modified_range("2024-01-01", 0),
+                Some(RangeFilter {
+                    min: Some(jan1),
+                    max: Some(jan1 + DAY - 1)
+                })
+            );
+
+            let f = parse_search_query("modified:>2024-01-01 plan");
+            assert_eq!(
+                f.mtime_range,
+                Some(RangeFilter {
+                    min: Some(jan1 + DAY),
+                    max: None
+                })
+            );
+            assert_eq!(f.plain_text.as_deref(), Some("plan"));
+        }
+
+        #[test]
+        fn test_parse_modified_relative_durations() {
+            let now = 1_700_000_000;
+
+            assert_eq!(
+                parse_modified_range("<7d", now),
+                Some(RangeFilter {
+                    min: Some(now - 7 * 24 * 3600),
+                    max: None
+                })
+            );
+            assert_eq!(
+                parse_modified_range("<24h", now),
+                Some(RangeFilter {
+                    min: Some(now - 24 * 3600),
+                    max: None
+                })
+            );
+            assert_eq!(
+                parse_modified_range(">2w", now),
+                Some(RangeFilter {
+                    min: None,
+                    max: Some(now - 14 * 24 * 3600)
+                })
+            );
+        }
+
+        #[test]
+        fn test_parse_modified_invalid_falls_back_to_plain() {
+            for expr in ["modified:yesterday", "modified:2023-02-30", "modified:<7y"] {
+                let f = parse_search_query(expr);
+                assert!(f.mtime_range.is_none(), "{} should not parse", expr);
+                assert_eq!(f.plain_text.as_deref(), Some(expr));
+            }
+        }
+    }
+
+    mod search_integration_tests {
+        use super::*;
+
+        fn populate_test_db(conn: &Connection) {
+            // Create a realistic file structure:
+            // /project/src/App.tsx
+            // /project/src/components/Header.tsx
+            // /project/src/components/Footer.tsx
+            // /project/src/lib/utils.ts
+            // /project/conductor/tracks/track1.md
+            // /project/conductor/tracks/track2.md
+            // /project/conductor/plan.md
+            // /project/docs/plan.md  (duplicate name)
+            // /project/tests/app.test.ts
+            // /project/node_modules/react/index.js  (dir)
+            let entries = vec![
+                ("/project", None, "project", true),
+                ("/project/src", Some("/project"), "src", true),
+                ("/project/src/App.tsx", Some("/project/src"), "App.tsx", false),
+                ("/project/src/components", Some("/project/src"), "components", true),
+                ("/project/src/components/Header.tsx", Some("/project/src/components"), "Header.tsx", false),
+                ("/project/src/components/Footer.tsx", Some("/project/src/components"), "Footer.tsx", false),
+                ("/project/src/lib", Some("/project/src"), "lib", true),
+                ("/project/src/lib/utils.ts", Some("/project/src/lib"), "utils.ts", false),
+                ("/project/conductor", Some("/project"), "conductor", true),
+                ("/project/conductor/tracks", Some("/project/conductor"), "tracks", true),
+                ("/project/conductor/tracks/track1.md", Some("/project/conductor/tracks"), "track1.md", false),
+                ("/project/conductor/tracks/track2.md", Some("/project/conductor/tracks"), "track2.md", false),
+                ("/project/conductor/plan.md", Some("/project/conductor"), "plan.md", false),
+                ("/project/docs", Some("/project"), "docs", true),
+                ("/project/docs/plan.md", Some("/project/docs"), "plan.md", false),
+                ("/project/tests", Some("/project"), "tests", true),
+                ("/project/tests/app.test.ts", Some("/project/tests"), "app.test.ts", false),
+            ];
+
+            for (path, parent, name, is_dir) in entries {
+                conn.execute(
+                    "INSERT INTO files (path, parent_path, name, size, mtime, is_dir, fingerprint)
+                     VALUES (?, ?, ?, ?, ?, ?, ?)",
+                    params![path, parent, name, if is_dir { None::<i64> } else { Some(100) }, Some(1000i64), is_dir as i32, None::<String>],
+                ).unwrap();
+            }
+        }
+
+        #[test]
+        fn test_search_size_filter() {
+            let conn = create_test_db();
+            populate_test_db(&conn);
+            conn.execute(
+                "UPDATE files SET size = ? WHERE path = '/project/src/lib/utils.ts'",
+                params![2 * 1024 * 1024],
+            )
+            .unwrap();
+
+            let large = search_db(&conn, "size:>1mb", true, false).unwrap();
+            assert_eq!(large.len(), 1);
+            assert_eq!(large[0].name, "utils.ts");
+            assert_eq!(large[0].score, 0);
+
+            // Directories have no size and never match
+            let small = search_db(&conn, "size:<1kb", true, false).unwrap();
+            assert_eq!(small.len(), 8);
+            assert!(small.iter().all(|r| !r.is_dir));
+
+            let small_plan = search_db(&conn, "size:<1kb plan", true, false).unwrap();
+            assert_eq!(small_plan.len(), 2);
+            assert!(small_plan.iter().all(|r| r.name == "plan.md"));
+        }
+
+        #[test]
+        fn test_search_modified_filter() {
+            let conn = create_test_db();
+            populate_test_db(&conn);
+            let now = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs() as i64;
+            conn.execute(
+                "UPDATE files SET mtime = ? WHERE path = '/project/docs/plan.md'",
+                params![now - 60],
+            )
+            .unwrap();
+
+            let recent = search_db(&conn, "modified:<24h", true, false).unwrap();
+            assert_eq!(recent.len(), 1);
+            assert_eq!(recent[0].path, "/project/docs/plan.md");
+
+            // Every fixture entry but the touched one dates from 1970-01-01
+            let old = search_db(&conn, "modified:<=1970-01-01", true, false).unwrap();
+            assert_eq!(old.len(), 16);
+            assert!(old.iter().all(|r| r.path != "/project/docs/plan.md"));
+
+            let old_plans = search_db(&conn, "modified:>7d plan", true, false).unwrap();
+            assert_eq!(old_plans.len(), 1);
+            assert_eq!(old_plans[0].path, "/project/conductor/plan.md");
+        }
+
+        #[test]
+        fn test_group_results_by_dir() {
+            let conn = create_test_db();
+            populate_test_db(&conn);
+            let results = search_db(&conn, "tsx", true, false).unwrap();
+            let total = results.len();
+
+            let groups = group_results_by_dir(results);
+
+            let dirs: Vec<Option<&str>> = groups.iter().map(|g| g.dir.as_deref()).collect();
+            assert_eq!(dirs, vec![Some("/project/src"), Some("/project/src/components")]);
+            assert_eq!(groups[1].match_count, 2);
+            assert!(groups[1]
+                .results
+                .iter()
+                .all(|r| r.parent_path.as_deref() == Some("/project/src/components")));
+            assert_eq!(groups.iter().map(|g| g.match_count).sum::<usize>(), total);
+        }
+
+        #[test]
+        fn test_group_results_by_dir_orders_by_best_score() {
+            let conn = create_test_db();
+            populate_test_db(&conn);
+            let groups = group_results_by_dir(search_db(&conn, "plan", true, false).unwrap());
+
+            for pair in groups.windows(2) {
+                assert!(pair[0].best_score >= pair[1].best_score);
+            }
+            for group in &groups {
+                let best = group.results.iter().map(|r| r.score).max().unwrap();
+                assert_eq!(group.best_score, best);
+                assert_eq!(group.match_count, group.results.len());
+            }
+        }
+
+        #[test]
+        fn test_search_response_flat_serializes_as_list() {
+            let json = serde_json::to_value(SearchResponse::Flat(Vec::new())).unwrap();
+            assert!(json.is_array());
+        }
+
+        #[test]
+        fn test_search_match_ranges_for_name_match() {
+            let conn = create_test_db();
+            populate_test_db(&conn);
+            let results = search_db(&conn, "app", true, false).unwrap();
+            let app = results.iter().find(|r| r.name == "App.tsx").unwrap();
+
+            assert_eq!(app.match_ranges, vec![(13, 16)]);
+            let (start, end) = app.match_ranges[0];
+            assert_eq!(&app.path[start..end], "App");
+        }
+
+        #[test]
+        fn test_search_match_ranges_for_path_match() {
+            let conn = create_test_db();
+            populate_test_db(&conn);
+            let results = search_db(&conn, "conductor", true, false).unwrap();
+            let track = results.iter().find(|r| r.name == "track1.md").unwrap();
+
+            assert_eq!(track.match_ranges.len(), 1);
+            let (start, end) = track.match_ranges[0];
+            assert_eq!(&track.path[start..end], "conductor");
+            assert_eq!(start, "/project/".len());
+        }
+
+        #[test]
+        fn test_search_match_ranges_for_regex() {
+            let conn = create_test_db();
+            populate_test_db(&conn);
+            let results = search_db(&conn, "\\.tsx$", true, false).unwrap();
+
+            assert!(!results.is_empty());
+            for r in &results {
+                assert_eq!(r.match_ranges, vec![(r.path.len() - 4, r.path.len())]);
+            }
+        }
+
+        #[test]
+        fn test_find_case_insensitive_multibyte() {
+            assert_eq!(
+                find_case_insensitive("/x/Żółw-ŻÓŁW", "żółw"),
+                vec![(3, 10), (11, 18)]
+            );
+            assert_eq!(find_case_insensitive("aaaa", "aa"), vec![(0, 2), (2, 4)]);
+            assert!(find_case_insensitive("abc", "").is_empty());
+            assert!(find_case_insensitive("ab", "abc").is_empty());
+        }
+
+        #[test]
+        fn test_get_index_stats() {
+            let conn = create_test_db();
+            populate_test_db(&conn);
+            conn.execute(
+                "UPDATE files SET token_count = 25 WHERE name LIKE '%.tsx'",
+                [],
+            )
+            .unwrap();
+            // Orphan whose parent was never indexed counts as a second root
+            conn.execute(
+                "INSERT INTO files (path, parent_path, name, size, is_dir) VALUES ('/other/a.txt', '/other', 'a.txt', 50, 0)",
+                [],
+            )
+            .unwrap();
+
+            let stats = get_index_stats_internal(&conn).unwrap();
+            assert_eq!(
+                stats,
+                IndexStats {
+                    total_entries: 18,
+                    file_count: 10,
+                    dir_count: 8,
+                    total_bytes: 950,
+                    total_tokens: 75,
+                    root_count: 2,
+                }
+            );
+        }
+
+        fn remaining_paths(conn: &Connection) -> Vec<String> {
+            let mut stmt = conn
+                .prepare("SELECT path FROM files ORDER BY path")
+                .unwrap();
+            stmt.query_map([], |row| row.get(0))
+                .unwrap()
+                .collect::<Result<Vec<String>, _>>()
+                .unwrap()
+        }
+
+        #[test]
+        fn test_remove_subfolder_keeps_siblings() {
+            let mut conn = create_test_db();
+            populate_test_db(&conn);
+
+            let removed =
+                remove_from_index_internal(&mut conn, &SearchCache::default(), "/project/src")
+                    .unwrap();
+            // src, App.tsx, components + 2 children, lib + 1 child
+            assert_eq!(removed, 7);
+
+            let paths = remaining_paths(&conn);
+            assert!(!paths.iter().any(|p| p.starts_with("/project/src")));
+            assert!(paths.contains(&"/project".to_string()));
+            assert!(paths.contains(&"/project/docs/plan.md".to_string()));
+            assert!(paths.contains(&"/project/conductor/tracks/track1.md".to_string()));
+        }
+
+        #[test]
+        fn test_remove_single_file() {
+            let mut conn = create_test_db();
+            populate_test_db(&conn);
+
+            let removed = remove_from_index_internal(
+                &mut conn,
+                &SearchCache::default(),
+                "/project/conductor/plan.md",
+            )
+            .unwrap();
+            assert_eq!(removed, 1);
+
+            let paths = remaining_paths(&conn);
+            assert!(!paths.contains(&"/project/conductor/plan.md".to_string()));
+            assert!(paths.contains(&"/project/docs/plan.md".to_string()));
+            assert!(paths.contains(&"/project/conductor/tracks".to_string()));
+        }
+
+        #[test]
+        fn test_remove_root_and_unknown_path() {
+            let mut conn = create_test_db();
+            populate_test_db(&conn);
+
+            assert_eq!(
+                remove_from_index_internal(&mut conn, &SearchCache::default(), "/missing").unwrap(),
+                0
+            );
+            assert_eq!(remaining_paths(&conn).len(), 17);
+
+            // Backslash separators are normalized like indexed paths
+            let removed =
+                remove_from_index_internal(&mut conn, &SearchCache::default(), "\\project")
+                    .unwrap();
+            assert_eq!(removed, 17);
+            assert!(remaining_paths(&conn).is_empty());
+        }
+
+        fn cached_search(db: &DbConnection, cache: &SearchCache, pattern: &str) -> Vec<String> {
+            search_path_internal(db, cache, pattern, true, false, false)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.path)
+                .collect()
         }
 
         #[test]
-        fn test_parse_combined_file_dir() {
-            let f = parse_search_query("file:App dir:src");
-            assert_eq!(f.file_name.as_deref(), Some("App"));
-            assert_eq!(f.directory_name.as_deref(), Some("src"));
+        fn test_search_cache_hit_on_identical_query() {
+            let conn = create_test_db();
+            populate_test_db(&conn);
+            let db: DbConnection = Arc::new(Mutex::new(conn));
+            let cache = SearchCache::default();
+
+            assert_eq!(
+                cached_search(&db, &cache, "utils"),
+                vec!["/project/src/lib/utils.ts"]
+            );
+
+            db.lock()
+                .unwrap()
+                .execute(
+                    "INSERT INTO files (path, parent_path, name, is_dir)
+                     VALUES ('/project/src/lib/utils.test.ts', '/project/src/lib', 'utils.test.ts', 0)",
+                    [],
+                )
+                .unwrap();
+
+            // Served from the cache, surrounding whitespace included, so the new row is unseen
+            assert_eq!(
+                cached_search(&db, &cache, "  utils "),
+                vec!["/project/src/lib/utils.ts"]
+            );
+            // Different options are a different query
+            let unranked = search_path_internal(&db, &cache, "utils", false, false, false).unwrap();
+            assert_eq!(unranked.len(), 2);
         }
 
         #[test]
-        fn test_parse_plain_text() {
-            let f = parse_search_query("test");
-            assert_eq!(f.plain_text.as_deref(), Some("test"));
-            assert!(f.regex_pattern.is_none());
+        fn test_search_cache_cleared_after_index_mutation() {
+            let conn = create_test_db();
+            populate_test_db(&conn);
+            let db: DbConnection = Arc::new(Mutex::new(conn));
+            let cache = SearchCache::default();
+
+            assert_eq!(cached_search(&db, &cache, "plan").len(), 2);
+            remove_from_index_internal(&mut db.lock().unwrap(), &cache, "/project/docs").unwrap();
+            assert_eq!(
+                cached_search(&db, &cache, "plan"),
+                vec!["/project/conductor/plan.md"]
+            );
+
+            // Indexing a folder adds its files to later searches
+            let temp_dir = TempDir::new().unwrap();
+            fs::write(temp_dir.path().join("plan.txt"), "steps").unwrap();
+            index_folder_internal(temp_dir.path(), &db, &cache, |_| {}).unwrap();
+            assert_eq!(cached_search(&db, &cache, "plan").len(), 2);
+
+            clear_index_internal(&db.lock().unwrap(), &cache).unwrap();
+            assert!(cached_search(&db, &cache, "plan").is_empty());
         }
 
         #[test]
-        fn test_parse_regex_pattern() {
-            let f = parse_search_query("\\.test\\.ts$");
-            assert!(f.regex_pattern.is_some());
-            assert!(f.plain_text.is_none());
+        fn test_search_cache_evicts_least_recently_used() {
+            let cache = SearchCache::default();
+            let key = |pattern: &str| SearchKey {
+                pattern: pattern.to_string(),
+                prefer_source: true,
+                fuzzy: false,
+                exclude_ignored: false,
+            };
+
+            for i in 0..SEARCH_CACHE_CAPACITY {
+                cache.insert(key(&i.to_string()), Vec::new());
+            }
+            // Using the oldest entry makes "1" the least recently used instead
+            assert!(cache.get(&key("0")).is_some());
+            cache.insert(key("new"), Vec::new());
+
+            assert!(cache.get(&key("0")).is_some());
+            assert!(cache.get(&key("1")).is_none());
+            assert!(cache.get(&key("new")).is_some());
         }
 
         #[test]
-        fn test_parse_invalid_regex_falls_back_to_plain() {
-            let f = parse_search_query("[invalid");
-            assert!(f.regex_pattern.is_none());
-            assert_eq!(f.plain_text.as_deref(), Some("[invalid"));
+        fn test_get_roots_lists_each_root_once() {
+            let conn = create_test_db();
+            populate_test_db(&conn);
+            conn.execute(
+                "INSERT INTO files (path, parent_path, name, size, is_dir) VALUES ('/other/a.txt', '/other', 'a.txt', 50, 0)",
+                [],
+            )
+            .unwrap();
+            // /project/src was indexed on its own as well, so it is both a root and a child
+            conn.execute(
+                "UPDATE files SET is_root = 1 WHERE path IN ('/project', '/project/src')",
+                [],
+            )
+            .unwrap();
+
+            let roots = get_roots_internal(&conn).unwrap();
+            let paths: Vec<&str> = roots.iter().map(|r| r.path.as_str()).collect();
+            // Directories first, like the tree
+            assert_eq!(paths, vec!["/project", "/project/src", "/other/a.txt"]);
+
+            assert_eq!(
+                roots[2],
+                IndexRoot {
+                    path: "/other/a.txt".to_string(),
+                    name: "a.txt".to_string(),
+                    is_dir: false,
+                    child_count: 0,
+                    file_count: 1,
+                    total_bytes: 50,
+                }
+            );
+            let stats = get_index_stats_internal(&conn).unwrap();
+            assert_eq!(roots[0].file_count + 1, stats.file_count);
+            assert_eq!(roots[0].total_bytes + 50, stats.total_bytes);
+            assert!(roots[1].file_count < roots[0].file_count);
         }
 
         #[test]
-        fn test_parse_mixed_file_and_plain() {
-            let f = parse_search_query("file:App test");
-            assert_eq!(f.file_name.as_deref(), Some("App"));
-            assert_eq!(f.plain_text.as_deref(), Some("test"));
+        fn test_get_index_stats_empty() {
+            let conn = create_test_db();
+            let stats = get_index_stats_internal(&conn).unwrap();
+            assert_eq!(stats.total_entries, 0);
+            assert_eq!(stats.total_bytes, 0);
+            assert_eq!(stats.root_count, 0);
         }
 
         #[test]
-        fn test_parse_case_insensitive_prefix() {
-            let f = parse_search_query("FILE:App DIR:src");
-            assert_eq!(f.file_name.as_deref(), Some("App"));
-            assert_eq!(f.directory_name.as_deref(), Some("src"));
-        }
-    }
+        fn test_search_prefers_source_over_generated_files() {
+            let conn = create_test_db();
+            let files = [
+                ("/web/utils.min.js", "utils.min.js", Some("javascript")),
+                ("/web/utils.ts", "utils.ts", Some("typescript")),
+                ("/web/utils.js.map", "utils.js.map", None),
+                ("/web/dist/utils.js", "utils.js", Some("javascript")),
+            ];
+            for (path, name, language) in files {
+                conn.execute(
+                    "INSERT INTO files (path, parent_path, name, is_dir, mtime, language)
+                     VALUES (?, ?, ?, 0, 1000, ?)",
+                    params![path, path.rsplit_once('/').unwrap().0, name, language],
+                )
+                .unwrap();
+            }
 
-    mod search_integration_tests {
-        use super::*;
+            let results = search_db(&conn, "utils", true, false).unwrap();
+            assert_eq!(results[0].name, "utils.ts");
+            assert!(results.iter().skip(1).all(|r| r.score < results[0].score));
 
-        fn populate_test_db(conn: &Connection) {
-            // Create a realistic file structure:
-            // /project/src/App.tsx
-            // /project/src/components/Header.tsx
-            // /project/src/components/Footer.tsx
-            // /project/src/lib/utils.ts
-            // /project/conductor/tracks/track1.md
-            // /project/conductor/tracks/track2.md
-            // /project/conductor/plan.md
-            // /project/docs/plan.md  (duplicate name)
-            // /project/tests/app.test.ts
-            // /project/node_modules/react/index.js  (dir)
-            let entries = vec![
-                ("/project", None, "project", true),
-                ("/project/src", Some("/project"), "src", true),
-                ("/project/src/App.tsx", Some("/project/src"), "App.tsx", false),
-                ("/project/src/components", Some("/project/src"), "components", true),
-                ("/project/src/components/Header.tsx", Some("/project/src/components"), "Header.tsx", false),
-                ("/project/src/components/Footer.tsx", Some("/project/src/components"), "Footer.tsx", false),
-                ("/project/src/lib", Some("/project/src"), "lib", true),
-                ("/project/src/lib/utils.ts", Some("/project/src/lib"), "utils.ts", false),
-                ("/project/conductor", Some("/project"), "conductor", true),
-                ("/project/conductor/tracks", Some("/project/conductor"), "tracks", true),
-                ("/project/conductor/tracks/track1.md", Some("/project/conductor/tracks"), "track1.md", false),
-                ("/project/conductor/tracks/track2.md", Some("/project/conductor/tracks"), "track2.md", false),
-                ("/project/conductor/plan.md", Some("/project/conductor"), "plan.md", false),
-                ("/project/docs", Some("/project"), "docs", true),
-                ("/project/docs/plan.md", Some("/project/docs"), "plan.md", false),
-                ("/project/tests", Some("/project"), "tests", true),
-                ("/project/tests/app.test.ts", Some("/project/tests"), "app.test.ts", false),
+            // Without the adjustment equal matches fall back to name order
+            let results = search_db(&conn, "utils", false, false).unwrap();
+            assert_eq!(results[0].name, "utils.js.map");
+        }
+
+        #[test]
+        fn test_search_fuzzy_matches_subsequence() {
+            let conn = create_test_db();
+            let files = [
+                "/web/apple_jam_sauce.txt",
+                "/web/app.js",
+                "/web/readme.md",
+                "/web/MyComponentHeader.tsx",
             ];
+            for path in files {
+                conn.execute(
+                    "INSERT INTO files (path, parent_path, name, is_dir) VALUES (?, '/web', ?, 0)",
+                    params![path, path.rsplit_once('/').unwrap().1],
+                )
+                .unwrap();
+            }
 
-            for (path, parent, name, is_dir) in entries {
+            assert!(search_db(&conn, "appjs", true, false).unwrap().is_empty());
+
+            let results = search_db(&conn, "appjs", true, true).unwrap();
+            let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+            assert_eq!(names, vec!["app.js", "apple_jam_sauce.txt"]);
+            assert!(results[0].score > results[1].score);
+            assert_eq!(
+                results[0].match_ranges,
+                vec![(5, 6), (6, 7), (7, 8), (9, 10), (10, 11)]
+            );
+
+            let results = search_db(&conn, "MyCompHdr", true, true).unwrap();
+            assert_eq!(results[0].name, "MyComponentHeader.tsx");
+
+            // Substring matches still outrank fuzzy ones
+            let results = search_db(&conn, "app", true, true).unwrap();
+            assert!(results.iter().all(|r| r.score > FUZZY_MAX_SCORE));
+        }
+
+        #[test]
+        fn test_fuzzy_match_ranks_below_path_only_match() {
+            let conn = create_test_db();
+            for (path, parent, name) in [
+                ("/web/app.js", "/web", "app.js"),
+                ("/legacy/appjs/index.html", "/legacy/appjs", "index.html"),
+            ] {
                 conn.execute(
-                    "INSERT INTO files (path, parent_path, name, size, mtime, is_dir, fingerprint)
-                     VALUES (?, ?, ?, ?, ?, ?, ?)",
-                    params![path, parent, name, if is_dir { None::<i64> } else { Some(100) }, Some(1000i64), is_dir as i32, None::<String>],
-                ).unwrap();
+                    "INSERT INTO files (path, parent_path, name, is_dir) VALUES (?, ?, ?, 0)",
+                    params![path, parent, name],
+                )
+                .unwrap();
+            }
+
+            for prefer_source in [true, false] {
+                let results = search_db(&conn, "appjs", prefer_source, true).unwrap();
+                let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+                // Only the path of index.html contains the query; app.js matches fuzzily
+                assert_eq!(names, vec!["index.html", "app.js"]);
+                assert!(results[0].score > results[1].score);
             }
         }
 
+        #[test]
+        fn test_fuzzy_match_prefers_runs_and_word_starts() {
+            let matched = fuzzy_match("MyComponentHeader.tsx", "mchdr").unwrap();
+            // `h` is taken from the `Header` hump rather than anywhere earlier
+            assert_eq!(matched.ranges[2], (11, 12));
+            assert!(fuzzy_match("app.js", "ajp").is_none());
+            assert!(fuzzy_match("a", "ab").is_none());
+            assert!(fuzzy_match("app.js", "").is_none());
+
+            let run = fuzzy_match("app.js", "app").unwrap();
+            let scattered = fuzzy_match("a_p_p.js", "app").unwrap();
+            assert!(run.score > scattered.score);
+        }
+
         #[test]
         fn test_search_plain_text_finds_by_name() {
             let conn = create_test_db();
             populate_test_db(&conn);
-            let results = search_db(&conn, "track1").unwrap();
+            let results = search_db(&conn, "track1", true, false).unwrap();
             assert!(!results.is_empty());
             assert_eq!(results[0].name, "track1.md");
         }
@@ -1172,7 +4648,7 @@ mod tests {
         fn test_search_plain_text_ordered_by_score() {
             let conn = create_test_db();
             populate_test_db(&conn);
-            let results = search_db(&conn, "plan").unwrap();
+            let results = search_db(&conn, "plan", true, false).unwrap();
             // Should find both plan.md files, sorted by score DESC
             assert!(results.len() >= 2);
             assert!(results[0].score >= results[1].score);
@@ -1182,7 +4658,7 @@ mod tests {
         fn test_search_exact_name_match_scores_higher() {
             let conn = create_test_db();
             populate_test_db(&conn);
-            let results = search_db(&conn, "App.tsx").unwrap();
+            let results = search_db(&conn, "App.tsx", true, false).unwrap();
             // Exact name match should score highest
             assert!(!results.is_empty());
             assert_eq!(results[0].name, "App.tsx");
@@ -1196,7 +4672,7 @@ mod tests {
         fn test_search_file_prefix_only_matches_files() {
             let conn = create_test_db();
             populate_test_db(&conn);
-            let results = search_db(&conn, "file:App").unwrap();
+            let results = search_db(&conn, "file:App", true, false).unwrap();
             // Should find App.tsx (file), not any directory
             assert!(!results.is_empty());
             for r in &results {
@@ -1209,7 +4685,7 @@ mod tests {
         fn test_search_dir_prefix_only_matches_dirs() {
             let conn = create_test_db();
             populate_test_db(&conn);
-            let results = search_db(&conn, "dir:src").unwrap();
+            let results = search_db(&conn, "dir:src", true, false).unwrap();
             // Should find the "src" directory itself
             assert!(!results.is_empty());
             for r in &results {
@@ -1222,7 +4698,7 @@ mod tests {
         fn test_search_dir_prefix_partial_match() {
             let conn = create_test_db();
             populate_test_db(&conn);
-            let results = search_db(&conn, "dir:track").unwrap();
+            let results = search_db(&conn, "dir:track", true, false).unwrap();
             assert!(!results.is_empty());
             assert!(results.iter().any(|r| r.name == "tracks"));
         }
@@ -1232,7 +4708,7 @@ mod tests {
             let conn = create_test_db();
             populate_test_db(&conn);
             // file:plan dir:conductor means: find files named "plan" inside "conductor" directory
-            let results = search_db(&conn, "file:plan dir:conductor").unwrap();
+            let results = search_db(&conn, "file:plan dir:conductor", true, false).unwrap();
             assert!(!results.is_empty());
             // Should find conductor/plan.md but NOT docs/plan.md
             assert!(results.iter().any(|r| r.path == "/project/conductor/plan.md"));
@@ -1243,7 +4719,7 @@ mod tests {
         fn test_search_empty_returns_empty() {
             let conn = create_test_db();
             populate_test_db(&conn);
-            let results = search_db(&conn, "").unwrap();
+            let results = search_db(&conn, "", true, false).unwrap();
             assert!(results.is_empty());
         }
 
@@ -1251,7 +4727,7 @@ mod tests {
         fn test_search_no_match_returns_empty() {
             let conn = create_test_db();
             populate_test_db(&conn);
-            let results = search_db(&conn, "xyznonexistent").unwrap();
+            let results = search_db(&conn, "xyznonexistent", true, false).unwrap();
             assert!(results.is_empty());
         }
 
@@ -1259,7 +4735,7 @@ mod tests {
         fn test_search_case_insensitive() {
             let conn = create_test_db();
             populate_test_db(&conn);
-            let results = search_db(&conn, "app").unwrap();
+            let results = search_db(&conn, "app", true, false).unwrap();
             assert!(!results.is_empty());
             assert!(results.iter().any(|r| r.name == "App.tsx"));
         }
@@ -1268,7 +4744,7 @@ mod tests {
         fn test_search_results_have_scores() {
             let conn = create_test_db();
             populate_test_db(&conn);
-            let results = search_db(&conn, "Header").unwrap();
+            let results = search_db(&conn, "Header", true, false).unwrap();
             assert!(!results.is_empty());
             // All results should have a positive score
             for r in &results {
@@ -1280,7 +4756,7 @@ mod tests {
         fn test_search_regex_pattern() {
             let conn = create_test_db();
             populate_test_db(&conn);
-            let results = search_db(&conn, "\\.tsx$").unwrap();
+            let results = search_db(&conn, "\\.tsx$", true, false).unwrap();
             // Should find all .tsx files
             assert!(!results.is_empty());
             for r in &results {
@@ -1439,5 +4915,432 @@ mod tests {
             assert!(entries.iter().any(|p| p.to_string_lossy().contains("main.rs")));
             assert!(entries.iter().any(|p| p.to_string_lossy().contains(".gitignore")));
         }
+
+        fn walked_paths(root: &Path, stats: Arc<WalkStats>) -> Vec<PathBuf> {
+            let manager = GitignoreManager::new(root);
+            filtered_walk(root, Some(manager), false, true, 0, stats)
+                .filter_map(|e| e.ok())
+                .map(|e| e.path().to_path_buf())
+                .collect()
+        }
+
+        #[test]
+        fn test_filtered_walk_applies_root_gitignore() {
+            let temp_dir = create_test_directory_with_gitignore();
+            let path = temp_dir.path();
+            let stats = Arc::new(WalkStats::default());
+
+            let entries = walked_paths(path, stats.clone());
+
+            assert!(entries.contains(&path.join("main.rs")));
+            assert!(entries.contains(&path.join("src/lib.rs")));
+            assert!(!entries
+                .iter()
+                .any(|p| p.starts_with(path.join("node_modules"))));
+            assert!(!entries.iter().any(|p| p.starts_with(path.join("build"))));
+            assert!(!entries.contains(&path.join("debug.log")));
+            // node_modules/, build/ and debug.log are pruned; their contents are never visited
+            assert_eq!(stats.ignored.load(Ordering::Relaxed), 3);
+            assert_eq!(stats.gitignore_files.load(Ordering::Relaxed), 1);
+        }
+
+        #[test]
+        fn test_index_summary_reports_gitignore_effect() {
+            let temp_dir = create_test_directory_with_gitignore();
+            let db: DbConnection = Arc::new(Mutex::new(create_test_db()));
+
+            let summary =
+                index_folder_internal(temp_dir.path(), &db, &SearchCache::default(), |_| {})
+                    .unwrap();
+
+            assert_eq!(summary.ignored, 3);
+            assert_eq!(summary.gitignore_files, 1);
+            // The root, src, main.rs, src/lib.rs and .gitignore itself
+            assert_eq!(summary.inserted, 5);
+        }
+
+        #[test]
+        fn test_index_applies_project_excluded_extensions() {
+            let temp_dir = create_test_directory_with_gitignore();
+            let path = temp_dir.path();
+            fs::write(path.join("Cargo.lock"), "# generated").unwrap();
+            fs::write(path.join("src/lib.snap"), "snapshot").unwrap();
+            fs::write(
+                path.join(crate::project_config::PROJECT_CONFIG_FILE),
+                "excluded_extensions = [\".lock\", \".snap\"]\n",
+            )
+            .unwrap();
+            let db: DbConnection = Arc::new(Mutex::new(create_test_db()));
+
+            let summary =
+                index_folder_internal(path, &db, &SearchCache::default(), |_| {}).unwrap();
+
+            let conn = db.lock().unwrap();
+            let indexed = |name: &str| {
+                conn.query_row(
+                    "SELECT COUNT(*) FROM files WHERE name = ?",
+                    params![name],
+                    |row| row.get::<_, i64>(0),
+                )
+                .unwrap()
+                    > 0
+            };
+            assert!(!indexed("Cargo.lock"));
+            assert!(!indexed("lib.snap"));
+            assert!(indexed("lib.rs"));
+            // Three gitignored entries plus the two excluded files
+            assert_eq!(summary.ignored, 5);
+        }
+
+        #[test]
+        fn test_index_skips_hidden_entries_when_disabled() {
+            let temp_dir = create_test_directory_with_gitignore();
+            let path = temp_dir.path();
+            fs::write(path.join(".env"), "SECRET=1").unwrap();
+            fs::create_dir_all(path.join(".github")).unwrap();
+            fs::write(path.join(".github/ci.yml"), "on: push").unwrap();
+            let db: DbConnection = Arc::new(Mutex::new(create_test_db()));
+            db.lock()
+                .unwrap()
+                .execute(
+                    "INSERT INTO settings (key, value) VALUES ('include_hidden', 'false')",
+                    [],
+                )
+                .unwrap();
+
+            let summary =
+                index_folder_internal(path, &db, &SearchCache::default(), |_| {}).unwrap();
+
+            let conn = db.lock().unwrap();
+            let indexed = |name: &str| {
+                conn.query_row(
+                    "SELECT COUNT(*) FROM files WHERE name = ?",
+                    params![name],
+                    |row| row.get::<_, i64>(0),
+                )
+                .unwrap()
+                    > 0
+            };
+            assert!(!indexed(".env"));
+            assert!(!indexed(".github"));
+            assert!(!indexed("ci.yml"));
+            assert!(!indexed(".gitignore"));
+            // The skipped .gitignore still keeps its rules in force
+            assert!(!indexed("debug.log"));
+            assert!(indexed("main.rs"));
+            assert_eq!(summary.gitignore_files, 1);
+            // Three gitignored entries plus .env, .github and .gitignore
+            assert_eq!(summary.ignored, 6);
+        }
+
+        #[test]
+        fn test_retain_unignored_drops_paths_ignored_after_indexing() {
+            let temp_dir = create_test_directory_with_gitignore();
+            let path = temp_dir.path();
+            let db: DbConnection = Arc::new(Mutex::new(create_test_db()));
+            index_folder_internal(path, &db, &SearchCache::default(), |_| {}).unwrap();
+
+            // A rule added once the file is already indexed
+            fs::write(path.join("src/.gitignore"), "lib.rs\n").unwrap();
+
+            let (mut results, roots) = {
+                let conn = db.lock().unwrap();
+                (
+                    search_db(&conn, "lib", true, false).unwrap(),
+                    indexed_roots(&conn).unwrap(),
+                )
+            };
+            assert!(results.iter().any(|r| r.name == "lib.rs"));
+
+            retain_unignored(&mut results, &roots, |root| {
+                (Some(GitignoreManager::new(root)), ProjectConfig::default())
+            });
+            assert!(!results.iter().any(|r| r.name == "lib.rs"));
+
+            // With gitignore support off, only the project's excluded extensions apply
+            let conn = db.lock().unwrap();
+            let mut results = search_db(&conn, "rs", true, false).unwrap();
+            let project = ProjectConfig {
+                excluded_extensions: Some(vec!["rs".to_string()]),
+                ..ProjectConfig::default()
+            };
+            retain_unignored(&mut results, &roots, |_| (None, project.clone()));
+            assert!(!results.is_empty());
+            assert!(results.iter().all(|r| !r.name.ends_with(".rs")));
+        }
+
+        #[test]
+        fn test_index_single_file_as_root() {
+            let temp_dir = create_test_directory_with_gitignore();
+            let file = temp_dir.path().join("debug.log");
+            let db: DbConnection = Arc::new(Mutex::new(create_test_db()));
+
+            // Picked explicitly, so the *.log rule next to it doesn't apply
+            let summary =
+                index_folder_internal(&file, &db, &SearchCache::default(), |_| {}).unwrap();
+            assert_eq!(summary.inserted, 1);
+            assert_eq!(summary.ignored, 0);
+
+            let conn = db.lock().unwrap();
+            let file_path = normalize_path(&file.to_string_lossy());
+            let (name, size, is_root): (String, Option<i64>, bool) = conn
+                .query_row(
+                    "SELECT name, size, is_root FROM files WHERE path = ?",
+                    params![file_path],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .unwrap();
+            assert_eq!(name, "debug.log");
+            assert_eq!(size, Some("log content".len() as i64));
+            assert!(is_root);
+            assert_eq!(indexed_roots(&conn).unwrap(), vec![file_path.clone()]);
+
+            let mut results = search_db(&conn, "debug", true, false).unwrap();
+            retain_unignored(&mut results, &[file_path], |root| {
+                (Some(GitignoreManager::new(root)), ProjectConfig::default())
+            });
+            assert_eq!(results.len(), 1);
+        }
+
+        #[test]
+        fn test_preview_ignored_splits_indexed_and_ignored() {
+            let temp_dir = create_test_directory_with_gitignore();
+            let path = temp_dir.path();
+            let root = normalize_path(&path.to_string_lossy());
+
+            let preview =
+                preview_ignored_internal(path, Some(GitignoreManager::new(path)), 100).unwrap();
+
+            assert!(preview.ignored.contains(&format!("{}/node_modules", root)));
+            assert!(preview.ignored.contains(&format!("{}/build", root)));
+            assert!(preview.ignored.contains(&format!("{}/debug.log", root)));
+            assert_eq!(preview.ignored_count, 3);
+
+            assert!(preview.indexed.contains(&format!("{}/src", root)));
+            assert!(preview.indexed.contains(&format!("{}/src/lib.rs", root)));
+            assert!(preview.indexed.contains(&format!("{}/main.rs", root)));
+            assert!(!preview.indexed.iter().any(|p| p.contains("node_modules")));
+        }
+
+        #[test]
+        fn test_preview_ignored_without_gitignore_and_capped() {
+            let temp_dir = create_test_directory_with_gitignore();
+            let path = temp_dir.path();
+
+            let preview = preview_ignored_internal(path, None, 100).unwrap();
+            assert!(preview.ignored.is_empty());
+            // src, src/lib.rs, node_modules, node_modules/package, its index.js, build,
+            // build/output.js, main.rs, debug.log, .gitignore
+            assert_eq!(preview.indexed_count, 10);
+
+            let capped = preview_ignored_internal(path, None, 4).unwrap();
+            assert_eq!(capped.indexed.len(), 4);
+            assert_eq!(capped.indexed_count, 10);
+        }
+
+        #[test]
+        fn test_explain_ignore_attributes_rule() {
+            let temp_dir = create_test_directory_with_gitignore();
+            let path = temp_dir.path();
+            let root = normalize_path(&path.to_string_lossy());
+
+            let explained = explain_ignore_internal(
+                path,
+                &path.join("debug.log"),
+                Some(GitignoreManager::new(path)),
+            )
+            .unwrap();
+            assert_eq!(
+                explained,
+                IgnoreExplanation {
+                    gitignore: Some(format!("{}/.gitignore", root)),
+                    pattern: "*.log".to_string(),
+                    matched_path: format!("{}/debug.log", root),
+                }
+            );
+
+            let explained = explain_ignore_internal(
+                path,
+                &path.join("node_modules/package/index.js"),
+                Some(GitignoreManager::new(path)),
+            )
+            .unwrap();
+            assert_eq!(explained.pattern, "node_modules/");
+            assert_eq!(explained.matched_path, format!("{}/node_modules", root));
+
+            assert_eq!(
+                explain_ignore_internal(
+                    path,
+                    &path.join("main.rs"),
+                    Some(GitignoreManager::new(path))
+                ),
+                None
+            );
+            // Gitignore support turned off
+            assert_eq!(
+                explain_ignore_internal(path, &path.join("debug.log"), None),
+                None
+            );
+        }
+
+        #[test]
+        fn test_filtered_walk_discovers_nested_gitignore_mid_walk() {
+            let temp_dir = TempDir::new().unwrap();
+            let path = temp_dir.path();
+
+            fs::create_dir_all(path.join("a/b/c")).unwrap();
+            fs::write(path.join("a/b/keep.rs"), "fn keep() {}").unwrap();
+            fs::write(path.join("a/b/secret.txt"), "hidden").unwrap();
+            fs::write(path.join("a/b/c/secret.txt"), "hidden").unwrap();
+            fs::write(path.join("a/secret.txt"), "visible").unwrap();
+            // Only a nested .gitignore; nothing is loaded before the walk starts
+            fs::write(path.join("a/b/.gitignore"), "secret.txt\n").unwrap();
+
+            let entries = walked_paths(path, Arc::default());
+
+            assert!(entries.contains(&path.join("a/b/keep.rs")));
+            assert!(entries.contains(&path.join("a/secret.txt")));
+            assert!(!entries.contains(&path.join("a/b/secret.txt")));
+            assert!(!entries.contains(&path.join("a/b/c/secret.txt")));
+        }
+    }
+
+    #[test]
+    fn test_lossy_path_string_valid_utf8() {
+        let (path, lossy) = lossy_path_string(Path::new("/project/src/main.rs"));
+        assert_eq!(path, "/project/src/main.rs");
+        assert!(!lossy);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_paths_are_indexed_lossily() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let bad_dir = temp_dir.path().join(OsStr::from_bytes(b"caf\xe9"));
+        if fs::create_dir(&bad_dir).is_err() {
+            // Some filesystems refuse non-UTF-8 names
+            return;
+        }
+        fs::write(bad_dir.join("notes.txt"), "hello").unwrap();
+
+        let (bad_dir_str, lossy) = lossy_path_string(&bad_dir);
+        assert!(lossy);
+        assert!(bad_dir_str.ends_with("caf\u{FFFD}"));
+
+        let dir_entry = FileEntry::from_path(&bad_dir, None).unwrap();
+        assert!(dir_entry.lossy_path);
+        assert_eq!(dir_entry.name, "caf\u{FFFD}");
+
+        let child = walkdir::WalkDir::new(&bad_dir)
+            .min_depth(1)
+            .into_iter()
+            .next()
+            .unwrap()
+            .unwrap();
+        let file_entry = FileEntry::from_dir_entry(&child).unwrap();
+        assert!(file_entry.lossy_path);
+        assert_eq!(file_entry.name, "notes.txt");
+        assert_eq!(
+            file_entry.parent_path.as_deref(),
+            Some(bad_dir_str.as_str())
+        );
+    }
+
+    #[cfg(unix)]
+    mod symlink_tests {
+        use super::*;
+        use std::os::unix::fs::symlink;
+        use std::sync::mpsc;
+        use std::thread;
+
+        fn walk(root: &Path, follow_symlinks: bool) -> Vec<walkdir::DirEntry> {
+            filtered_walk(root, None, follow_symlinks, true, 0, Arc::default())
+                .filter_map(|e| e.ok())
+                .collect()
+        }
+
+        fn count_named(entries: &[walkdir::DirEntry], name: &str) -> usize {
+            entries.iter().filter(|e| e.file_name() == name).count()
+        }
+
+        #[test]
+        fn test_symlinks_skipped_by_default() {
+            let temp_dir = TempDir::new().unwrap();
+            let path = temp_dir.path();
+            fs::create_dir_all(path.join("real")).unwrap();
+            fs::write(path.join("real/file.txt"), "content").unwrap();
+            symlink(path.join("real"), path.join("link")).unwrap();
+
+            let entries = walk(path, false);
+
+            assert_eq!(count_named(&entries, "file.txt"), 1);
+            assert!(!entries.iter().any(|e| e.path() == path.join("link")));
+        }
+
+        #[test]
+        fn test_symlink_to_sibling_indexed_once() {
+            let temp_dir = TempDir::new().unwrap();
+            let path = temp_dir.path();
+            fs::create_dir_all(path.join("real")).unwrap();
+            fs::write(path.join("real/file.txt"), "content").unwrap();
+            // Sorts before "real" by name, but real entries are still visited first
+            symlink(path.join("real"), path.join("a_link")).unwrap();
+
+            let entries = walk(path, true);
+
+            assert_eq!(count_named(&entries, "file.txt"), 1);
+            assert!(entries
+                .iter()
+                .any(|e| e.path() == path.join("real/file.txt")));
+        }
+
+        #[test]
+        fn test_symlink_outside_root_records_target() {
+            let temp_dir = TempDir::new().unwrap();
+            let outside = TempDir::new().unwrap();
+            let path = temp_dir.path();
+            fs::write(outside.path().join("shared.txt"), "shared").unwrap();
+            symlink(outside.path(), path.join("shared")).unwrap();
+
+            let entries = walk(path, true);
+
+            assert!(entries
+                .iter()
+                .any(|e| e.path() == path.join("shared/shared.txt")));
+            let link = entries
+                .iter()
+                .find(|e| e.path() == path.join("shared"))
+                .unwrap();
+            let file_entry = FileEntry::from_dir_entry(link).unwrap();
+            assert!(file_entry.is_dir);
+            assert_eq!(
+                file_entry.symlink_target,
+                resolve_symlink_target(outside.path())
+            );
+        }
+
+        #[test]
+        fn test_self_referential_symlink_terminates() {
+            let temp_dir = TempDir::new().unwrap();
+            let root = temp_dir.path().to_path_buf();
+            fs::create_dir_all(root.join("a")).unwrap();
+            fs::write(root.join("a/file.txt"), "content").unwrap();
+            symlink(root.join("a"), root.join("a/loop")).unwrap();
+            symlink(&root, root.join("a/up")).unwrap();
+
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                let entries = walk(&root, true);
+                tx.send(count_named(&entries, "file.txt")).unwrap();
+            });
+
+            let file_count = rx
+                .recv_timeout(Duration::from_secs(10))
+                .expect("walk did not terminate");
+            assert_eq!(file_count, 1);
+        }
     }
 }
@@ -0,0 +1,14 @@
+use crate::error::CommandResult;
+use crate::log_buffer::LogBuffer;
+use std::sync::Arc;
+
+/// Return the last `lines` application log lines, oldest first
+///
+/// Only lines logged since startup are available, up to the buffer's capacity.
+#[tauri::command]
+pub async fn get_recent_logs(
+    lines: usize,
+    buffer: tauri::State<'_, Arc<LogBuffer>>,
+) -> CommandResult<Vec<String>> {
+    Ok(buffer.recent(lines))
+}
@@ -3,12 +3,20 @@ pub mod indexing;
 pub mod browser;
 pub mod prompts;
 pub mod history;
+pub mod bookmarks;
+pub mod database;
+pub mod diagnostics;
+pub mod git;
+pub mod logs;
 pub mod settings;
 
 // Re-export all commands for easy access
 pub use extraction::{extract_text, get_supported_file_types};
 pub use indexing::{get_children, index_folder, search_path};
-pub use browser::{launch_browser, get_available_interfaces};
+pub use browser::{
+    add_custom_interface, check_browser_prerequisites, get_available_interfaces, get_browser_launch,
+    launch_browser, remove_custom_interface,
+};
 pub use prompts::{build_prompt_from_files, get_file_content, get_file_contents, get_templates};
 pub use history::{save_history, load_history, validate_history_paths, delete_history, clear_history};
 pub use settings::{
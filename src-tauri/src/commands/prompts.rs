@@ -1,178 +1,1735 @@
+use crate::commands::browser::{launch_browser_internal, BrowserLaunch, BrowserProcesses};
+use crate::commands::extraction::{extract_text_from_file, is_text_file, TextFileOverrides};
+use crate::commands::git::git_changes;
+use crate::commands::indexing::indexed_roots;
+use crate::commands::settings::{get_setting_with_conn, AppSettings};
 use crate::db::DbConnection;
-use crate::error::AppResult;
-use crate::templates::{build_prompt, get_builtin_templates, PromptTemplate};
-use rusqlite::params;
+use crate::error::{AppError, AppResult, CommandError, CommandResult};
+use crate::language::detect_language;
+use crate::paths::{is_within, normalize_path};
+use crate::project_config::ProjectConfig;
+use crate::templates::{
+    build_prompt_pieces, build_prompt_with_vars, estimate_tokens as estimate_text_tokens,
+    get_builtin_templates, placeholder_names, render_files_markdown, uses_placeholder,
+    PromptTemplate, SUPPORTED_PLACEHOLDERS,
+};
+use rayon::prelude::*;
+use rusqlite::{params, params_from_iter, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::Write;
+use std::path::Path;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileContent {
     pub path: String,
     pub content: String,
+    /// Size in bytes as recorded in the index
+    #[serde(default)]
+    pub size: Option<i64>,
+    #[serde(default)]
+    pub mtime: Option<i64>,
+    #[serde(default)]
+    pub token_count: Option<i64>,
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Encoding the content was decoded from, e.g. `utf-8` or `windows-1252`
+    #[serde(default)]
+    pub encoding: Option<String>,
+}
+
+/// Metadata of an indexed entry returned with its content
+struct IndexedFile {
+    is_dir: bool,
+    size: Option<i64>,
+    mtime: Option<i64>,
+    token_count: Option<i64>,
+    language: Option<String>,
+}
+
+impl IndexedFile {
+    /// Look up `path` in the index; `None` when it isn't indexed
+    fn query(conn: &rusqlite::Connection, path: &str) -> rusqlite::Result<Option<Self>> {
+        conn.query_row(
+            "SELECT is_dir, size, mtime, token_count, language FROM files WHERE path = ?",
+            params![path],
+            |row| {
+                Ok(Self {
+                    is_dir: row.get::<_, i32>(0)? != 0,
+                    size: row.get(1)?,
+                    mtime: row.get(2)?,
+                    token_count: row.get(3)?,
+                    language: row.get(4)?,
+                })
+            },
+        )
+        .optional()
+    }
+
+    /// Read the file, decoding it with the same encoding detection as `extract_text`
+    fn read(self, path: String) -> AppResult<FileContent> {
+        let extracted = extract_text_from_file(&path, None)?;
+        Ok(FileContent {
+            path,
+            content: extracted.text,
+            size: self.size,
+            mtime: self.mtime,
+            token_count: self.token_count,
+            language: self.language,
+            encoding: Some(extracted.encoding),
+        })
+    }
+}
+
+/// Why a requested file's content could not be returned
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FileFailureReason {
+    NotIndexed,
+    IsDirectory,
+    Io { message: String },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileFailure {
+    pub path: String,
+    pub reason: FileFailureReason,
+}
+
+/// File contents that could be read, plus the reason for every file that couldn't
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileContentsResult {
+    pub contents: Vec<FileContent>,
+    pub failures: Vec<FileFailure>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BuildPromptRequest {
-    pub template_id: String,
+    /// Template to use; the configured `default_template` when omitted
+    #[serde(default)]
+    pub template_id: Option<String>,
     pub custom_instructions: Option<String>,
     pub file_paths: Vec<String>,
+    /// Truncate each file's content to this many characters, appending a marker
+    #[serde(default)]
+    pub per_file_char_limit: Option<usize>,
+    /// Emit a placeholder block for binary files instead of their (unreadable) content
+    #[serde(default)]
+    pub binary_placeholders: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BuildPromptResponse {
     pub prompt: String,
     pub file_count: usize,
+    /// Characters read from the files, before any truncation; the size to show users
     pub total_chars: usize,
+    /// UTF-8 bytes read from the files, before any truncation
+    #[serde(default)]
+    pub total_bytes: usize,
+    /// Number of files cut down to `per_file_char_limit`
+    #[serde(default)]
+    pub truncated_files: usize,
+    /// Binary files written as placeholders, not counted in `file_count`
+    #[serde(default)]
+    pub omitted_files: Vec<String>,
 }
 
+/// Result of writing a built prompt to disk; the prompt itself is not sent back
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PromptFileResponse {
+    pub path: String,
+    pub bytes_written: u64,
+    pub file_count: usize,
+    pub total_chars: usize,
+    pub total_bytes: usize,
+    pub truncated_files: usize,
+    pub omitted_files: Vec<String>,
+}
+
+/// Projected size of a prompt, worked out by `estimate_prompt` without reading the files
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptEstimate {
+    pub file_count: usize,
+    /// On-disk size of the files in bytes, before any truncation
+    ///
+    /// Matches `BuildPromptResponse.total_bytes` for UTF-8 files, and exceeds its
+    /// `total_chars` wherever the text is not ASCII.
+    pub total_bytes: usize,
+    /// Estimated tokens of the whole prompt, template included
+    pub total_tokens: usize,
+    pub truncated_files: usize,
+    pub omitted_files: Vec<String>,
+}
+
+/// A prompt built by `build_and_launch` and the browser launch it was sent to
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildAndLaunchResponse {
+    pub prompt: BuildPromptResponse,
+    pub launch: BrowserLaunch,
+}
+
+/// One message-sized part of a prompt split by `build_prompt_chunks`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PromptChunk {
+    /// Position of this chunk, starting at 0
+    pub index: usize,
+    /// Number of chunks the prompt was split into
+    pub total: usize,
+    pub content: String,
+    pub token_estimate: usize,
+}
+
+/// The placeholders a template uses, and those a prompt build would leave as written
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct TemplateValidation {
+    pub placeholders: Vec<String>,
+    /// Placeholders outside the supported set, in order of first use
+    pub unknown_placeholders: Vec<String>,
+}
+
+/// Placeholder content for binary files left out of a prompt
+const BINARY_PLACEHOLDER: &str = "[binary file omitted]";
+
 /// Get all available prompt templates
 #[tauri::command]
-pub async fn get_templates() -> Result<Vec<PromptTemplate>, String> {
+pub async fn get_templates() -> CommandResult<Vec<PromptTemplate>> {
     Ok(get_builtin_templates())
 }
 
+/// Check a custom template's placeholders against the ones prompt building fills in
+#[tauri::command]
+pub async fn validate_template(template: String) -> CommandResult<TemplateValidation> {
+    Ok(validate_template_internal(&template))
+}
+
+/// Internal function to sort a template's placeholders into supported and unknown ones
+fn validate_template_internal(template: &str) -> TemplateValidation {
+    let placeholders = placeholder_names(template);
+    let unknown_placeholders = placeholders
+        .iter()
+        .filter(|name| !SUPPORTED_PLACEHOLDERS.contains(&name.as_str()))
+        .cloned()
+        .collect();
+
+    TemplateValidation {
+        placeholders,
+        unknown_placeholders,
+    }
+}
+
+/// Estimate the token count of arbitrary text, such as the custom prompt box
+///
+/// There is no tokenizer in the backend, so every model gets the same ~4 chars/token
+/// heuristic used for `{{total_tokens}}`; `model` is accepted so callers won't need to change
+/// once per-model encodings exist.
+#[tauri::command]
+pub async fn estimate_tokens(text: String, model: Option<String>) -> CommandResult<usize> {
+    let _ = model;
+    Ok(crate::templates::estimate_tokens(&text))
+}
+
 /// Read file content from the filesystem
 fn read_file_content(path: &str) -> AppResult<String> {
     let content = fs::read_to_string(path)?;
     Ok(content)
 }
 
+/// Cut `content` down to `limit` characters, appending a marker with how many were dropped.
+///
+/// Returns the content unchanged when it fits, along with whether it was truncated.
+fn truncate_content(content: String, limit: usize) -> (String, bool) {
+    let Some((cut, _)) = content.char_indices().nth(limit) else {
+        return (content, false);
+    };
+
+    let dropped = content[cut..].chars().count();
+    let mut truncated = content;
+    truncated.truncate(cut);
+    truncated.push_str(&format!("\n...[truncated {} chars]...", dropped));
+    (truncated, true)
+}
+
+/// Check whether a file should be treated as binary when building a prompt
+///
+/// Anything `is_text_file` or the language table recognizes is text, unless the user blocked
+/// its extension.
+fn is_binary_file(path: &str, text_overrides: &TextFileOverrides) -> bool {
+    text_overrides.blocks(path)
+        || (!is_text_file(path, text_overrides) && detect_language(Path::new(path)).is_none())
+}
+
 /// Build a prompt from selected files and template
 #[tauri::command]
 pub async fn build_prompt_from_files(
     request: BuildPromptRequest,
     db: tauri::State<'_, DbConnection>,
-) -> Result<BuildPromptResponse, String> {
+) -> CommandResult<BuildPromptResponse> {
+    // Only validation needs the database; files are read after the lock is released
+    let plan = {
+        let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+        plan_prompt(&conn, &request)?
+    };
+    assemble_prompt(&request, plan)
+}
+
+/// Build a prompt like `build_prompt_from_files` and open it in an AI interface like
+/// `launch_browser`, so the prompt never has to round-trip through the frontend
+///
+/// Fails before launching anything when none of the selected files are indexed.
+#[tauri::command]
+pub async fn build_and_launch(
+    request: BuildPromptRequest,
+    interface: String,
+    custom_url: Option<String>,
+    app: tauri::AppHandle,
+    db: tauri::State<'_, DbConnection>,
+    processes: tauri::State<'_, BrowserProcesses>,
+) -> CommandResult<BuildAndLaunchResponse> {
+    let plan = {
+        let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+        plan_prompt(&conn, &request)?
+    };
+    let prompt = assemble_launch_prompt(&request, plan)?;
+
+    let launch = launch_browser_internal(
+        &interface,
+        prompt.prompt.clone(),
+        custom_url,
+        &app,
+        &db,
+        &processes,
+    )
+    .await?;
+
+    Ok(BuildAndLaunchResponse { prompt, launch })
+}
+
+/// Internal function to build the prompt for `build_and_launch`, refusing an empty one
+fn assemble_launch_prompt(
+    request: &BuildPromptRequest,
+    plan: PromptPlan,
+) -> CommandResult<BuildPromptResponse> {
+    if plan.file_paths.is_empty() {
+        return Err(CommandError::new(
+            CommandError::INVALID_ARGUMENT,
+            "None of the selected files are indexed; there is no prompt to send",
+        ));
+    }
+
+    let response = assemble_prompt(request, plan)?;
+    if response.prompt.trim().is_empty() {
+        return Err(CommandError::new(
+            CommandError::INVALID_ARGUMENT,
+            "The built prompt is empty",
+        ));
+    }
+    Ok(response)
+}
+
+/// Build a prompt like `build_prompt_from_files`, split into chunks of at most
+/// `max_tokens_per_chunk` estimated tokens for sending as consecutive messages
+///
+/// The first chunk starts with the template header. Files are never split across chunks
+/// unless one alone exceeds the limit, in which case it is split on line boundaries.
+/// Concatenating the chunk contents in order gives the unsplit prompt.
+#[tauri::command]
+pub async fn build_prompt_chunks(
+    request: BuildPromptRequest,
+    max_tokens_per_chunk: usize,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<Vec<PromptChunk>> {
+    if max_tokens_per_chunk == 0 {
+        return Err(CommandError::new(
+            CommandError::INVALID_ARGUMENT,
+            "max_tokens_per_chunk must be greater than 0",
+        ));
+    }
+
+    let plan = {
+        let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+        plan_prompt(&conn, &request)?
+    };
+    assemble_prompt_chunks(&request, plan, max_tokens_per_chunk)
+}
+
+/// Read the planned files and split the prompt into chunks; needs no database access
+fn assemble_prompt_chunks(
+    request: &BuildPromptRequest,
+    plan: PromptPlan,
+    max_tokens_per_chunk: usize,
+) -> CommandResult<Vec<PromptChunk>> {
+    let file_contents = read_windowed(&plan.file_paths, |file_path| {
+        load_file_block(file_path, request, &plan.text_overrides)
+    })
+    .map(|(file_path, block)| (file_path, block.into_content()));
+    let pieces = build_prompt_pieces(
+        &plan.template_id,
+        request.custom_instructions.as_deref(),
+        &plan.template_vars(request)?,
+        file_contents,
+    )
+    .map_err(|e| CommandError::new(CommandError::INVALID_ARGUMENT, e))?;
+
+    let chunks = pack_chunks(pieces, max_tokens_per_chunk);
     log::info!(
-        "Building prompt with template '{}' for {} files",
-        request.template_id,
-        request.file_paths.len()
+        "Split prompt for {} files into {} chunks",
+        plan.file_paths.len(),
+        chunks.len()
     );
+    Ok(chunks)
+}
 
-    let conn = db
-        .lock()
-        .map_err(|e| format!("Failed to lock database: {}", e))?;
+/// Pack prompt pieces greedily into chunks within `max_tokens` estimated tokens.
+///
+/// Budgets are checked in characters, matching the ~4 chars/token estimate. A piece that
+/// can't fit in an empty chunk is split on line boundaries.
+fn pack_chunks(pieces: Vec<String>, max_tokens: usize) -> Vec<PromptChunk> {
+    let max_chars = max_tokens.saturating_mul(4);
+    let mut contents: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
 
-    // Verify files exist in database and read content
-    let mut file_contents = Vec::new();
-    let mut total_chars = 0;
+    for piece in pieces {
+        let chars = piece.chars().count();
+        if current_chars + chars <= max_chars {
+            current.push_str(&piece);
+            current_chars += chars;
+            continue;
+        }
 
-    for file_path in &request.file_paths {
-        // Verify file exists in index and is not a directory
-        let is_valid: bool = conn
-            .query_row(
-                "SELECT 1 FROM files WHERE path = ? AND is_dir = 0",
-                params![file_path],
-                |_| Ok(true),
-            )
-            .unwrap_or(false);
+        if !current.is_empty() {
+            contents.push(std::mem::take(&mut current));
+        }
+        if chars <= max_chars {
+            current = piece;
+            current_chars = chars;
+        } else {
+            let mut parts = split_on_lines(&piece, max_chars);
+            current = parts.pop().unwrap_or_default();
+            current_chars = current.chars().count();
+            contents.extend(parts);
+        }
+    }
+    if !current.is_empty() {
+        contents.push(current);
+    }
 
-        if !is_valid {
-            log::warn!("File not in index or is a directory: {}", file_path);
+    let total = contents.len();
+    contents
+        .into_iter()
+        .enumerate()
+        .map(|(index, content)| PromptChunk {
+            index,
+            total,
+            token_estimate: estimate_text_tokens(&content),
+            content,
+        })
+        .collect()
+}
+
+/// Split `text` into parts of at most `max_chars` characters, breaking after newlines.
+///
+/// A single line longer than `max_chars` is broken mid-line.
+fn split_on_lines(text: &str, max_chars: usize) -> Vec<String> {
+    let max_chars = max_chars.max(1);
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+
+    for line in text.split_inclusive('\n') {
+        let chars = line.chars().count();
+        if current_chars + chars > max_chars && !current.is_empty() {
+            parts.push(std::mem::take(&mut current));
+            current_chars = 0;
+        }
+        if chars <= max_chars {
+            current.push_str(line);
+            current_chars += chars;
             continue;
         }
 
-        match read_file_content(file_path) {
-            Ok(content) => {
-                total_chars += content.len();
-                file_contents.push((file_path.clone(), content));
+        let mut line_chars = line.chars().peekable();
+        while line_chars.peek().is_some() {
+            let segment: String = line_chars.by_ref().take(max_chars).collect();
+            if segment.chars().count() == max_chars {
+                parts.push(segment);
+            } else {
+                current_chars = segment.chars().count();
+                current = segment;
             }
-            Err(e) => {
-                log::warn!("Failed to read file {}: {}", file_path, e);
-                file_contents.push((file_path.clone(), format!("[Error reading file: {}]", e)));
+        }
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+    parts
+}
+
+/// Build a prompt exactly like `build_prompt_from_files` and write it to `dest_path`
+///
+/// Parent directories are created as needed. An existing file is only replaced when
+/// `overwrite` is set.
+#[tauri::command]
+pub async fn build_prompt_to_file(
+    request: BuildPromptRequest,
+    dest_path: String,
+    overwrite: Option<bool>,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<PromptFileResponse> {
+    let plan = {
+        let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+        plan_prompt(&conn, &request)?
+    };
+    let response = assemble_prompt(&request, plan)?;
+    write_prompt_file(response, Path::new(&dest_path), overwrite.unwrap_or(false))
+}
+
+/// Write a built prompt to `dest_path`, creating parent directories as needed
+fn write_prompt_file(
+    response: BuildPromptResponse,
+    dest_path: &Path,
+    overwrite: bool,
+) -> CommandResult<PromptFileResponse> {
+    if let Some(parent) = dest_path.parent() {
+        fs::create_dir_all(parent).map_err(AppError::from)?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true);
+    if overwrite {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+
+    let mut file = options.open(dest_path).map_err(|e| {
+        if e.kind() == std::io::ErrorKind::AlreadyExists {
+            CommandError::new(
+                CommandError::INVALID_ARGUMENT,
+                format!("File already exists: {}", dest_path.display()),
+            )
+        } else {
+            CommandError::new(
+                CommandError::IO,
+                format!("Failed to open {}: {}", dest_path.display(), e),
+            )
+        }
+    })?;
+    file.write_all(response.prompt.as_bytes())
+        .map_err(AppError::from)?;
+
+    log::info!(
+        "Wrote prompt ({} bytes) to {}",
+        response.prompt.len(),
+        dest_path.display()
+    );
+
+    Ok(PromptFileResponse {
+        path: dest_path.to_string_lossy().to_string(),
+        bytes_written: response.prompt.len() as u64,
+        file_count: response.file_count,
+        total_chars: response.total_chars,
+        total_bytes: response.total_bytes,
+        truncated_files: response.truncated_files,
+        omitted_files: response.omitted_files,
+    })
+}
+
+/// Resolve the template for a request: the requested id, else the project's
+/// `default_template`, else the configured `default_template`, else the built-in default
+///
+/// A configured default that no longer matches a known template is an error rather than a
+/// silent fallback, so a stale setting gets noticed.
+fn resolve_template_id(
+    conn: &rusqlite::Connection,
+    requested: Option<&str>,
+    project_default: Option<&str>,
+) -> CommandResult<String> {
+    if let Some(template_id) = requested {
+        return Ok(template_id.to_string());
+    }
+
+    let template_id = match project_default {
+        Some(template_id) => template_id.to_string(),
+        None => get_setting_with_conn(conn, "default_template")?
+            .unwrap_or_else(|| AppSettings::default().default_template),
+    };
+    if !get_builtin_templates()
+        .iter()
+        .any(|template| template.id == template_id)
+    {
+        return Err(CommandError::new(
+            CommandError::INVALID_ARGUMENT,
+            format!(
+                "Configured default_template '{}' does not match any known template",
+                template_id
+            ),
+        ));
+    }
+
+    Ok(template_id)
+}
+
+/// Maximum number of paths bound in a single `IN (...)` lookup
+const PATH_QUERY_CHUNK: usize = 500;
+
+/// The resolved template and the requested files that are indexed, in request order
+struct PromptPlan {
+    template_id: String,
+    file_paths: Vec<String>,
+    /// Innermost indexed root of each file, without repeats, for `{{git_diff}}`
+    file_roots: Vec<String>,
+    text_overrides: TextFileOverrides,
+}
+
+impl PromptPlan {
+    /// Values for the placeholders the caller supplies, computed only when the prompt uses them
+    fn template_vars(
+        &self,
+        request: &BuildPromptRequest,
+    ) -> CommandResult<HashMap<&'static str, String>> {
+        let mut vars = HashMap::new();
+        let custom_instructions = request.custom_instructions.as_deref();
+        if uses_placeholder(&self.template_id, custom_instructions, "git_diff")
+            .map_err(|e| CommandError::new(CommandError::INVALID_ARGUMENT, e))?
+        {
+            let sections: Vec<String> = self
+                .file_roots
+                .iter()
+                .map(|root| git_changes(Path::new(root)).to_prompt_section(root))
+                .filter(|section| !section.is_empty())
+                .collect();
+            vars.insert("git_diff", sections.join("\n\n"));
+        }
+        Ok(vars)
+    }
+}
+
+/// A file's content for the prompt and how it was obtained
+enum FileBlock {
+    Omitted,
+    Read {
+        content: String,
+        /// Characters read, before truncation
+        chars: usize,
+        /// Bytes of the decoded content, before truncation
+        bytes: usize,
+        truncated: bool,
+    },
+    Failed(String),
+}
+
+impl FileBlock {
+    fn into_content(self) -> String {
+        match self {
+            FileBlock::Omitted => BINARY_PLACEHOLDER.to_string(),
+            FileBlock::Read { content, .. } => content,
+            FileBlock::Failed(placeholder) => placeholder,
+        }
+    }
+}
+
+/// Files read in parallel at a time while building a prompt
+const READ_WINDOW: usize = 16;
+
+/// Load `file_paths` in order, `READ_WINDOW` of them in parallel at a time
+///
+/// A window is only read once the caller has consumed the previous one, so a prompt build
+/// holds at most one window of file contents besides its output.
+fn read_windowed<'a, T, F>(
+    file_paths: &'a [String],
+    load: F,
+) -> impl Iterator<Item = (&'a String, T)> + 'a
+where
+    T: Send + 'a,
+    F: Fn(&str) -> T + Sync + 'a,
+{
+    file_paths.chunks(READ_WINDOW).flat_map(move |window| {
+        let loaded: Vec<T> = window.par_iter().map(|file_path| load(file_path)).collect();
+        window.iter().zip(loaded)
+    })
+}
+
+/// Indexed files (not directories) among `paths`, looked up in chunked `IN (...)` queries
+fn indexed_files(
+    conn: &rusqlite::Connection,
+    paths: &[String],
+) -> rusqlite::Result<HashSet<String>> {
+    let mut indexed = HashSet::new();
+    for chunk in paths.chunks(PATH_QUERY_CHUNK) {
+        let placeholders = vec!["?"; chunk.len()].join(", ");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT path FROM files WHERE is_dir = 0 AND path IN ({})",
+            placeholders
+        ))?;
+        let rows = stmt.query_map(params_from_iter(chunk), |row| row.get::<_, String>(0))?;
+        for path in rows {
+            indexed.insert(path?);
+        }
+    }
+    Ok(indexed)
+}
+
+/// Everything a prompt build needs from the database
+fn plan_prompt(
+    conn: &rusqlite::Connection,
+    request: &BuildPromptRequest,
+) -> CommandResult<PromptPlan> {
+    let (file_paths, file_roots) = indexed_selection(conn, &request.file_paths)?;
+
+    // Files all from one indexed folder follow its .ai-context.toml
+    let project_default = match (&request.template_id, file_roots.as_slice()) {
+        (None, [root]) if Path::new(root).is_dir() => {
+            ProjectConfig::load_or_default(Path::new(root)).default_template
+        }
+        _ => None,
+    };
+    let template_id = resolve_template_id(
+        conn,
+        request.template_id.as_deref(),
+        project_default.as_deref(),
+    )?;
+
+    Ok(PromptPlan {
+        template_id,
+        file_paths,
+        file_roots,
+        text_overrides: TextFileOverrides::load(conn),
+    })
+}
+
+/// The requested files that are indexed, in request order, and the roots holding them
+///
+/// Paths are normalized first since the index stores them with forward slashes. An indexed
+/// file outside every indexed root, such as a row left behind when its folder was removed,
+/// fails the whole selection rather than being read.
+fn indexed_selection(
+    conn: &rusqlite::Connection,
+    file_paths: &[String],
+) -> CommandResult<(Vec<String>, Vec<String>)> {
+    let paths: Vec<String> = file_paths
+        .iter()
+        .map(|file_path| normalize_path(file_path))
+        .collect();
+    let indexed = indexed_files(conn, &paths)?;
+    let file_paths: Vec<String> = paths
+        .into_iter()
+        .filter(|file_path| {
+            let is_valid = indexed.contains(file_path);
+            if !is_valid {
+                log::warn!("File not in index or is a directory: {}", file_path);
             }
+            is_valid
+        })
+        .collect();
+
+    let roots = indexed_roots(conn)?;
+    let mut file_roots: Vec<String> = Vec::new();
+    for file_path in &file_paths {
+        let Some(root) = roots
+            .iter()
+            .filter(|root| is_within(file_path, root))
+            .max_by_key(|root| root.len())
+        else {
+            return Err(CommandError::new(
+                CommandError::PATH,
+                format!("File is outside every indexed folder: {}", file_path),
+            ));
+        };
+        if !file_roots.contains(root) {
+            file_roots.push(root.clone());
         }
     }
 
-    // Build the prompt
-    let prompt = build_prompt(
-        &request.template_id,
+    Ok((file_paths, file_roots))
+}
+
+/// Read one file for the prompt, applying binary placeholders and truncation
+fn load_file_block(
+    file_path: &str,
+    request: &BuildPromptRequest,
+    text_overrides: &TextFileOverrides,
+) -> FileBlock {
+    if request.binary_placeholders && is_binary_file(file_path, text_overrides) {
+        return FileBlock::Omitted;
+    }
+
+    match read_file_content(file_path) {
+        Ok(content) => {
+            let chars = content.chars().count();
+            let bytes = content.len();
+            let (content, truncated) = match request.per_file_char_limit {
+                Some(limit) => truncate_content(content, limit),
+                None => (content, false),
+            };
+            FileBlock::Read {
+                content,
+                chars,
+                bytes,
+                truncated,
+            }
+        }
+        Err(e) => {
+            log::warn!("Failed to read file {}: {}", file_path, e);
+            FileBlock::Failed(format!("[Error reading file: {}]", e))
+        }
+    }
+}
+
+/// Read the planned files and build the prompt; needs no database access
+fn assemble_prompt(
+    request: &BuildPromptRequest,
+    plan: PromptPlan,
+) -> CommandResult<BuildPromptResponse> {
+    log::info!(
+        "Building prompt with template '{}' for {} files",
+        plan.template_id,
+        plan.file_paths.len()
+    );
+
+    let mut file_count = 0;
+    let mut total_chars = 0;
+    let mut total_bytes = 0;
+    let mut truncated_files = 0;
+    let mut omitted_files = Vec::new();
+
+    // Files are read in parallel windows and tallied as the prompt consumes them
+    let file_contents = read_windowed(&plan.file_paths, |file_path| {
+        load_file_block(file_path, request, &plan.text_overrides)
+    })
+    .map(|(file_path, block)| {
+        match &block {
+            FileBlock::Omitted => omitted_files.push(file_path.clone()),
+            FileBlock::Read {
+                chars,
+                bytes,
+                truncated,
+                ..
+            } => {
+                file_count += 1;
+                total_chars += chars;
+                total_bytes += bytes;
+                if *truncated {
+                    truncated_files += 1;
+                }
+            }
+            FileBlock::Failed(_) => file_count += 1,
+        }
+        (file_path, block.into_content())
+    });
+    let prompt = build_prompt_with_vars(
+        &plan.template_id,
         request.custom_instructions.as_deref(),
-        &file_contents,
-    )?;
+        &plan.template_vars(request)?,
+        file_contents,
+    )
+    .map_err(|e| CommandError::new(CommandError::INVALID_ARGUMENT, e))?;
 
     Ok(BuildPromptResponse {
         prompt,
-        file_count: file_contents.len(),
+        file_count,
         total_chars,
+        total_bytes,
+        truncated_files,
+        omitted_files,
     })
 }
 
+/// Concatenate the selected files as fenced markdown under a list of their paths, without a
+/// template around them
+///
+/// Files are checked against the index like in `build_prompt_from_files`; binary files get a
+/// placeholder block.
+#[tauri::command]
+pub async fn files_to_markdown(
+    file_paths: Vec<String>,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<String> {
+    let (file_paths, text_overrides) = {
+        let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+        let (file_paths, _) = indexed_selection(&conn, &file_paths)?;
+        (file_paths, TextFileOverrides::load(&conn))
+    };
+    Ok(files_to_markdown_internal(&file_paths, &text_overrides))
+}
+
+/// Read already validated files and render them as markdown; needs no database access
+fn files_to_markdown_internal(file_paths: &[String], text_overrides: &TextFileOverrides) -> String {
+    let request = BuildPromptRequest {
+        template_id: None,
+        custom_instructions: None,
+        file_paths: Vec::new(),
+        per_file_char_limit: None,
+        binary_placeholders: true,
+    };
+    render_files_markdown(
+        read_windowed(file_paths, |file_path| {
+            load_file_block(file_path, &request, text_overrides)
+        })
+        .map(|(file_path, block)| (file_path, block.into_content())),
+    )
+}
+
+/// Project the metadata `build_prompt_from_files` would return for `request`, without
+/// reading any file
+///
+/// Sizes come from the index, falling back to the file's metadata when none is stored, and
+/// a stored `token_count` is preferred over estimating from the size. Sizes are in bytes, so
+/// `per_file_char_limit` is checked against the byte size, which may flag non-ASCII files that
+/// would fit. The template around the files is rendered for real, so only the file contents
+/// are approximated.
+#[tauri::command]
+pub async fn estimate_prompt(
+    request: BuildPromptRequest,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<PromptEstimate> {
+    // Template variables can run git, so they are computed after the lock is released
+    let (plan, sizes) = {
+        let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+        let plan = plan_prompt(&conn, &request)?;
+        let sizes = indexed_sizes(&conn, &plan.file_paths)?;
+        (plan, sizes)
+    };
+    assemble_estimate(&request, &plan, &sizes)
+}
+
+/// Indexed `size` and `token_count` by path
+type IndexedSizes = HashMap<String, (Option<i64>, Option<i64>)>;
+
+/// Indexed `size` and `token_count` of `paths`, looked up in chunked `IN (...)` queries
+fn indexed_sizes(conn: &rusqlite::Connection, paths: &[String]) -> rusqlite::Result<IndexedSizes> {
+    let mut sizes = HashMap::new();
+    for chunk in paths.chunks(PATH_QUERY_CHUNK) {
+        let placeholders = vec!["?"; chunk.len()].join(", ");
+        let mut stmt = conn.prepare(&format!(
+            "SELECT path, size, token_count FROM files WHERE is_dir = 0 AND path IN ({})",
+            placeholders
+        ))?;
+        let rows = stmt.query_map(params_from_iter(chunk), |row| {
+            Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?)))
+        })?;
+        for row in rows {
+            let (path, size) = row?;
+            sizes.insert(path, size);
+        }
+    }
+    Ok(sizes)
+}
+
+/// Estimate the planned prompt from the indexed sizes; needs no database access
+fn assemble_estimate(
+    request: &BuildPromptRequest,
+    plan: &PromptPlan,
+    sizes: &IndexedSizes,
+) -> CommandResult<PromptEstimate> {
+    let mut estimate = PromptEstimate {
+        file_count: 0,
+        total_bytes: 0,
+        total_tokens: 0,
+        truncated_files: 0,
+        omitted_files: Vec::new(),
+    };
+    let mut file_tokens = 0;
+    let mut skeleton_contents = Vec::with_capacity(plan.file_paths.len());
+
+    for file_path in &plan.file_paths {
+        if request.binary_placeholders && is_binary_file(file_path, &plan.text_overrides) {
+            estimate.omitted_files.push(file_path.clone());
+            skeleton_contents.push(BINARY_PLACEHOLDER);
+            continue;
+        }
+
+        estimate.file_count += 1;
+        skeleton_contents.push("");
+
+        let (size, token_count) = sizes.get(file_path).copied().unwrap_or_default();
+        let bytes = match size {
+            Some(size) => size.max(0) as usize,
+            // Unreadable files still count, but add no content
+            None => fs::metadata(file_path).map_or(0, |meta| meta.len() as usize),
+        };
+        estimate.total_bytes += bytes;
+
+        match request.per_file_char_limit {
+            Some(limit) if bytes > limit => {
+                estimate.truncated_files += 1;
+                let marker = format!("\n...[truncated {} chars]...", bytes - limit);
+                file_tokens += (limit + marker.len()).div_ceil(4);
+            }
+            _ => {
+                file_tokens += token_count
+                    .map(|tokens| tokens.max(0) as usize)
+                    .unwrap_or_else(|| bytes.div_ceil(4));
+            }
+        }
+    }
+
+    // Everything but the file contents is known exactly, so render it with empty files
+    let skeleton = build_prompt_with_vars(
+        &plan.template_id,
+        request.custom_instructions.as_deref(),
+        &plan.template_vars(request)?,
+        plan.file_paths.iter().zip(skeleton_contents),
+    )
+    .map_err(|e| CommandError::new(CommandError::INVALID_ARGUMENT, e))?;
+    estimate.total_tokens = estimate_text_tokens(&skeleton) + file_tokens;
+
+    Ok(estimate)
+}
+
 /// Get file content by path
 #[tauri::command]
 pub async fn get_file_content(
     file_path: String,
     db: tauri::State<'_, DbConnection>,
-) -> Result<FileContent, String> {
-    let conn = db
-        .lock()
-        .map_err(|e| format!("Failed to lock database: {}", e))?;
+) -> CommandResult<FileContent> {
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+    get_file_content_internal(&conn, &file_path)
+}
+
+/// Internal function to read one indexed file
+///
+/// The path is normalized to forward slashes before both the index lookup and the read.
+fn get_file_content_internal(
+    conn: &rusqlite::Connection,
+    file_path: &str,
+) -> CommandResult<FileContent> {
+    let file_path = normalize_path(file_path);
 
     // Verify file exists in index and is not a directory
-    conn.query_row(
-        "SELECT 1 FROM files WHERE path = ? AND is_dir = 0",
-        params![&file_path],
-        |_| Ok(()),
-    )
-    .map_err(|e| format!("File not found in index: {}", e))?;
+    let indexed = match IndexedFile::query(conn, &file_path)? {
+        Some(indexed) if !indexed.is_dir => indexed,
+        _ => {
+            return Err(CommandError::new(
+                CommandError::NOT_FOUND,
+                format!("File not found in index: {}", file_path),
+            ))
+        }
+    };
 
-    let content = read_file_content(&file_path)
-        .map_err(|e| format!("Failed to read file content: {}", e))?;
+    indexed
+        .read(file_path)
+        .map_err(|e| CommandError::new(e.code(), format!("Failed to read file content: {}", e)))
+}
 
-    Ok(FileContent {
-        path: file_path,
-        content,
-    })
+/// Internal function to read every requested file, recording why any of them failed
+fn get_file_contents_internal(
+    conn: &rusqlite::Connection,
+    file_paths: Vec<String>,
+) -> CommandResult<FileContentsResult> {
+    let mut result = FileContentsResult {
+        contents: Vec::new(),
+        failures: Vec::new(),
+    };
+
+    for file_path in file_paths {
+        let file_path = normalize_path(&file_path);
+
+        // Verify file exists in index and is not a directory
+        let reason = match IndexedFile::query(conn, &file_path)? {
+            None => FileFailureReason::NotIndexed,
+            Some(indexed) if indexed.is_dir => FileFailureReason::IsDirectory,
+            Some(indexed) => match indexed.read(file_path.clone()) {
+                Ok(content) => {
+                    result.contents.push(content);
+                    continue;
+                }
+                Err(e) => FileFailureReason::Io {
+                    message: e.to_string(),
+                },
+            },
+        };
+
+        log::warn!("Skipping file {}: {:?}", file_path, reason);
+        result.failures.push(FileFailure {
+            path: file_path,
+            reason,
+        });
+    }
+
+    Ok(result)
 }
 
 /// Get multiple file contents by paths
+///
+/// Files that are not indexed, are directories or can't be read are skipped; use
+/// `get_file_contents_detailed` to find out which and why.
 #[tauri::command]
 pub async fn get_file_contents(
     file_paths: Vec<String>,
     db: tauri::State<'_, DbConnection>,
-) -> Result<Vec<FileContent>, String> {
-    let conn = db
-        .lock()
-        .map_err(|e| format!("Failed to lock database: {}", e))?;
+) -> CommandResult<Vec<FileContent>> {
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+    Ok(get_file_contents_internal(&conn, file_paths)?.contents)
+}
 
-    let mut contents = Vec::new();
+/// Get multiple file contents by paths, along with a failure reason for each file skipped
+#[tauri::command]
+pub async fn get_file_contents_detailed(
+    file_paths: Vec<String>,
+    db: tauri::State<'_, DbConnection>,
+) -> CommandResult<FileContentsResult> {
+    let conn = db.lock().map_err(|e| CommandError::lock("database", e))?;
+    get_file_contents_internal(&conn, file_paths)
+}
 
-    for file_path in file_paths {
-        // Verify file exists in index and is not a directory
-        let is_valid: bool = conn
-            .query_row(
-                "SELECT 1 FROM files WHERE path = ? AND is_dir = 0",
-                params![&file_path],
-                |_| Ok(true),
-            )
-            .unwrap_or(false);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_config::PROJECT_CONFIG_FILE;
+    use crate::templates::build_prompt;
+    use rusqlite::Connection;
+    use tempfile::{NamedTempFile, TempDir};
 
-        if !is_valid {
-            log::warn!("File not in index or is a directory: {}", file_path);
-            continue;
+    /// Plan and assemble a prompt with an already locked connection
+    fn build_prompt_internal(
+        conn: &rusqlite::Connection,
+        request: &BuildPromptRequest,
+    ) -> CommandResult<BuildPromptResponse> {
+        let plan = plan_prompt(conn, request)?;
+        assemble_prompt(request, plan)
+    }
+
+    /// Plan and estimate a prompt with an already locked connection
+    fn estimate_prompt_internal(
+        conn: &rusqlite::Connection,
+        request: &BuildPromptRequest,
+    ) -> CommandResult<PromptEstimate> {
+        let plan = plan_prompt(conn, request)?;
+        let sizes = indexed_sizes(conn, &plan.file_paths)?;
+        assemble_estimate(request, &plan, &sizes)
+    }
+
+    fn build_prompt_to_file_internal(
+        conn: &rusqlite::Connection,
+        request: &BuildPromptRequest,
+        dest_path: &Path,
+        overwrite: bool,
+    ) -> CommandResult<PromptFileResponse> {
+        let response = build_prompt_internal(conn, request)?;
+        write_prompt_file(response, dest_path, overwrite)
+    }
+
+    #[tokio::test]
+    async fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(String::new(), None).await.unwrap(), 0);
+
+        // cl100k encodes this sentence as 10 tokens; the heuristic should stay close
+        let text = "The quick brown fox jumps over the lazy dog.".to_string();
+        let estimate = estimate_tokens(text.clone(), None).await.unwrap();
+        assert!((9..=12).contains(&estimate), "estimate was {}", estimate);
+        assert_eq!(
+            estimate_tokens(text, Some("gpt-4".to_string()))
+                .await
+                .unwrap(),
+            estimate
+        );
+    }
+
+    /// Index `files` as (name, content) pairs under a temp directory
+    fn setup_indexed_files(files: &[(&str, &str)]) -> (TempDir, Connection, Vec<String>) {
+        let temp_dir = TempDir::new().unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::schema::init_database(&conn).unwrap();
+
+        let mut paths = Vec::new();
+        for (name, content) in files {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, content).unwrap();
+            let path = path.to_str().unwrap().to_string();
+            conn.execute(
+                "INSERT INTO files (path, name, is_dir) VALUES (?, ?, 0)",
+                params![path, name],
+            )
+            .unwrap();
+            paths.push(path);
         }
 
-        match read_file_content(&file_path) {
-            Ok(content) => {
-                contents.push(FileContent {
-                    path: file_path,
-                    content,
-                });
+        (temp_dir, conn, paths)
+    }
+
+    #[test]
+    fn test_read_windowed_bounds_live_contents() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// File content that counts how many are alive at once
+        struct Content<'a> {
+            live: &'a AtomicUsize,
+        }
+        impl AsRef<str> for Content<'_> {
+            fn as_ref(&self) -> &str {
+                "content"
             }
-            Err(e) => {
-                log::warn!("Failed to read file {}: {}", file_path, e);
+        }
+        impl Drop for Content<'_> {
+            fn drop(&mut self) {
+                self.live.fetch_sub(1, Ordering::SeqCst);
             }
         }
+
+        let live = AtomicUsize::new(0);
+        let max_live = AtomicUsize::new(0);
+        let paths: Vec<String> = (0..READ_WINDOW * 4 + 3)
+            .map(|i| format!("f{}.txt", i))
+            .collect();
+        let files = read_windowed(&paths, |_| {
+            let now = live.fetch_add(1, Ordering::SeqCst) + 1;
+            max_live.fetch_max(now, Ordering::SeqCst);
+            Content { live: &live }
+        });
+
+        let prompt = build_prompt("custom", Some("{{file_count}}"), files).unwrap();
+        assert!(prompt.starts_with(&format!("{}\n\n<f0.txt>", paths.len())));
+        assert!(prompt.ends_with(&format!("<f{}.txt>\n```txt\ncontent\n```", paths.len() - 1)));
+        assert_eq!(max_live.load(Ordering::SeqCst), READ_WINDOW);
+        assert_eq!(live.load(Ordering::SeqCst), 0);
     }
 
-    Ok(contents)
-}
+    #[test]
+    fn test_backslash_paths_match_normalized_index() {
+        let (_temp_dir, conn, paths) = setup_indexed_files(&[("a.txt", "alpha")]);
+        let backslashed = paths[0].replace('/', "\\");
+        assert_ne!(backslashed, paths[0]);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::io::Write;
-    use tempfile::NamedTempFile;
+        let file = get_file_content_internal(&conn, &backslashed).unwrap();
+        assert_eq!(file.path, paths[0]);
+        assert_eq!(file.content, "alpha");
+
+        let result = get_file_contents_internal(&conn, vec![backslashed.clone()]).unwrap();
+        assert_eq!(result.contents.len(), 1);
+        assert!(result.failures.is_empty());
+
+        let request = BuildPromptRequest {
+            template_id: Some("custom".to_string()),
+            custom_instructions: Some("{{files}}".to_string()),
+            file_paths: vec![backslashed],
+            per_file_char_limit: None,
+            binary_placeholders: false,
+        };
+        let response = build_prompt_internal(&conn, &request).unwrap();
+        assert_eq!(response.file_count, 1);
+        assert!(response.prompt.contains("alpha"));
+    }
+
+    #[test]
+    fn test_get_file_contents_reports_failures() {
+        let (temp_dir, conn, paths) = setup_indexed_files(&[("a.txt", "alpha"), ("gone.txt", "x")]);
+        let dir = temp_dir.path().to_str().unwrap().to_string();
+        conn.execute(
+            "INSERT INTO files (path, name, is_dir) VALUES (?, 'dir', 1)",
+            params![dir],
+        )
+        .unwrap();
+        // Indexed, but deleted from disk since
+        fs::remove_file(&paths[1]).unwrap();
+        let missing = temp_dir
+            .path()
+            .join("missing.txt")
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let result = get_file_contents_internal(
+            &conn,
+            vec![
+                paths[0].clone(),
+                dir.clone(),
+                missing.clone(),
+                paths[1].clone(),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(result.contents.len(), 1);
+        assert_eq!(result.contents[0].path, paths[0]);
+        assert_eq!(result.contents[0].content, "alpha");
+
+        let failures: Vec<(&str, &FileFailureReason)> = result
+            .failures
+            .iter()
+            .map(|f| (f.path.as_str(), &f.reason))
+            .collect();
+        assert_eq!(failures.len(), 3);
+        assert_eq!(failures[0], (dir.as_str(), &FileFailureReason::IsDirectory));
+        assert_eq!(
+            failures[1],
+            (missing.as_str(), &FileFailureReason::NotIndexed)
+        );
+        assert_eq!(failures[2].0, paths[1]);
+        assert!(matches!(failures[2].1, FileFailureReason::Io { .. }));
+    }
+
+    #[test]
+    fn test_get_file_content_returns_indexed_metadata() {
+        let (temp_dir, conn, paths) = setup_indexed_files(&[("main.rs", "fn main() {}")]);
+        conn.execute(
+            "UPDATE files SET size = 12, mtime = 1700000000, token_count = 3, language = 'rust'
+             WHERE path = ?",
+            params![paths[0]],
+        )
+        .unwrap();
+
+        let file = get_file_content_internal(&conn, &paths[0]).unwrap();
+        assert_eq!(file.content, "fn main() {}");
+        assert_eq!(file.size, Some(12));
+        assert_eq!(file.mtime, Some(1_700_000_000));
+        assert_eq!(file.token_count, Some(3));
+        assert_eq!(file.language.as_deref(), Some("rust"));
+        assert_eq!(file.encoding.as_deref(), Some("utf-8"));
+
+        // Content that isn't UTF-8 is decoded and its encoding reported
+        let latin1 = temp_dir.path().join("latin1.txt");
+        fs::write(&latin1, b"caf\xe9 cr\xe8me br\xfbl\xe9e").unwrap();
+        let latin1 = latin1.to_str().unwrap().to_string();
+        conn.execute(
+            "INSERT INTO files (path, name, is_dir) VALUES (?, 'latin1.txt', 0)",
+            params![latin1],
+        )
+        .unwrap();
+
+        let result = get_file_contents_internal(&conn, vec![latin1]).unwrap();
+        assert_eq!(result.contents[0].content, "café crème brûlée");
+        assert_eq!(result.contents[0].encoding.as_deref(), Some("windows-1252"));
+        assert_eq!(result.contents[0].size, None);
+    }
+
+    #[test]
+    fn test_file_failure_reason_serialization() {
+        let json = serde_json::to_value(FileFailureReason::NotIndexed).unwrap();
+        assert_eq!(json, serde_json::json!({ "kind": "not_indexed" }));
+
+        let json = serde_json::to_value(FileFailureReason::Io {
+            message: "denied".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "kind": "io", "message": "denied" })
+        );
+    }
+
+    #[test]
+    fn test_truncate_content() {
+        let (content, truncated) = truncate_content("short".to_string(), 10);
+        assert_eq!(content, "short");
+        assert!(!truncated);
+
+        let (content, truncated) = truncate_content("exactly10!".to_string(), 10);
+        assert_eq!(content, "exactly10!");
+        assert!(!truncated);
+
+        // Limits count characters, not bytes
+        let (content, truncated) = truncate_content("ąęść-tail".to_string(), 4);
+        assert_eq!(content, "ąęść\n...[truncated 5 chars]...");
+        assert!(truncated);
+    }
+
+    #[test]
+    fn test_build_prompt_per_file_char_limit() {
+        let long = "x".repeat(50);
+        let (_temp_dir, conn, paths) =
+            setup_indexed_files(&[("long.txt", long.as_str()), ("short.txt", "tiny")]);
+        let request = BuildPromptRequest {
+            template_id: Some("custom".to_string()),
+            custom_instructions: Some("{{files}}".to_string()),
+            file_paths: paths,
+            per_file_char_limit: Some(20),
+            binary_placeholders: false,
+        };
+
+        let response = build_prompt_internal(&conn, &request).unwrap();
+
+        assert_eq!(response.truncated_files, 1);
+        assert_eq!(response.file_count, 2);
+        assert_eq!(response.total_chars, 54);
+        assert_eq!(response.total_bytes, 54);
+        assert!(response.prompt.contains(&format!(
+            "{}\n...[truncated 30 chars]...\n```",
+            "x".repeat(20)
+        )));
+        assert!(!response.prompt.contains(&"x".repeat(21)));
+        assert!(response.prompt.contains("```txt\ntiny\n```"));
+    }
+
+    #[test]
+    fn test_build_prompt_chunks() {
+        let files: Vec<(String, String)> = (0..6)
+            .map(|i| {
+                (
+                    format!("f{}.txt", i),
+                    format!("line {}\n", i).repeat(10 + i * 5),
+                )
+            })
+            .collect();
+        let files: Vec<(&str, &str)> = files
+            .iter()
+            .map(|(name, content)| (name.as_str(), content.as_str()))
+            .collect();
+        let (_temp_dir, conn, paths) = setup_indexed_files(&files);
+        let request = BuildPromptRequest {
+            template_id: Some("planning".to_string()),
+            custom_instructions: Some("Plan it".to_string()),
+            file_paths: paths.clone(),
+            per_file_char_limit: None,
+            binary_placeholders: false,
+        };
+        let whole = build_prompt_internal(&conn, &request).unwrap().prompt;
+
+        let plan = plan_prompt(&conn, &request).unwrap();
+        let chunks = assemble_prompt_chunks(&request, plan, 150).unwrap();
+
+        assert!(chunks.len() > 1);
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.index, i);
+            assert_eq!(chunk.total, chunks.len());
+            assert!(chunk.token_estimate <= 150, "chunk {} is too big", i);
+        }
+        assert!(chunks[0]
+            .content
+            .starts_with("You are a technical architect"));
+        assert!(chunks[0].content.contains("Plan it"));
+
+        // No file is split, and files keep their order across chunks
+        let mut seen = Vec::new();
+        for chunk in &chunks {
+            for path in &paths {
+                if chunk.content.contains(&format!("<{}>", path)) {
+                    assert!(chunk.content.matches("```").count() % 2 == 0);
+                    seen.push(path.clone());
+                }
+            }
+        }
+        assert_eq!(seen, paths);
+
+        let reassembled: String = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+        assert_eq!(reassembled, whole);
+    }
+
+    #[test]
+    fn test_pack_chunks_splits_oversized_piece_on_lines() {
+        let big = "0123456789\n".repeat(10);
+        let pieces = vec!["header\n".to_string(), big.clone(), "tail".to_string()];
+
+        let chunks = pack_chunks(pieces, 10);
+
+        // 40 characters per chunk; only the oversized piece is broken, on line boundaries
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.content.chars().count() <= 40));
+        assert_eq!(chunks[0].content, "header\n");
+        assert!(chunks[1..chunks.len() - 1]
+            .iter()
+            .all(|chunk| chunk.content.ends_with('\n')));
+        let reassembled: String = chunks.iter().map(|chunk| chunk.content.as_str()).collect();
+        assert_eq!(reassembled, format!("header\n{}tail", big));
+    }
+
+    #[test]
+    fn test_split_on_lines_breaks_long_lines() {
+        assert_eq!(
+            split_on_lines("abcdefgh\nij\n", 3),
+            vec!["abc", "def", "gh\n", "ij\n"]
+        );
+        assert_eq!(split_on_lines("a\nb\nc", 4), vec!["a\nb\n", "c"]);
+    }
+
+    #[test]
+    fn test_build_prompt_keeps_request_order() {
+        // Sizes vary so parallel reads finish in no particular order
+        let files: Vec<(String, String)> = (0..40)
+            .map(|i| {
+                (
+                    format!("file{:02}.txt", i),
+                    format!("content {}", i).repeat(1 + (i * 37) % 200),
+                )
+            })
+            .collect();
+        let file_refs: Vec<(&str, &str)> = files
+            .iter()
+            .map(|(name, content)| (name.as_str(), content.as_str()))
+            .collect();
+        let (_temp_dir, conn, mut paths) = setup_indexed_files(&file_refs);
+        paths.reverse();
+        // Unindexed paths in between are dropped without disturbing the order
+        paths.insert(10, "/not/indexed.txt".to_string());
+
+        let request = BuildPromptRequest {
+            template_id: Some("custom".to_string()),
+            custom_instructions: Some("{{files}}".to_string()),
+            file_paths: paths.clone(),
+            per_file_char_limit: None,
+            binary_placeholders: false,
+        };
+        let response = build_prompt_internal(&conn, &request).unwrap();
+
+        assert_eq!(response.file_count, 40);
+        let positions: Vec<usize> = paths
+            .iter()
+            .filter(|path| !path.starts_with("/not"))
+            .map(|path| response.prompt.find(&format!("<{}>", path)).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_build_prompt_rejects_files_outside_indexed_roots() {
+        let (temp_dir, conn, paths) = setup_indexed_files(&[("a.rs", "fn a() {}")]);
+        let root = normalize_path(&temp_dir.path().to_string_lossy());
+        conn.execute(
+            "INSERT INTO files (path, name, is_dir, is_root) VALUES (?, 'root', 1, 1)",
+            params![root],
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE files SET parent_path = ? WHERE path = ?",
+            params![root, paths[0]],
+        )
+        .unwrap();
+
+        // A row left behind after its folder was dropped from the index
+        let other_dir = TempDir::new().unwrap();
+        let stale = normalize_path(&other_dir.path().join("stale.rs").to_string_lossy());
+        fs::write(&stale, "fn stale() {}").unwrap();
+        conn.execute(
+            "INSERT INTO files (path, parent_path, name, is_dir) VALUES (?, ?, 'stale.rs', 0)",
+            params![stale, normalize_path(&other_dir.path().to_string_lossy())],
+        )
+        .unwrap();
+
+        let mut request = BuildPromptRequest {
+            template_id: Some("custom".to_string()),
+            custom_instructions: Some("{{files}}".to_string()),
+            file_paths: paths.clone(),
+            per_file_char_limit: None,
+            binary_placeholders: false,
+        };
+        let response = build_prompt_internal(&conn, &request).unwrap();
+        assert!(response.prompt.contains("fn a() {}"));
+
+        request.file_paths.push(stale.clone());
+        let err = build_prompt_internal(&conn, &request).unwrap_err();
+        assert_eq!(err.code, CommandError::PATH);
+        assert!(err.message.contains(&stale));
+    }
+
+    #[test]
+    fn test_indexed_files_checks_in_chunks() {
+        let names: Vec<String> = (0..PATH_QUERY_CHUNK + 5)
+            .map(|i| format!("f{}.txt", i))
+            .collect();
+        let files: Vec<(&str, &str)> = names.iter().map(|name| (name.as_str(), "")).collect();
+        let (_temp_dir, conn, mut paths) = setup_indexed_files(&files);
+        paths.push("/missing.txt".to_string());
+
+        let indexed = indexed_files(&conn, &paths).unwrap();
+        assert_eq!(indexed.len(), PATH_QUERY_CHUNK + 5);
+        assert!(!indexed.contains("/missing.txt"));
+    }
+
+    #[test]
+    fn test_build_prompt_binary_placeholders() {
+        let (_temp_dir, conn, paths) = setup_indexed_files(&[
+            ("logo.png", "\u{89}PNG\r\n\u{1a}\n"),
+            ("main.rs", "fn main() {}"),
+        ]);
+        let mut request = BuildPromptRequest {
+            template_id: Some("custom".to_string()),
+            custom_instructions: Some("{{files}}".to_string()),
+            file_paths: paths.clone(),
+            per_file_char_limit: None,
+            binary_placeholders: true,
+        };
+
+        let response = build_prompt_internal(&conn, &request).unwrap();
+
+        assert!(response.prompt.contains(&format!(
+            "<{}>\n```png\n[binary file omitted]\n```",
+            paths[0]
+        )));
+        assert!(response.prompt.contains("```rust\nfn main() {}\n```"));
+        assert_eq!(response.omitted_files, vec![paths[0].clone()]);
+        assert_eq!(response.file_count, 1);
+
+        // Without the option binary files are read like any other
+        request.binary_placeholders = false;
+        let response = build_prompt_internal(&conn, &request).unwrap();
+        assert!(!response.prompt.contains(BINARY_PLACEHOLDER));
+        assert!(response.omitted_files.is_empty());
+        assert_eq!(response.file_count, 2);
+    }
+
+    #[test]
+    fn test_build_prompt_respects_text_overrides() {
+        let (_temp_dir, conn, paths) =
+            setup_indexed_files(&[("app.conf2", "port = 8080"), ("server.log", "started")]);
+        let request = BuildPromptRequest {
+            template_id: Some("custom".to_string()),
+            custom_instructions: Some("{{files}}".to_string()),
+            file_paths: paths.clone(),
+            per_file_char_limit: None,
+            binary_placeholders: true,
+        };
+
+        let response = build_prompt_internal(&conn, &request).unwrap();
+        assert_eq!(response.omitted_files, vec![paths[0].clone()]);
+        assert!(response.prompt.contains("started"));
+
+        for (key, value) in [
+            ("text_extensions", r#"[".conf2"]"#),
+            ("blocked_text_extensions", r#"[".log"]"#),
+        ] {
+            conn.execute(
+                "INSERT INTO settings (key, value) VALUES (?, ?)",
+                params![key, value],
+            )
+            .unwrap();
+        }
+
+        let response = build_prompt_internal(&conn, &request).unwrap();
+        assert_eq!(response.omitted_files, vec![paths[1].clone()]);
+        assert!(response.prompt.contains("port = 8080"));
+        assert!(!response.prompt.contains("started"));
+    }
+
+    #[test]
+    fn test_estimate_prompt_matches_build() {
+        let source = "fn main() {\n    println!(\"hello\");\n}\n".repeat(20);
+        let notes = "Some notes about the project.\n".repeat(5);
+        let (_temp_dir, conn, paths) = setup_indexed_files(&[
+            ("main.rs", source.as_str()),
+            ("notes.md", notes.as_str()),
+            ("logo.png", "\u{89}PNG\r\n\u{1a}\n"),
+        ]);
+        // One size comes from the index, the others from the file's metadata
+        conn.execute(
+            "UPDATE files SET size = ? WHERE path = ?",
+            params![source.len() as i64, paths[0]],
+        )
+        .unwrap();
+
+        let mut request = BuildPromptRequest {
+            template_id: None,
+            custom_instructions: Some("Review these files".to_string()),
+            file_paths: paths.clone(),
+            per_file_char_limit: None,
+            binary_placeholders: true,
+        };
+
+        for limit in [None, Some(100)] {
+            request.per_file_char_limit = limit;
+            let estimate = estimate_prompt_internal(&conn, &request).unwrap();
+            let response = build_prompt_internal(&conn, &request).unwrap();
+
+            assert_eq!(estimate.file_count, response.file_count);
+            assert_eq!(estimate.total_bytes, response.total_bytes);
+            assert_eq!(estimate.truncated_files, response.truncated_files);
+            assert_eq!(estimate.omitted_files, response.omitted_files);
+
+            let actual = estimate_text_tokens(&response.prompt);
+            assert!(
+                estimate.total_tokens.abs_diff(actual) <= 5,
+                "estimated {} tokens, built {}",
+                estimate.total_tokens,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn test_build_and_launch_prompt() {
+        let (_temp_dir, conn, paths) = setup_indexed_files(&[("a.rs", "fn a() {}")]);
+        let mut request = BuildPromptRequest {
+            template_id: Some("agent".to_string()),
+            custom_instructions: Some("Review".to_string()),
+            file_paths: paths,
+            per_file_char_limit: None,
+            binary_placeholders: false,
+        };
+
+        let plan = plan_prompt(&conn, &request).unwrap();
+        let response = assemble_launch_prompt(&request, plan).unwrap();
+        assert_eq!(response.file_count, 1);
+        assert_eq!(
+            response.prompt,
+            build_prompt_internal(&conn, &request).unwrap().prompt
+        );
+
+        for file_paths in [Vec::new(), vec!["/not/indexed.rs".to_string()]] {
+            request.file_paths = file_paths;
+            let plan = plan_prompt(&conn, &request).unwrap();
+            let err = assemble_launch_prompt(&request, plan).unwrap_err();
+            assert_eq!(err.code, CommandError::INVALID_ARGUMENT);
+        }
+    }
+
+    #[test]
+    fn test_build_prompt_counts_chars_and_bytes() {
+        let (_temp_dir, conn, paths) = setup_indexed_files(&[("notes.md", "Zażółć 🦀")]);
+        let request = BuildPromptRequest {
+            template_id: Some("custom".to_string()),
+            custom_instructions: Some("{{files}}".to_string()),
+            file_paths: paths,
+            per_file_char_limit: None,
+            binary_placeholders: false,
+        };
+
+        let response = build_prompt_internal(&conn, &request).unwrap();
+
+        assert_eq!(response.total_chars, 8);
+        assert_eq!(response.total_bytes, "Zażółć 🦀".len());
+        assert!(response.total_bytes > response.total_chars);
+
+        // Estimates come from file sizes, so they agree on bytes
+        let estimate = estimate_prompt_internal(&conn, &request).unwrap();
+        assert_eq!(estimate.total_bytes, response.total_bytes);
+    }
+
+    #[test]
+    fn test_build_prompt_to_file() {
+        let (temp_dir, conn, paths) =
+            setup_indexed_files(&[("a.rs", "fn a() {}"), ("b.rs", "fn b() {}")]);
+        let request = BuildPromptRequest {
+            template_id: Some("agent".to_string()),
+            custom_instructions: Some("Review".to_string()),
+            file_paths: paths,
+            per_file_char_limit: None,
+            binary_placeholders: false,
+        };
+        let dest = temp_dir.path().join("out/nested/prompt.md");
+
+        let response = build_prompt_to_file_internal(&conn, &request, &dest, false).unwrap();
+
+        let written = fs::read_to_string(&dest).unwrap();
+        let expected = build_prompt_internal(&conn, &request).unwrap();
+        assert_eq!(written, expected.prompt);
+        assert_eq!(response.bytes_written, written.len() as u64);
+        assert_eq!(response.file_count, 2);
+        assert_eq!(response.total_chars, expected.total_chars);
+        assert_eq!(response.total_bytes, expected.total_bytes);
+    }
+
+    #[test]
+    fn test_build_prompt_to_file_refuses_overwrite() {
+        let (temp_dir, conn, paths) = setup_indexed_files(&[("a.rs", "fn a() {}")]);
+        let request = BuildPromptRequest {
+            template_id: Some("agent".to_string()),
+            custom_instructions: None,
+            file_paths: paths,
+            per_file_char_limit: None,
+            binary_placeholders: false,
+        };
+        let dest = temp_dir.path().join("prompt.md");
+        fs::write(&dest, "keep me").unwrap();
+
+        let err = build_prompt_to_file_internal(&conn, &request, &dest, false).unwrap_err();
+        assert_eq!(err.code, CommandError::INVALID_ARGUMENT);
+        assert_eq!(fs::read_to_string(&dest).unwrap(), "keep me");
+
+        build_prompt_to_file_internal(&conn, &request, &dest, true).unwrap();
+        assert!(fs::read_to_string(&dest).unwrap().contains("fn a() {}"));
+    }
 
     #[test]
     fn test_get_templates() {
@@ -205,6 +1762,11 @@ mod tests {
         let file_content = FileContent {
             path: "/test/path.rs".to_string(),
             content: "fn main() {}".to_string(),
+            size: Some(12),
+            mtime: None,
+            token_count: None,
+            language: Some("rust".to_string()),
+            encoding: Some("utf-8".to_string()),
         };
 
         let json = serde_json::to_string(&file_content).unwrap();
@@ -214,14 +1776,23 @@ mod tests {
         let deserialized: FileContent = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized.path, file_content.path);
         assert_eq!(deserialized.content, file_content.content);
+        assert_eq!(deserialized.language, file_content.language);
+
+        // Payloads without the metadata fields still parse
+        let deserialized: FileContent =
+            serde_json::from_str(r#"{"path": "/a.rs", "content": ""}"#).unwrap();
+        assert_eq!(deserialized.size, None);
+        assert_eq!(deserialized.encoding, None);
     }
 
     #[test]
     fn test_build_prompt_request_serialization() {
         let request = BuildPromptRequest {
-            template_id: "code-review".to_string(),
+            template_id: Some("code-review".to_string()),
             custom_instructions: Some("Focus on security".to_string()),
             file_paths: vec!["/path/a.rs".to_string(), "/path/b.rs".to_string()],
+            per_file_char_limit: Some(1000),
+            binary_placeholders: true,
         };
 
         let json = serde_json::to_string(&request).unwrap();
@@ -230,29 +1801,180 @@ mod tests {
         assert_eq!(deserialized.template_id, request.template_id);
         assert_eq!(deserialized.custom_instructions, request.custom_instructions);
         assert_eq!(deserialized.file_paths, request.file_paths);
+        assert_eq!(
+            deserialized.per_file_char_limit,
+            request.per_file_char_limit
+        );
+        assert!(deserialized.binary_placeholders);
     }
 
     #[test]
     fn test_build_prompt_request_without_custom_instructions() {
         let request = BuildPromptRequest {
-            template_id: "explain-code".to_string(),
+            template_id: Some("explain-code".to_string()),
             custom_instructions: None,
             file_paths: vec!["/path/file.rs".to_string()],
+            per_file_char_limit: None,
+            binary_placeholders: false,
         };
 
         let json = serde_json::to_string(&request).unwrap();
         let deserialized: BuildPromptRequest = serde_json::from_str(&json).unwrap();
 
-        assert_eq!(deserialized.template_id, "explain-code");
+        assert_eq!(deserialized.template_id.as_deref(), Some("explain-code"));
         assert!(deserialized.custom_instructions.is_none());
     }
 
+    #[test]
+    fn test_validate_template() {
+        let validation =
+            validate_template_internal("{{custom_instructions}}\n{{bogus}}\n{{files}}\n{{bogus}}");
+        assert_eq!(
+            validation.placeholders,
+            vec!["custom_instructions", "bogus", "files"]
+        );
+        assert_eq!(validation.unknown_placeholders, vec!["bogus"]);
+
+        let validation = validate_template_internal("{{files}} on {{date}}");
+        assert!(validation.unknown_placeholders.is_empty());
+    }
+
+    #[test]
+    fn test_files_to_markdown() {
+        let (_temp_dir, conn, mut paths) =
+            setup_indexed_files(&[("main.rs", "fn main() {}"), ("logo.png", "\u{89}PNG")]);
+        paths.push("/not/indexed.rs".to_string());
+
+        let (file_paths, _) = indexed_selection(&conn, &paths).unwrap();
+        let markdown = files_to_markdown_internal(&file_paths, &TextFileOverrides::default());
+
+        assert!(markdown.starts_with(&format!(
+            "Files:\n- {}\n- {}\n\n<{}>\n```rust\nfn main() {{}}\n```",
+            paths[0], paths[1], paths[0]
+        )));
+        assert!(markdown.ends_with(&format!(
+            "<{}>\n```png\n{}\n```",
+            paths[1], BINARY_PLACEHOLDER
+        )));
+        assert!(!markdown.contains("indexed.rs"));
+    }
+
+    #[test]
+    fn test_build_prompt_diff_template_outside_git() {
+        let (_temp_dir, conn, paths) = setup_indexed_files(&[("main.rs", "fn main() {}")]);
+        let request: BuildPromptRequest = serde_json::from_value(serde_json::json!({
+            "file_paths": paths,
+            "template_id": "diff",
+        }))
+        .unwrap();
+
+        // Not a repository, so the placeholder is filled with nothing
+        let response = build_prompt_internal(&conn, &request).unwrap();
+        assert!(!response.prompt.contains("{{git_diff}}"));
+        assert!(response.prompt.contains("fn main() {}"));
+    }
+
+    #[test]
+    fn test_build_prompt_uses_configured_default_template() {
+        let (_temp_dir, conn, paths) = setup_indexed_files(&[("main.rs", "fn main() {}")]);
+        let mut request: BuildPromptRequest =
+            serde_json::from_value(serde_json::json!({ "file_paths": paths })).unwrap();
+        assert!(request.template_id.is_none());
+
+        // Nothing configured: the built-in default
+        let response = build_prompt_internal(&conn, &request).unwrap();
+        let agent = build_prompt(
+            "agent",
+            None,
+            [(request.file_paths[0].as_str(), "fn main() {}")],
+        )
+        .unwrap();
+        assert_eq!(response.prompt, agent);
+
+        conn.execute(
+            "INSERT INTO settings (key, value) VALUES ('default_template', 'custom')",
+            [],
+        )
+        .unwrap();
+        request.custom_instructions = Some("Only {{file_count}} file".to_string());
+        let response = build_prompt_internal(&conn, &request).unwrap();
+        assert!(response.prompt.starts_with("Only 1 file"));
+    }
+
+    #[test]
+    fn test_build_prompt_uses_project_default_template() {
+        let (temp_dir, conn, paths) = setup_indexed_files(&[("main.rs", "fn main() {}")]);
+        let root = normalize_path(&temp_dir.path().to_string_lossy());
+        conn.execute(
+            "INSERT INTO files (path, name, is_dir, is_root) VALUES (?, 'root', 1, 1)",
+            params![root],
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE files SET parent_path = ? WHERE path = ?",
+            params![root, paths[0]],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO settings (key, value) VALUES ('default_template', 'agent')",
+            [],
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join(PROJECT_CONFIG_FILE),
+            "default_template = \"review\"\n",
+        )
+        .unwrap();
+
+        let mut request = BuildPromptRequest {
+            template_id: None,
+            custom_instructions: None,
+            file_paths: paths.clone(),
+            per_file_char_limit: None,
+            binary_placeholders: false,
+        };
+        let file = [(paths[0].as_str(), "fn main() {}")];
+
+        // The project's default wins over the global setting
+        let response = build_prompt_internal(&conn, &request).unwrap();
+        assert_eq!(response.prompt, build_prompt("review", None, file).unwrap());
+
+        // An explicitly requested template wins over both
+        request.template_id = Some("agent".to_string());
+        let response = build_prompt_internal(&conn, &request).unwrap();
+        assert_eq!(response.prompt, build_prompt("agent", None, file).unwrap());
+    }
+
+    #[test]
+    fn test_build_prompt_invalid_default_template_errors() {
+        let (_temp_dir, conn, paths) = setup_indexed_files(&[("main.rs", "fn main() {}")]);
+        conn.execute(
+            "INSERT INTO settings (key, value) VALUES ('default_template', 'missing')",
+            [],
+        )
+        .unwrap();
+        let request = BuildPromptRequest {
+            template_id: None,
+            custom_instructions: None,
+            file_paths: paths,
+            per_file_char_limit: None,
+            binary_placeholders: false,
+        };
+
+        let err = build_prompt_internal(&conn, &request).unwrap_err();
+        assert_eq!(err.code, CommandError::INVALID_ARGUMENT);
+        assert!(err.message.contains("'missing'"), "{}", err.message);
+    }
+
     #[test]
     fn test_build_prompt_response_serialization() {
         let response = BuildPromptResponse {
             prompt: "Generated prompt content".to_string(),
             file_count: 3,
             total_chars: 1500,
+            total_bytes: 1620,
+            truncated_files: 1,
+            omitted_files: vec!["/project/logo.png".to_string()],
         };
 
         let json = serde_json::to_string(&response).unwrap();
@@ -261,6 +1983,9 @@ mod tests {
         assert_eq!(deserialized.prompt, response.prompt);
         assert_eq!(deserialized.file_count, response.file_count);
         assert_eq!(deserialized.total_chars, response.total_chars);
+        assert_eq!(deserialized.total_bytes, response.total_bytes);
+        assert_eq!(deserialized.truncated_files, response.truncated_files);
+        assert_eq!(deserialized.omitted_files, response.omitted_files);
     }
 
     #[test]
@@ -1,7 +1,30 @@
 use crate::db::DbConnection;
+use crate::project_config::ProjectConfig;
+use crate::templates::get_builtin_templates;
 use rusqlite::{params, OptionalExtension};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Display;
+use std::str::FromStr;
+use tauri::{AppHandle, Emitter};
+
+/// Allowed range for `token_limit`
+const TOKEN_LIMIT_RANGE: std::ops::RangeInclusive<i64> = 1..=10_000_000;
+
+/// Allowed range for `cache_size_mb`, matching the settings UI
+const CACHE_SIZE_MB_RANGE: std::ops::RangeInclusive<i64> = 10..=1000;
+
+/// Allowed range for `index_batch_size`; values outside it are clamped when indexing
+pub(crate) const INDEX_BATCH_SIZE_RANGE: std::ops::RangeInclusive<u32> = 100..=50_000;
+
+/// Event emitted after settings are saved, carrying the keys whose values changed
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+/// Payload of the `settings-changed` event
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SettingsChanged {
+    pub keys: Vec<String>,
+}
 
 /// Application settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +35,117 @@ pub struct AppSettings {
     pub auto_save_history: bool,
     pub cache_size_mb: i64,
     pub respect_gitignore: bool,
+    /// Also apply the user's global git excludesfile when respecting gitignore rules
+    #[serde(default)]
+    pub use_global_gitignore: bool,
+    /// Also apply `<root>/.dockerignore` when respecting gitignore rules
+    #[serde(default)]
+    pub respect_dockerignore: bool,
+    /// Descend into symlinked directories while indexing
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// Index entries whose name starts with a dot, such as `.env` or `.github`
+    #[serde(default = "default_include_hidden")]
+    pub include_hidden: bool,
+    /// Maximum directory depth to descend into while indexing, relative to the indexed root; 0 means unlimited
+    #[serde(default)]
+    pub max_index_depth: u32,
+    /// Number of entries written per batch when saving an indexed folder
+    #[serde(default = "default_index_batch_size")]
+    pub index_batch_size: u32,
+    /// Base directory for browser profiles; empty means the app data directory
+    #[serde(default)]
+    pub browser_profile_dir: String,
+    /// Extensions to treat as text on top of the built-in list, e.g. `.conf2`
+    #[serde(default)]
+    pub text_extensions: Vec<String>,
+    /// File names to treat as text whatever their extension, e.g. `.env.local`
+    #[serde(default)]
+    pub text_file_names: Vec<String>,
+    /// Extensions never to treat as text, even when the built-in list has them
+    #[serde(default)]
+    pub blocked_text_extensions: Vec<String>,
+}
+
+fn default_index_batch_size() -> u32 {
+    1000
+}
+
+fn default_include_hidden() -> bool {
+    true
+}
+
+impl AppSettings {
+    /// Check that every value is within its allowed range, collecting all problems
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if !TOKEN_LIMIT_RANGE.contains(&self.token_limit) {
+            errors.push(format!(
+                "token_limit must be between {} and {}, got {}",
+                TOKEN_LIMIT_RANGE.start(),
+                TOKEN_LIMIT_RANGE.end(),
+                self.token_limit
+            ));
+        }
+
+        if !CACHE_SIZE_MB_RANGE.contains(&self.cache_size_mb) {
+            errors.push(format!(
+                "cache_size_mb must be between {} and {}, got {}",
+                CACHE_SIZE_MB_RANGE.start(),
+                CACHE_SIZE_MB_RANGE.end(),
+                self.cache_size_mb
+            ));
+        }
+
+        if !INDEX_BATCH_SIZE_RANGE.contains(&self.index_batch_size) {
+            errors.push(format!(
+                "index_batch_size must be between {} and {}, got {}",
+                INDEX_BATCH_SIZE_RANGE.start(),
+                INDEX_BATCH_SIZE_RANGE.end(),
+                self.index_batch_size
+            ));
+        }
+
+        if !get_builtin_templates()
+            .iter()
+            .any(|template| template.id == self.default_template)
+        {
+            errors.push(format!(
+                "default_template '{}' does not match any known template",
+                self.default_template
+            ));
+        }
+
+        if self
+            .excluded_extensions
+            .iter()
+            .any(|ext| ext.trim().is_empty())
+        {
+            errors.push("excluded_extensions cannot contain empty entries".to_string());
+        }
+
+        for (key, list) in [
+            ("text_extensions", &self.text_extensions),
+            ("text_file_names", &self.text_file_names),
+            ("blocked_text_extensions", &self.blocked_text_extensions),
+        ] {
+            if list.iter().any(|entry| entry.trim().is_empty()) {
+                errors.push(format!("{} cannot contain empty entries", key));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Configured text cache size in bytes (at least 1MB)
+    pub fn cache_size_bytes(&self) -> u64 {
+        self.cache_size_mb.max(1) as u64 * 1024 * 1024
+    }
 }
 
 impl Default for AppSettings {
@@ -36,12 +170,22 @@ impl Default for AppSettings {
             auto_save_history: true,
             cache_size_mb: 100,
             respect_gitignore: true,
+            use_global_gitignore: false,
+            respect_dockerignore: false,
+            follow_symlinks: false,
+            include_hidden: default_include_hidden(),
+            max_index_depth: 0,
+            index_batch_size: default_index_batch_size(),
+            browser_profile_dir: String::new(),
+            text_extensions: Vec::new(),
+            text_file_names: Vec::new(),
+            blocked_text_extensions: Vec::new(),
         }
     }
 }
 
 /// Internal function to save a setting
-fn save_setting_internal(db: &DbConnection, key: &str, value: &str) -> Result<(), String> {
+pub(crate) fn save_setting_internal(db: &DbConnection, key: &str, value: &str) -> Result<(), String> {
     let conn = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
 
     conn.execute(
@@ -53,30 +197,64 @@ fn save_setting_internal(db: &DbConnection, key: &str, value: &str) -> Result<()
     Ok(())
 }
 
+/// Keys whose values differ between two settings snapshots, in sorted order
+fn changed_keys(before: &HashMap<String, String>, after: &HashMap<String, String>) -> Vec<String> {
+    before
+        .keys()
+        .chain(after.keys())
+        .filter(|key| before.get(*key) != after.get(*key))
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Notify listeners that settings changed; nothing is emitted when no value differed
+fn emit_settings_changed(app: &AppHandle, keys: Vec<String>) {
+    if keys.is_empty() {
+        return;
+    }
+
+    if let Err(e) = app.emit(SETTINGS_CHANGED_EVENT, SettingsChanged { keys }) {
+        log::warn!("Failed to emit settings-changed event: {}", e);
+    }
+}
+
 /// Save a setting to the database
 #[tauri::command]
 pub async fn save_setting(
+    app: AppHandle,
     db: tauri::State<'_, DbConnection>,
     key: String,
     value: String,
 ) -> Result<(), String> {
-    save_setting_internal(&db, &key, &value)
+    let previous = get_setting_internal(&db, &key)?;
+    save_setting_internal(&db, &key, &value)?;
+
+    if previous.as_deref() != Some(value.as_str()) {
+        emit_settings_changed(&app, vec![key]);
+    }
+
+    Ok(())
 }
 
 /// Internal function to get a setting
-fn get_setting_internal(db: &DbConnection, key: &str) -> Result<Option<String>, String> {
+pub(crate) fn get_setting_internal(db: &DbConnection, key: &str) -> Result<Option<String>, String> {
     let conn = db.lock().map_err(|e| format!("Database lock error: {}", e))?;
+    get_setting_with_conn(&conn, key).map_err(|e| format!("Failed to get setting: {}", e))
+}
 
-    let result = conn
-        .query_row(
-            "SELECT value FROM settings WHERE key = ?1",
-            params![key],
-            |row| row.get(0),
-        )
-        .optional()
-        .map_err(|e| format!("Failed to get setting: {}", e))?;
-
-    Ok(result)
+/// Get a setting using a connection the caller has already locked
+pub(crate) fn get_setting_with_conn(
+    conn: &rusqlite::Connection,
+    key: &str,
+) -> rusqlite::Result<Option<String>> {
+    conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1",
+        params![key],
+        |row| row.get(0),
+    )
+    .optional()
 }
 
 /// Get a setting from the database
@@ -115,89 +293,270 @@ pub async fn get_all_settings(
     get_all_settings_internal(&db)
 }
 
-/// Internal function to load settings
-pub(crate) fn load_settings_internal(db: &DbConnection) -> Result<AppSettings, String> {
-    let settings_map = get_all_settings_internal(db)?;
-
-    let mut settings = AppSettings::default();
+/// A stored setting value that could not be parsed and was replaced by its default
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SettingWarning {
+    pub key: String,
+    pub value: String,
+    pub message: String,
+}
 
-    // Parse excluded_extensions
-    if let Some(excluded_ext) = settings_map.get("excluded_extensions") {
-        if let Ok(ext_list) = serde_json::from_str::<Vec<String>>(excluded_ext) {
-            settings.excluded_extensions = ext_list;
-        }
-    }
+/// Settings loaded from the database together with any parse warnings
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadedSettings {
+    #[serde(flatten)]
+    pub settings: AppSettings,
+    pub warnings: Vec<SettingWarning>,
+}
 
-    // Parse token_limit
-    if let Some(token_limit) = settings_map.get("token_limit") {
-        if let Ok(limit) = token_limit.parse::<i64>() {
-            settings.token_limit = limit;
-        }
+/// Internal function to get a setting parsed into a typed value
+pub(crate) fn get_setting_typed<T>(db: &DbConnection, key: &str) -> Result<Option<T>, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match get_setting_internal(db, key)? {
+        Some(value) => value
+            .parse::<T>()
+            .map(Some)
+            .map_err(|e| format!("Invalid value '{}' for setting {}: {}", value, key, e)),
+        None => Ok(None),
     }
+}
 
-    // Parse default_template
-    if let Some(template) = settings_map.get("default_template") {
-        settings.default_template = template.clone();
-    }
+/// Internal function to save a typed setting value
+pub(crate) fn set_setting_typed<T: ToString>(
+    db: &DbConnection,
+    key: &str,
+    value: &T,
+) -> Result<(), String> {
+    save_setting_internal(db, key, &value.to_string())
+}
 
-    // Parse auto_save_history
-    if let Some(auto_save) = settings_map.get("auto_save_history") {
-        if let Ok(auto_save_bool) = auto_save.parse::<bool>() {
-            settings.auto_save_history = auto_save_bool;
+/// Parse a stored setting into `target`, recording a warning instead of failing
+fn parse_setting_into<T>(
+    settings_map: &HashMap<String, String>,
+    key: &str,
+    target: &mut T,
+    warnings: &mut Vec<SettingWarning>,
+) where
+    T: FromStr,
+    T::Err: Display,
+{
+    if let Some(value) = settings_map.get(key) {
+        match value.parse::<T>() {
+            Ok(parsed) => *target = parsed,
+            Err(e) => warnings.push(SettingWarning {
+                key: key.to_string(),
+                value: value.clone(),
+                message: e.to_string(),
+            }),
         }
     }
+}
 
-    // Parse cache_size_mb
-    if let Some(cache_size) = settings_map.get("cache_size_mb") {
-        if let Ok(size) = cache_size.parse::<i64>() {
-            settings.cache_size_mb = size;
+/// Parse a stored JSON list setting into `target`, recording a warning instead of failing
+fn parse_list_setting_into(
+    settings_map: &HashMap<String, String>,
+    key: &str,
+    target: &mut Vec<String>,
+    warnings: &mut Vec<SettingWarning>,
+) {
+    if let Some(value) = settings_map.get(key) {
+        match serde_json::from_str::<Vec<String>>(value) {
+            Ok(list) => *target = list,
+            Err(e) => warnings.push(SettingWarning {
+                key: key.to_string(),
+                value: value.clone(),
+                message: e.to_string(),
+            }),
         }
     }
+}
 
-    // Parse respect_gitignore
-    if let Some(respect_gitignore) = settings_map.get("respect_gitignore") {
-        if let Ok(respect_gitignore_bool) = respect_gitignore.parse::<bool>() {
-            settings.respect_gitignore = respect_gitignore_bool;
-        }
+/// Internal function to save a list setting as JSON
+fn set_list_setting(db: &DbConnection, key: &str, list: &[String]) -> Result<(), String> {
+    let json =
+        serde_json::to_string(list).map_err(|e| format!("Failed to serialize {}: {}", key, e))?;
+    save_setting_internal(db, key, &json)
+}
+
+/// Internal function to load settings
+pub(crate) fn load_settings_internal(db: &DbConnection) -> Result<LoadedSettings, String> {
+    let settings_map = get_all_settings_internal(db)?;
+
+    let mut settings = AppSettings::default();
+    let mut warnings = Vec::new();
+
+    parse_list_setting_into(
+        &settings_map,
+        "excluded_extensions",
+        &mut settings.excluded_extensions,
+        &mut warnings,
+    );
+    parse_setting_into(
+        &settings_map,
+        "token_limit",
+        &mut settings.token_limit,
+        &mut warnings,
+    );
+    parse_setting_into(
+        &settings_map,
+        "default_template",
+        &mut settings.default_template,
+        &mut warnings,
+    );
+    parse_setting_into(
+        &settings_map,
+        "auto_save_history",
+        &mut settings.auto_save_history,
+        &mut warnings,
+    );
+    parse_setting_into(
+        &settings_map,
+        "cache_size_mb",
+        &mut settings.cache_size_mb,
+        &mut warnings,
+    );
+    parse_setting_into(
+        &settings_map,
+        "respect_gitignore",
+        &mut settings.respect_gitignore,
+        &mut warnings,
+    );
+    parse_setting_into(
+        &settings_map,
+        "use_global_gitignore",
+        &mut settings.use_global_gitignore,
+        &mut warnings,
+    );
+    parse_setting_into(
+        &settings_map,
+        "respect_dockerignore",
+        &mut settings.respect_dockerignore,
+        &mut warnings,
+    );
+    parse_setting_into(
+        &settings_map,
+        "follow_symlinks",
+        &mut settings.follow_symlinks,
+        &mut warnings,
+    );
+    parse_setting_into(
+        &settings_map,
+        "include_hidden",
+        &mut settings.include_hidden,
+        &mut warnings,
+    );
+    parse_setting_into(
+        &settings_map,
+        "max_index_depth",
+        &mut settings.max_index_depth,
+        &mut warnings,
+    );
+    parse_setting_into(
+        &settings_map,
+        "index_batch_size",
+        &mut settings.index_batch_size,
+        &mut warnings,
+    );
+    parse_setting_into(
+        &settings_map,
+        "browser_profile_dir",
+        &mut settings.browser_profile_dir,
+        &mut warnings,
+    );
+    parse_list_setting_into(
+        &settings_map,
+        "text_extensions",
+        &mut settings.text_extensions,
+        &mut warnings,
+    );
+    parse_list_setting_into(
+        &settings_map,
+        "text_file_names",
+        &mut settings.text_file_names,
+        &mut warnings,
+    );
+    parse_list_setting_into(
+        &settings_map,
+        "blocked_text_extensions",
+        &mut settings.blocked_text_extensions,
+        &mut warnings,
+    );
+
+    for warning in &warnings {
+        log::warn!(
+            "Ignoring invalid value '{}' for setting {}: {}",
+            warning.value,
+            warning.key,
+            warning.message
+        );
     }
 
-    Ok(settings)
+    Ok(LoadedSettings { settings, warnings })
 }
 
 /// Load application settings with defaults
+///
+/// Values that fail to parse fall back to their defaults and are reported in `warnings`.
 #[tauri::command]
-pub async fn load_settings(db: tauri::State<'_, DbConnection>) -> Result<AppSettings, String> {
+pub async fn load_settings(db: tauri::State<'_, DbConnection>) -> Result<LoadedSettings, String> {
     load_settings_internal(&db)
 }
 
 /// Internal function to save settings
-fn save_settings_internal(db: &DbConnection, settings: &AppSettings) -> Result<(), String> {
-    // Serialize and save each setting
-    let excluded_ext_json = serde_json::to_string(&settings.excluded_extensions)
-        .map_err(|e| format!("Failed to serialize excluded_extensions: {}", e))?;
+///
+/// Returns the keys whose stored values changed.
+fn save_settings_internal(
+    db: &DbConnection,
+    settings: &AppSettings,
+) -> Result<Vec<String>, String> {
+    let before = get_all_settings_internal(db)?;
 
-    save_setting_internal(db, "excluded_extensions", &excluded_ext_json)?;
-    save_setting_internal(db, "token_limit", &settings.token_limit.to_string())?;
+    // Serialize and save each setting
+    set_list_setting(db, "excluded_extensions", &settings.excluded_extensions)?;
+    set_setting_typed(db, "token_limit", &settings.token_limit)?;
     save_setting_internal(db, "default_template", &settings.default_template)?;
-    save_setting_internal(db, "auto_save_history", &settings.auto_save_history.to_string())?;
-    save_setting_internal(db, "cache_size_mb", &settings.cache_size_mb.to_string())?;
-    save_setting_internal(db, "respect_gitignore", &settings.respect_gitignore.to_string())?;
-
-    Ok(())
+    set_setting_typed(db, "auto_save_history", &settings.auto_save_history)?;
+    set_setting_typed(db, "cache_size_mb", &settings.cache_size_mb)?;
+    set_setting_typed(db, "respect_gitignore", &settings.respect_gitignore)?;
+    set_setting_typed(db, "use_global_gitignore", &settings.use_global_gitignore)?;
+    set_setting_typed(db, "respect_dockerignore", &settings.respect_dockerignore)?;
+    set_setting_typed(db, "follow_symlinks", &settings.follow_symlinks)?;
+    set_setting_typed(db, "include_hidden", &settings.include_hidden)?;
+    set_setting_typed(db, "max_index_depth", &settings.max_index_depth)?;
+    set_setting_typed(db, "index_batch_size", &settings.index_batch_size)?;
+    save_setting_internal(db, "browser_profile_dir", &settings.browser_profile_dir)?;
+    set_list_setting(db, "text_extensions", &settings.text_extensions)?;
+    set_list_setting(db, "text_file_names", &settings.text_file_names)?;
+    set_list_setting(
+        db,
+        "blocked_text_extensions",
+        &settings.blocked_text_extensions,
+    )?;
+
+    let after = get_all_settings_internal(db)?;
+
+    Ok(changed_keys(&before, &after))
 }
 
 /// Save application settings
 #[tauri::command]
 pub async fn save_settings(
+    app: AppHandle,
     db: tauri::State<'_, DbConnection>,
     settings: AppSettings,
 ) -> Result<(), String> {
-    save_settings_internal(&db, &settings)
+    let changed = save_settings_internal(&db, &settings)?;
+    emit_settings_changed(&app, changed);
+
+    Ok(())
 }
 
 /// Internal function to export settings
 fn export_settings_internal(db: &DbConnection) -> Result<String, String> {
-    let settings = load_settings_internal(db)?;
+    let settings = load_settings_internal(db)?.settings;
     serde_json::to_string_pretty(&settings)
         .map_err(|e| format!("Failed to export settings: {}", e))
 }
@@ -209,22 +568,30 @@ pub async fn export_settings(db: tauri::State<'_, DbConnection>) -> Result<Strin
 }
 
 /// Internal function to import settings
-fn import_settings_internal(db: &DbConnection, json_data: &str) -> Result<(), String> {
+///
+/// Returns the keys whose stored values changed.
+fn import_settings_internal(db: &DbConnection, json_data: &str) -> Result<Vec<String>, String> {
     let settings: AppSettings =
         serde_json::from_str(json_data).map_err(|e| format!("Failed to parse settings: {}", e))?;
 
-    save_settings_internal(db, &settings)?;
+    settings
+        .validate()
+        .map_err(|errors| format!("Invalid settings: {}", errors.join("; ")))?;
 
-    Ok(())
+    save_settings_internal(db, &settings)
 }
 
 /// Import settings from JSON
 #[tauri::command]
 pub async fn import_settings(
+    app: AppHandle,
     db: tauri::State<'_, DbConnection>,
     json_data: String,
 ) -> Result<(), String> {
-    import_settings_internal(&db, &json_data)
+    let changed = import_settings_internal(&db, &json_data)?;
+    emit_settings_changed(&app, changed);
+
+    Ok(())
 }
 
 /// Internal function to delete a setting
@@ -259,10 +626,45 @@ pub async fn reset_settings(db: tauri::State<'_, DbConnection>) -> Result<(), St
     reset_settings_internal(&db)
 }
 
+/// A root's project config together with the settings that apply to it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectSettings {
+    /// Contents of the root's `.ai-context.toml`; `None` when it has none
+    pub config: Option<ProjectConfig>,
+    /// The saved settings with the project config applied on top
+    pub effective: AppSettings,
+}
+
+/// Internal function to load the project config at `root` and merge it into the settings
+fn get_project_config_internal(
+    db: &DbConnection,
+    root: &std::path::Path,
+) -> Result<ProjectSettings, String> {
+    let config = ProjectConfig::load(root).map_err(|e| e.to_string())?;
+    let settings = load_settings_internal(db)?.settings;
+    let effective = match &config {
+        Some(config) => config.apply(settings),
+        None => settings,
+    };
+    Ok(ProjectSettings { config, effective })
+}
+
+/// Get the `.ai-context.toml` of an indexed root and the settings in effect for it
+///
+/// Values from the project config win over saved settings, which win over the defaults.
+#[tauri::command]
+pub async fn get_project_config(
+    root: String,
+    db: tauri::State<'_, DbConnection>,
+) -> Result<ProjectSettings, String> {
+    get_project_config_internal(&db, std::path::Path::new(&root))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::db::schema::init_database;
+    use crate::project_config::PROJECT_CONFIG_FILE;
     use rusqlite::Connection;
     use std::sync::{Arc, Mutex};
 
@@ -306,17 +708,40 @@ mod tests {
             auto_save_history: false,
             cache_size_mb: 50,
             respect_gitignore: false,
+            use_global_gitignore: true,
+            respect_dockerignore: true,
+            follow_symlinks: true,
+            include_hidden: false,
+            max_index_depth: 3,
+            index_batch_size: 5000,
+            browser_profile_dir: "/tmp/profiles".to_string(),
+            text_extensions: vec![".conf2".to_string()],
+            text_file_names: vec![".env.local".to_string()],
+            blocked_text_extensions: vec!["log".to_string()],
         };
 
         save_settings_internal(&db, &settings).unwrap();
 
-        let loaded = load_settings_internal(&db).unwrap();
+        let loaded = load_settings_internal(&db).unwrap().settings;
         assert_eq!(loaded.excluded_extensions, settings.excluded_extensions);
         assert_eq!(loaded.token_limit, settings.token_limit);
         assert_eq!(loaded.default_template, settings.default_template);
         assert_eq!(loaded.auto_save_history, settings.auto_save_history);
         assert_eq!(loaded.cache_size_mb, settings.cache_size_mb);
         assert_eq!(loaded.respect_gitignore, settings.respect_gitignore);
+        assert_eq!(loaded.use_global_gitignore, settings.use_global_gitignore);
+        assert_eq!(loaded.respect_dockerignore, settings.respect_dockerignore);
+        assert_eq!(loaded.follow_symlinks, settings.follow_symlinks);
+        assert_eq!(loaded.include_hidden, settings.include_hidden);
+        assert_eq!(loaded.max_index_depth, settings.max_index_depth);
+        assert_eq!(loaded.index_batch_size, settings.index_batch_size);
+        assert_eq!(loaded.browser_profile_dir, settings.browser_profile_dir);
+        assert_eq!(loaded.text_extensions, settings.text_extensions);
+        assert_eq!(loaded.text_file_names, settings.text_file_names);
+        assert_eq!(
+            loaded.blocked_text_extensions,
+            settings.blocked_text_extensions
+        );
     }
 
     #[test]
@@ -326,10 +751,20 @@ mod tests {
         let settings = AppSettings {
             excluded_extensions: vec![".test".to_string()],
             token_limit: 100000,
-            default_template: "test".to_string(),
+            default_template: "review".to_string(),
             auto_save_history: false,
             cache_size_mb: 50,
             respect_gitignore: false,
+            use_global_gitignore: true,
+            respect_dockerignore: true,
+            follow_symlinks: true,
+            include_hidden: false,
+            max_index_depth: 3,
+            index_batch_size: 5000,
+            browser_profile_dir: String::new(),
+            text_extensions: Vec::new(),
+            text_file_names: Vec::new(),
+            blocked_text_extensions: Vec::new(),
         };
 
         save_settings_internal(&db, &settings).unwrap();
@@ -344,7 +779,7 @@ mod tests {
         // Import settings
         import_settings_internal(&db, &exported).unwrap();
 
-        let loaded = load_settings_internal(&db).unwrap();
+        let loaded = load_settings_internal(&db).unwrap().settings;
         assert_eq!(loaded.excluded_extensions, vec![".test".to_string()]);
         assert_eq!(loaded.token_limit, 100000);
         assert_eq!(loaded.respect_gitignore, false);
@@ -379,7 +814,7 @@ mod tests {
     fn test_default_settings() {
         let db = setup_test_db();
 
-        let settings = load_settings_internal(&db).unwrap();
+        let settings = load_settings_internal(&db).unwrap().settings;
 
         // Should return default settings when database is empty
         assert_eq!(settings.token_limit, 200000);
@@ -388,6 +823,15 @@ mod tests {
         assert_eq!(settings.cache_size_mb, 100);
         assert!(settings.excluded_extensions.contains(&".exe".to_string()));
         assert!(settings.respect_gitignore);
+        assert!(!settings.use_global_gitignore);
+        assert!(!settings.follow_symlinks);
+        assert!(settings.include_hidden);
+        assert_eq!(settings.max_index_depth, 0);
+        assert_eq!(settings.index_batch_size, 1000);
+        assert!(settings.browser_profile_dir.is_empty());
+        assert!(settings.text_extensions.is_empty());
+        assert!(settings.text_file_names.is_empty());
+        assert!(settings.blocked_text_extensions.is_empty());
     }
 
     #[test]
@@ -396,12 +840,181 @@ mod tests {
 
         // Test saving respect_gitignore as true
         save_setting_internal(&db, "respect_gitignore", "true").unwrap();
-        let settings = load_settings_internal(&db).unwrap();
+        let settings = load_settings_internal(&db).unwrap().settings;
         assert!(settings.respect_gitignore);
 
         // Test saving respect_gitignore as false
         save_setting_internal(&db, "respect_gitignore", "false").unwrap();
-        let settings = load_settings_internal(&db).unwrap();
+        let settings = load_settings_internal(&db).unwrap().settings;
         assert!(!settings.respect_gitignore);
     }
+
+    #[test]
+    fn test_save_settings_reports_changed_keys() {
+        let db = setup_test_db();
+
+        // First save writes every key
+        let changed = save_settings_internal(&db, &AppSettings::default()).unwrap();
+        assert!(changed.contains(&"token_limit".to_string()));
+
+        // Saving identical settings changes nothing
+        let changed = save_settings_internal(&db, &AppSettings::default()).unwrap();
+        assert!(changed.is_empty());
+
+        let settings = AppSettings {
+            cache_size_mb: 250,
+            respect_gitignore: false,
+            ..AppSettings::default()
+        };
+        let changed = save_settings_internal(&db, &settings).unwrap();
+        assert_eq!(
+            changed,
+            vec!["cache_size_mb".to_string(), "respect_gitignore".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_changed_keys_includes_added_and_removed() {
+        let before = HashMap::from([
+            ("a".to_string(), "1".to_string()),
+            ("b".to_string(), "2".to_string()),
+        ]);
+        let after = HashMap::from([
+            ("b".to_string(), "3".to_string()),
+            ("c".to_string(), "4".to_string()),
+        ]);
+
+        assert_eq!(changed_keys(&before, &after), vec!["a", "b", "c"]);
+        assert!(changed_keys(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn test_cache_size_bytes() {
+        let settings = AppSettings {
+            cache_size_mb: 50,
+            ..AppSettings::default()
+        };
+        assert_eq!(settings.cache_size_bytes(), 50 * 1024 * 1024);
+
+        let settings = AppSettings {
+            cache_size_mb: 0,
+            ..AppSettings::default()
+        };
+        assert_eq!(settings.cache_size_bytes(), 1024 * 1024);
+    }
+
+    #[test]
+    fn test_validate_settings() {
+        assert!(AppSettings::default().validate().is_ok());
+
+        let settings = AppSettings {
+            token_limit: -1,
+            cache_size_mb: -5,
+            default_template: "missing".to_string(),
+            ..AppSettings::default()
+        };
+        let errors = settings.validate().unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].contains("token_limit"));
+        assert!(errors[1].contains("cache_size_mb"));
+        assert!(errors[2].contains("missing"));
+    }
+
+    #[test]
+    fn test_import_invalid_settings_is_rejected() {
+        let db = setup_test_db();
+        save_settings_internal(&db, &AppSettings::default()).unwrap();
+        let before = get_all_settings_internal(&db).unwrap();
+
+        let mut json: serde_json::Value = serde_json::to_value(AppSettings::default()).unwrap();
+        json["token_limit"] = serde_json::json!(-100);
+
+        let err = import_settings_internal(&db, &json.to_string()).unwrap_err();
+        assert!(err.contains("token_limit must be between"));
+
+        // Stored settings are left untouched
+        assert_eq!(get_all_settings_internal(&db).unwrap(), before);
+    }
+
+    #[test]
+    fn test_typed_setting_roundtrip() {
+        let db = setup_test_db();
+
+        set_setting_typed(&db, "token_limit", &150000i64).unwrap();
+        assert_eq!(
+            get_setting_typed::<i64>(&db, "token_limit").unwrap(),
+            Some(150000)
+        );
+
+        set_setting_typed(&db, "auto_save_history", &false).unwrap();
+        assert_eq!(
+            get_setting_typed::<bool>(&db, "auto_save_history").unwrap(),
+            Some(false)
+        );
+
+        assert_eq!(get_setting_typed::<i64>(&db, "missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_typed_setting_parse_error() {
+        let db = setup_test_db();
+
+        save_setting_internal(&db, "token_limit", "lots").unwrap();
+        let err = get_setting_typed::<i64>(&db, "token_limit").unwrap_err();
+        assert!(err.contains("token_limit"));
+    }
+
+    #[test]
+    fn test_malformed_setting_produces_warning() {
+        let db = setup_test_db();
+
+        save_setting_internal(&db, "token_limit", "not-a-number").unwrap();
+        save_setting_internal(&db, "cache_size_mb", "250").unwrap();
+
+        let loaded = load_settings_internal(&db).unwrap();
+
+        // The malformed value falls back to the default but is reported
+        assert_eq!(loaded.settings.token_limit, 200000);
+        assert_eq!(loaded.settings.cache_size_mb, 250);
+        assert_eq!(loaded.warnings.len(), 1);
+        assert_eq!(loaded.warnings[0].key, "token_limit");
+        assert_eq!(loaded.warnings[0].value, "not-a-number");
+    }
+
+    #[test]
+    fn test_valid_settings_produce_no_warnings() {
+        let db = setup_test_db();
+
+        save_settings_internal(&db, &AppSettings::default()).unwrap();
+
+        let loaded = load_settings_internal(&db).unwrap();
+        assert!(loaded.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_project_config_overrides_saved_settings() {
+        let db = setup_test_db();
+        let temp_dir = tempfile::TempDir::new().unwrap();
+
+        save_setting_internal(&db, "default_template", "planner").unwrap();
+        save_setting_internal(&db, "follow_symlinks", "true").unwrap();
+        let settings = get_project_config_internal(&db, temp_dir.path()).unwrap();
+        assert_eq!(settings.config, None);
+        assert_eq!(settings.effective.default_template, "planner");
+
+        std::fs::write(
+            temp_dir.path().join(PROJECT_CONFIG_FILE),
+            "default_template = \"agent\"\nmax_index_depth = 3\n",
+        )
+        .unwrap();
+        let settings = get_project_config_internal(&db, temp_dir.path()).unwrap();
+        // Project config > saved settings > defaults
+        assert_eq!(settings.effective.default_template, "agent");
+        assert_eq!(settings.effective.max_index_depth, 3);
+        assert!(settings.effective.follow_symlinks);
+        assert_eq!(
+            settings.effective.token_limit,
+            AppSettings::default().token_limit
+        );
+    }
 }
@@ -1,11 +1,15 @@
 pub mod schema;
 
 use rusqlite::{Connection, Result};
+use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Manager};
 
 pub type DbConnection = Arc<Mutex<Connection>>;
 
+/// Database file name inside the app data directory
+pub const DB_FILE_NAME: &str = "ai-context-collector.db";
+
 /// Initialize the database connection and create tables
 pub fn init_db(app: &AppHandle) -> Result<DbConnection> {
     let app_dir = app
@@ -15,7 +19,7 @@ pub fn init_db(app: &AppHandle) -> Result<DbConnection> {
 
     std::fs::create_dir_all(&app_dir).expect("Failed to create app data directory");
 
-    let db_path = app_dir.join("ai-context-collector.db");
+    let db_path = app_dir.join(DB_FILE_NAME);
     log::info!("Database path: {:?}", db_path);
 
     let conn = Connection::open(db_path)?;
@@ -26,6 +30,47 @@ pub fn init_db(app: &AppHandle) -> Result<DbConnection> {
     Ok(Arc::new(Mutex::new(conn)))
 }
 
+/// Outcome of a WAL checkpoint, as reported by `PRAGMA wal_checkpoint`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CheckpointResult {
+    /// The checkpoint could not complete because another connection was busy
+    pub busy: bool,
+    /// Frames in the WAL file; -1 when the database is not in WAL mode
+    pub log_frames: i64,
+    /// Frames copied back into the database; -1 when the database is not in WAL mode
+    pub checkpointed_frames: i64,
+}
+
+/// Copy the WAL back into the database file and truncate it
+///
+/// Harmless when the database doesn't use WAL; SQLite then reports -1 frames.
+pub fn checkpoint(conn: &Connection) -> Result<CheckpointResult> {
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
+        Ok(CheckpointResult {
+            busy: row.get::<_, i64>(0)? != 0,
+            log_frames: row.get(1)?,
+            checkpointed_frames: row.get(2)?,
+        })
+    })
+}
+
+/// Checkpoint the shared connection before the app exits
+///
+/// A poisoned mutex only means a command panicked while holding it; the connection
+/// itself is still usable, so the checkpoint goes ahead.
+pub fn checkpoint_on_shutdown(db: &DbConnection) {
+    let conn = db.lock().unwrap_or_else(|e| {
+        log::warn!("Database mutex poisoned at shutdown, checkpointing anyway");
+        e.into_inner()
+    });
+
+    match checkpoint(&conn) {
+        Ok(result) if result.busy => log::warn!("WAL checkpoint at shutdown was blocked"),
+        Ok(_) => log::info!("WAL checkpoint completed at shutdown"),
+        Err(e) => log::warn!("WAL checkpoint at shutdown failed: {}", e),
+    }
+}
+
 /// Get a database connection from the state
 pub fn get_connection(db: &DbConnection) -> Result<std::sync::MutexGuard<Connection>> {
     db.lock().map_err(|e| {
@@ -46,6 +91,59 @@ mod tests {
         Arc::new(Mutex::new(conn))
     }
 
+    #[test]
+    fn test_checkpoint_truncates_wal() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let conn = Connection::open(&db_path).unwrap();
+        let mode: String = conn
+            .query_row("PRAGMA journal_mode=WAL", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "wal");
+        schema::init_database(&conn).unwrap();
+        for i in 0..100 {
+            conn.execute(
+                "INSERT INTO files (path, name, is_dir) VALUES (?, ?, 0)",
+                rusqlite::params![format!("/p/f{}.txt", i), format!("f{}.txt", i)],
+            )
+            .unwrap();
+        }
+
+        let wal_path = temp_dir.path().join("test.db-wal");
+        assert!(std::fs::metadata(&wal_path).unwrap().len() > 0);
+
+        let result = checkpoint(&conn).unwrap();
+        assert!(!result.busy);
+        assert_eq!(result.log_frames, result.checkpointed_frames);
+        assert_eq!(std::fs::metadata(&wal_path).unwrap().len(), 0);
+
+        // Data is intact and the poisoned-mutex path still checkpoints
+        let db: DbConnection = Arc::new(Mutex::new(conn));
+        let poisoner = Arc::clone(&db);
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("poison the mutex");
+        })
+        .join();
+        assert!(db.is_poisoned());
+        checkpoint_on_shutdown(&db);
+
+        let conn = db.lock().unwrap_or_else(|e| e.into_inner());
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 100);
+    }
+
+    #[test]
+    fn test_checkpoint_without_wal() {
+        let db = create_test_db();
+        let conn = get_connection(&db).unwrap();
+        let result = checkpoint(&conn).unwrap();
+        assert!(!result.busy);
+        assert_eq!(result.log_frames, -1);
+    }
+
     #[test]
     fn test_get_connection_success() {
         let db = create_test_db();
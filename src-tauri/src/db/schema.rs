@@ -1,5 +1,11 @@
 use rusqlite::{Connection, Result};
 
+/// Schema version `init_database` records in `PRAGMA user_version`
+///
+/// Bump it whenever `init_database` gains a migration, so diagnostics can tell an old
+/// database that was never migrated from a current one.
+pub const SCHEMA_VERSION: i64 = 1;
+
 /// Initialize the database schema
 pub fn init_database(conn: &Connection) -> Result<()> {
     // Core file index table - using path as PRIMARY KEY
@@ -15,11 +21,23 @@ pub fn init_database(conn: &Connection) -> Result<()> {
             mtime INTEGER,
             is_dir INTEGER DEFAULT 0,
             token_count INTEGER,
-            fingerprint TEXT
+            fingerprint TEXT,
+            symlink_target TEXT,
+            truncated_at_depth INTEGER DEFAULT 0,
+            language TEXT,
+            lossy_path INTEGER DEFAULT 0,
+            is_root INTEGER DEFAULT 0
         )",
         [],
     )?;
 
+    // Columns added after the initial schema; databases created by older versions lack them
+    add_column_if_missing(conn, "files", "symlink_target", "TEXT")?;
+    add_column_if_missing(conn, "files", "truncated_at_depth", "INTEGER DEFAULT 0")?;
+    add_column_if_missing(conn, "files", "language", "TEXT")?;
+    add_column_if_missing(conn, "files", "lossy_path", "INTEGER DEFAULT 0")?;
+    add_column_if_missing(conn, "files", "is_root", "INTEGER DEFAULT 0")?;
+
     // Create indices for efficient queries
     conn.execute(
         "CREATE INDEX IF NOT EXISTS idx_parent_path ON files(parent_path)",
@@ -44,6 +62,15 @@ pub fn init_database(conn: &Connection) -> Result<()> {
         [],
     )?;
 
+    // Bookmarked index paths; rows whose path leaves the index are ignored when listing
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS bookmarks (
+            path TEXT PRIMARY KEY,
+            added_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
     // Settings persistence table
     conn.execute(
         "CREATE TABLE IF NOT EXISTS settings (
@@ -53,6 +80,26 @@ pub fn init_database(conn: &Connection) -> Result<()> {
         [],
     )?;
 
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+    Ok(())
+}
+
+/// Add a column to an existing table unless it is already present
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        [table, column],
+        |row| row.get::<_, i64>(0).map(|count| count > 0),
+    )?;
+
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl),
+            [],
+        )?;
+    }
+
     Ok(())
 }
 
@@ -74,7 +121,7 @@ mod tests {
                 |row| row.get(0),
             )
             .unwrap();
-        assert_eq!(table_count, 3); // files, history, settings
+        assert_eq!(table_count, 4); // files, history, bookmarks, settings
 
         // Verify path is primary key
         let pk_info: String = conn
@@ -85,5 +132,43 @@ mod tests {
             )
             .unwrap();
         assert_eq!(pk_info, "path");
+
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_init_database_migrates_old_files_table() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute(
+            "CREATE TABLE files (
+                path TEXT PRIMARY KEY,
+                parent_path TEXT,
+                name TEXT NOT NULL,
+                size INTEGER,
+                mtime INTEGER,
+                is_dir INTEGER DEFAULT 0,
+                token_count INTEGER,
+                fingerprint TEXT
+            )",
+            [],
+        )
+        .unwrap();
+
+        init_database(&conn).unwrap();
+        // Running again must not try to add the column twice
+        init_database(&conn).unwrap();
+
+        let new_columns: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('files')
+                 WHERE name IN ('symlink_target', 'truncated_at_depth', 'language', 'lossy_path', 'is_root')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(new_columns, 5);
     }
 }
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -33,8 +35,85 @@ impl From<AppError> for String {
     }
 }
 
+impl AppError {
+    /// Stable, machine-readable code for this error kind
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Database(_) => CommandError::DB,
+            AppError::Io(_) | AppError::Walkdir(_) => CommandError::IO,
+            AppError::Serialization(_) => CommandError::SERIALIZATION,
+            AppError::Path(_) => CommandError::PATH,
+            AppError::InvalidArgument(_) => CommandError::INVALID_ARGUMENT,
+            AppError::BrowserError(_) => CommandError::BROWSER,
+            AppError::Unknown(_) => CommandError::UNKNOWN,
+        }
+    }
+}
+
 pub type AppResult<T> = Result<T, AppError>;
 
+/// Error returned from commands to the frontend.
+///
+/// Serialized as `{ code, message }`: `code` is stable and meant for branching in the UI,
+/// `message` is human-readable and may change between versions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandError {
+    pub code: String,
+    pub message: String,
+}
+
+impl CommandError {
+    pub const DB: &'static str = "DB";
+    pub const IO: &'static str = "IO";
+    pub const SERIALIZATION: &'static str = "SERIALIZATION";
+    pub const PATH: &'static str = "PATH";
+    pub const INVALID_ARGUMENT: &'static str = "INVALID_ARGUMENT";
+    pub const BROWSER: &'static str = "BROWSER";
+    pub const NOT_FOUND: &'static str = "NOT_FOUND";
+    pub const LOCK: &'static str = "LOCK";
+    pub const BUSY: &'static str = "BUSY";
+    pub const UNKNOWN: &'static str = "UNKNOWN";
+
+    pub fn new(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: code.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// Error for a poisoned or otherwise unavailable lock on shared state
+    pub fn lock(what: &str, error: impl fmt::Display) -> Self {
+        Self::new(Self::LOCK, format!("Failed to lock {}: {}", what, error))
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.code, self.message)
+    }
+}
+
+impl From<AppError> for CommandError {
+    fn from(error: AppError) -> Self {
+        Self::new(error.code(), error.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for CommandError {
+    fn from(error: rusqlite::Error) -> Self {
+        AppError::from(error).into()
+    }
+}
+
+/// Untyped errors from helpers that still return `String`
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self::new(Self::UNKNOWN, message)
+    }
+}
+
+pub type CommandResult<T> = Result<T, CommandError>;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -96,6 +175,42 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("bad input"));
     }
 
+    #[test]
+    fn test_command_error_codes() {
+        let io_error = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+        let command_error: CommandError = AppError::from(io_error).into();
+        assert_eq!(command_error.code, "IO");
+        assert!(command_error.message.contains("missing"));
+
+        let cases = [
+            (AppError::Path("p".to_string()), "PATH"),
+            (
+                AppError::InvalidArgument("a".to_string()),
+                "INVALID_ARGUMENT",
+            ),
+            (AppError::BrowserError("b".to_string()), "BROWSER"),
+            (AppError::Unknown("u".to_string()), "UNKNOWN"),
+            (
+                AppError::Database(rusqlite::Error::QueryReturnedNoRows),
+                "DB",
+            ),
+        ];
+        for (error, code) in cases {
+            let message = error.to_string();
+            let command_error = CommandError::from(error);
+            assert_eq!(command_error.code, code);
+            assert_eq!(command_error.message, message);
+        }
+    }
+
+    #[test]
+    fn test_command_error_serialization() {
+        let error = CommandError::new(CommandError::NOT_FOUND, "File not found in index");
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["code"], "NOT_FOUND");
+        assert_eq!(json["message"], "File not found in index");
+    }
+
     #[test]
     fn test_error_debug_impl() {
         let error = AppError::Unknown("debug test".to_string());
@@ -3,14 +3,30 @@
 //! This module provides functionality to read .gitignore files and filter
 //! files/directories based on the patterns defined in them.
 
-use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::gitignore::{gitconfig_excludes_path, Gitignore, GitignoreBuilder, Glob};
 use std::collections::HashMap;
+use std::fs;
 use std::path::{Path, PathBuf};
 
+/// The rule responsible for a path being ignored
+#[derive(Debug, Clone, PartialEq)]
+pub struct IgnoreMatch {
+    /// File the pattern was read from (a .gitignore or an exclude file)
+    pub source: Option<PathBuf>,
+    /// The pattern as written in the file
+    pub pattern: String,
+    /// Path the pattern matched: the path itself, or an ignored ancestor directory
+    pub matched_path: PathBuf,
+}
+
 /// Manages .gitignore patterns for directory traversal
 pub struct GitignoreManager {
     /// Cached gitignore matchers per directory
     gitignores: HashMap<PathBuf, Gitignore>,
+    /// Root-anchored exclude files (global excludesfile, .git/info/exclude), lowest precedence first
+    excludes: Vec<Gitignore>,
+    /// `<root>/.dockerignore`, checked independently of the git rules
+    dockerignore: Option<Gitignore>,
     /// Root directory being indexed
     root: PathBuf,
 }
@@ -20,6 +36,8 @@ impl GitignoreManager {
     pub fn new(root: &Path) -> Self {
         Self {
             gitignores: HashMap::new(),
+            excludes: Vec::new(),
+            dockerignore: None,
             root: root.to_path_buf(),
         }
     }
@@ -53,16 +71,89 @@ impl GitignoreManager {
         }
     }
 
+    /// Load an exclude file whose patterns are relative to the root directory.
+    ///
+    /// Exclude files apply below every .gitignore; files loaded later take precedence
+    /// over ones loaded earlier.
+    pub fn add_exclude_file(&mut self, exclude_path: &Path) -> Result<(), String> {
+        let mut builder = GitignoreBuilder::new(&self.root);
+        if let Some(err) = builder.add(exclude_path) {
+            log::warn!("Error parsing exclude file at {:?}: {}", exclude_path, err);
+        }
+
+        match builder.build() {
+            Ok(gitignore) => {
+                self.excludes.push(gitignore);
+                log::debug!("Loaded exclude file from {:?}", exclude_path);
+                Ok(())
+            }
+            Err(e) => {
+                log::warn!(
+                    "Failed to build exclude file from {:?}: {}",
+                    exclude_path,
+                    e
+                );
+                Err(format!("Failed to build exclude file: {}", e))
+            }
+        }
+    }
+
+    /// Load the user's global excludesfile as resolved from git config, if one exists.
+    ///
+    /// Must be called before `discover_gitignores` so the repository's own
+    /// `.git/info/exclude` keeps precedence over it.
+    pub fn load_global_gitignore(&mut self) -> bool {
+        match gitconfig_excludes_path() {
+            Some(path) if path.is_file() => self.add_exclude_file(&path).is_ok(),
+            _ => false,
+        }
+    }
+
+    /// Load `<root>/.dockerignore` if present.
+    ///
+    /// Docker only reads the file at the build context root and anchors every pattern there,
+    /// so `target` excludes `<root>/target` but not `<root>/crates/a/target`. Patterns are
+    /// rewritten to root-anchored gitignore globs. A path excluded by either the git rules or
+    /// the .dockerignore is ignored; `!` exceptions in one don't re-include paths the other
+    /// excludes.
+    pub fn load_dockerignore(&mut self) -> bool {
+        let dockerignore_path = self.root.join(".dockerignore");
+        let contents = match fs::read_to_string(&dockerignore_path) {
+            Ok(contents) => contents,
+            Err(_) => return false,
+        };
+
+        let mut builder = GitignoreBuilder::new(&self.root);
+        for line in contents.lines().filter_map(dockerignore_to_gitignore) {
+            if let Err(e) = builder.add_line(Some(dockerignore_path.clone()), &line) {
+                log::warn!("Invalid pattern in {:?}: {}", dockerignore_path, e);
+            }
+        }
+
+        match builder.build() {
+            Ok(dockerignore) => {
+                self.dockerignore = Some(dockerignore);
+                log::debug!("Loaded .dockerignore from {:?}", dockerignore_path);
+                true
+            }
+            Err(e) => {
+                log::warn!(
+                    "Failed to build .dockerignore from {:?}: {}",
+                    dockerignore_path,
+                    e
+                );
+                false
+            }
+        }
+    }
+
     /// Check if a path should be ignored based on loaded .gitignore patterns
     /// Returns true if the path should be ignored
+    ///
+    /// Looks up whether the path is a directory on disk; use `is_ignored_with_type`
+    /// when that is already known.
     pub fn is_ignored(&self, path: &Path) -> bool {
-        // Never ignore .gitignore files themselves
-        if path.file_name().map(|n| n == ".gitignore").unwrap_or(false) {
-            return false;
-        }
-
-        let is_dir = path.is_dir();
-        self.check_ignored(path, is_dir)
+        self.is_ignored_with_type(path, path.is_dir())
     }
 
     /// Check if a path should be ignored, given explicit is_dir information
@@ -73,17 +164,40 @@ impl GitignoreManager {
             return false;
         }
 
+        self.check_ignored(path, is_dir).is_some()
+    }
+
+    /// Explain why a path is ignored, or `None` if it isn't
+    ///
+    /// Uses the same rules as `is_ignored_with_type`, so it only knows about the
+    /// .gitignore files loaded so far.
+    pub fn explain_ignored(&self, path: &Path, is_dir: bool) -> Option<IgnoreMatch> {
+        if path.file_name().map(|n| n == ".gitignore").unwrap_or(false) {
+            return None;
+        }
+
         self.check_ignored(path, is_dir)
+            .map(|(matched_path, glob)| IgnoreMatch {
+                source: glob.from().map(Path::to_path_buf),
+                pattern: glob.original().to_string(),
+                matched_path: matched_path.to_path_buf(),
+            })
     }
 
-    /// Core ignore check used by both is_ignored and is_ignored_with_type.
+    /// Core ignore check used by is_ignored, is_ignored_with_type and explain_ignored.
+    ///
+    /// Returns the ignored path (the path itself or an ancestor directory) together with
+    /// the glob that ignored it, or `None` when the path is not ignored.
     ///
     /// Algorithm:
     /// 1. Collect ancestor directories from root down to path's parent (top-down order)
     /// 2. Check if any intermediate directory is itself ignored (directory inheritance:
     ///    if node_modules/ is ignored, everything inside is too)
     /// 3. Check the path itself against all gitignores (root to nearest, so nearest wins)
-    fn check_ignored(&self, path: &Path, is_dir: bool) -> bool {
+    ///
+    /// Exclude files are applied before any .gitignore, giving them the lowest precedence.
+    /// The .dockerignore is consulted only when the git rules leave the path included.
+    fn check_ignored<'a>(&'a self, path: &'a Path, is_dir: bool) -> Option<(&'a Path, &'a Glob)> {
         // Collect directories from path's parent up to root
         let mut dirs_to_root: Vec<&Path> = Vec::new();
         let mut current = path.parent();
@@ -106,45 +220,95 @@ impl GitignoreManager {
         // If a parent directory is ignored, all its children are implicitly ignored.
         for i in 1..dirs_to_root.len() {
             let dir = dirs_to_root[i];
-            let mut dir_ignored = false;
+            let mut dir_ignored = Self::apply_matches(&self.excludes, dir, true, None);
             // Check this directory against gitignores from root to its parent
             for j in 0..i {
                 if let Some(gitignore) = self.gitignores.get(dirs_to_root[j]) {
-                    match gitignore.matched(dir, true) {
-                        ignore::Match::None => {}
-                        ignore::Match::Ignore(_) => dir_ignored = true,
-                        ignore::Match::Whitelist(_) => dir_ignored = false,
-                    }
+                    dir_ignored = Self::apply_match(gitignore, dir, true, dir_ignored);
                 }
             }
-            if dir_ignored {
-                return true;
+            if let Some(glob) = dir_ignored {
+                return Some((dir, glob));
             }
         }
 
         // Check the path itself against all gitignores from root to closest parent.
         // Processing root first ensures nearest gitignore wins (applied last).
-        let mut ignored = false;
+        let mut ignored = Self::apply_matches(&self.excludes, path, is_dir, None);
         for &dir in &dirs_to_root {
             if let Some(gitignore) = self.gitignores.get(dir) {
-                match gitignore.matched(path, is_dir) {
-                    ignore::Match::None => {}
-                    ignore::Match::Ignore(_) => ignored = true,
-                    ignore::Match::Whitelist(_) => ignored = false,
-                }
+                ignored = Self::apply_match(gitignore, path, is_dir, ignored);
             }
         }
 
-        ignored
+        if let Some(glob) = ignored {
+            return Some((path, glob));
+        }
+
+        let dockerignore = self.dockerignore.as_ref()?;
+        let ancestors = dirs_to_root.get(1..).unwrap_or_default();
+        Self::check_dockerignore(dockerignore, path, is_dir, ancestors)
+    }
+
+    /// Check a path against the .dockerignore, Docker style: the deepest of the path and its
+    /// ancestors that any pattern matches decides, so `!docs/README.md` re-includes a file
+    /// inside an excluded `docs`.
+    fn check_dockerignore<'a>(
+        dockerignore: &'a Gitignore,
+        path: &'a Path,
+        is_dir: bool,
+        ancestors: &[&'a Path],
+    ) -> Option<(&'a Path, &'a Glob)> {
+        let candidates =
+            std::iter::once((path, is_dir)).chain(ancestors.iter().rev().map(|&dir| (dir, true)));
+        for (candidate, candidate_is_dir) in candidates {
+            match dockerignore.matched(candidate, candidate_is_dir) {
+                ignore::Match::None => continue,
+                ignore::Match::Ignore(glob) => return Some((candidate, glob)),
+                ignore::Match::Whitelist(_) => return None,
+            }
+        }
+        None
+    }
+
+    /// Apply matchers in order, returning the glob that leaves the path ignored, if any
+    fn apply_matches<'a>(
+        matchers: &'a [Gitignore],
+        path: &Path,
+        is_dir: bool,
+        initial: Option<&'a Glob>,
+    ) -> Option<&'a Glob> {
+        matchers.iter().fold(initial, |ignored_by, matcher| {
+            Self::apply_match(matcher, path, is_dir, ignored_by)
+        })
+    }
+
+    /// Apply one matcher on top of the current state; a whitelist match clears it
+    fn apply_match<'a>(
+        matcher: &'a Gitignore,
+        path: &Path,
+        is_dir: bool,
+        ignored_by: Option<&'a Glob>,
+    ) -> Option<&'a Glob> {
+        match matcher.matched(path, is_dir) {
+            ignore::Match::None => ignored_by,
+            ignore::Match::Ignore(glob) => Some(glob),
+            ignore::Match::Whitelist(_) => None,
+        }
     }
 
     /// Discover and load all .gitignore files in the directory tree
     /// This should be called during the first pass of directory traversal
+    ///
+    /// Also loads `<root>/.git/info/exclude` when present. The returned count only
+    /// includes .gitignore files.
     pub fn discover_gitignores(&mut self, root: &Path) -> Result<usize, String> {
         use walkdir::WalkDir;
 
         let mut count = 0;
 
+        self.load_info_exclude(root);
+
         for entry in WalkDir::new(root)
             .follow_links(false)
             .into_iter()
@@ -162,9 +326,30 @@ impl GitignoreManager {
         Ok(count)
     }
 
+    /// Load `<root>/.git/info/exclude` if present
+    pub fn load_info_exclude(&mut self, root: &Path) -> bool {
+        let info_exclude = root.join(".git").join("info").join("exclude");
+        info_exclude.is_file() && self.add_exclude_file(&info_exclude).is_ok()
+    }
+
+    /// Load the .gitignore directly inside `dir`, if present and not already loaded.
+    ///
+    /// Used to discover patterns lazily while walking: call it for each directory
+    /// before any of its children are checked.
+    pub fn load_dir_gitignore(&mut self, dir: &Path) -> bool {
+        if self.gitignores.contains_key(dir) {
+            return false;
+        }
+
+        let gitignore_path = dir.join(".gitignore");
+        gitignore_path.is_file() && self.add_patterns_from_file(&gitignore_path).is_ok()
+    }
+
     /// Clear all loaded patterns
     pub fn clear(&mut self) {
         self.gitignores.clear();
+        self.excludes.clear();
+        self.dockerignore = None;
     }
 
     /// Get the number of loaded gitignore files
@@ -173,6 +358,29 @@ impl GitignoreManager {
     }
 }
 
+/// Rewrite one .dockerignore line as a root-anchored gitignore pattern.
+///
+/// Returns `None` for blank lines and comments. Docker cleans each pattern like a path, so
+/// leading `/` or `./` and trailing `/` carry no meaning there and are dropped before anchoring.
+fn dockerignore_to_gitignore(line: &str) -> Option<String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let (negation, pattern) = match line.strip_prefix('!') {
+        Some(pattern) => ("!", pattern.trim()),
+        None => ("", line),
+    };
+    let pattern = pattern.trim_start_matches("./").trim_start_matches('/');
+    let pattern = pattern.trim_end_matches('/');
+    if pattern.is_empty() {
+        return None;
+    }
+
+    Some(format!("{}/{}", negation, pattern))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -384,6 +592,109 @@ mod tests {
         assert!(manager.is_ignored_with_type(&temp_dir.path().join("build"), true));
     }
 
+    #[test]
+    fn test_is_ignored_matches_is_ignored_with_type() {
+        let temp_dir = create_test_directory();
+        let root = temp_dir.path();
+
+        fs::write(root.join(".gitignore"), "*.log\nbuild/\n!keep.log\n").unwrap();
+        fs::write(root.join("src/.gitignore"), "!debug.log\ngenerated/\n").unwrap();
+        fs::create_dir_all(root.join("build/out")).unwrap();
+        fs::create_dir_all(root.join("src/generated")).unwrap();
+        fs::write(root.join("build/out/app.bin"), "bin").unwrap();
+        fs::write(root.join("src/generated/code.rs"), "code").unwrap();
+        fs::write(root.join("src/debug.log"), "debug").unwrap();
+        fs::write(root.join("src/error.log"), "error").unwrap();
+        fs::write(root.join("keep.log"), "keep").unwrap();
+        fs::write(root.join("drop.log"), "drop").unwrap();
+
+        let mut manager = GitignoreManager::new(root);
+        manager.discover_gitignores(root).unwrap();
+
+        for entry in walkdir::WalkDir::new(root).min_depth(1) {
+            let entry = entry.unwrap();
+            let path = entry.path();
+            assert_eq!(
+                manager.is_ignored(path),
+                manager.is_ignored_with_type(path, entry.file_type().is_dir()),
+                "mismatch for {:?}",
+                path
+            );
+        }
+
+        // Spot-check the expected results
+        assert!(manager.is_ignored(&root.join("build")));
+        assert!(manager.is_ignored(&root.join("build/out/app.bin")));
+        assert!(manager.is_ignored(&root.join("src/generated/code.rs")));
+        assert!(manager.is_ignored(&root.join("src/error.log")));
+        assert!(manager.is_ignored(&root.join("drop.log")));
+        assert!(!manager.is_ignored(&root.join("src/debug.log")));
+        assert!(!manager.is_ignored(&root.join("keep.log")));
+        assert!(!manager.is_ignored(&root.join("src/.gitignore")));
+    }
+
+    #[test]
+    fn test_info_exclude_hides_file() {
+        let temp_dir = create_test_directory();
+        let root = temp_dir.path();
+
+        fs::create_dir_all(root.join(".git/info")).unwrap();
+        fs::write(root.join(".git/info/exclude"), "secret.txt\nscratch/\n").unwrap();
+        fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        fs::write(root.join("secret.txt"), "secret").unwrap();
+        fs::create_dir_all(root.join("src/scratch")).unwrap();
+        fs::write(root.join("src/scratch/notes.md"), "notes").unwrap();
+
+        let mut manager = GitignoreManager::new(root);
+        let count = manager.discover_gitignores(root).unwrap();
+
+        // Only .gitignore files are counted
+        assert_eq!(count, 1);
+        assert!(manager.is_ignored(&root.join("secret.txt")));
+        assert!(manager.is_ignored(&root.join("src/scratch/notes.md")));
+        assert!(manager.is_ignored(&root.join("file.log")));
+        assert!(!manager.is_ignored(&root.join("file.txt")));
+    }
+
+    #[test]
+    fn test_gitignore_overrides_exclude_file() {
+        let temp_dir = create_test_directory();
+        let root = temp_dir.path();
+
+        let exclude_path = root.join("global-ignore");
+        fs::write(&exclude_path, "*.txt\n").unwrap();
+        // The in-tree .gitignore re-includes file.txt, which must win over the exclude file
+        fs::write(root.join(".gitignore"), "!file.txt\n").unwrap();
+        fs::write(root.join("other.txt"), "other").unwrap();
+
+        let mut manager = GitignoreManager::new(root);
+        manager.add_exclude_file(&exclude_path).unwrap();
+        manager.discover_gitignores(root).unwrap();
+
+        assert!(!manager.is_ignored(&root.join("file.txt")));
+        assert!(manager.is_ignored(&root.join("other.txt")));
+
+        manager.clear();
+        assert!(!manager.is_ignored(&root.join("other.txt")));
+    }
+
+    #[test]
+    fn test_load_dir_gitignore() {
+        let temp_dir = create_test_directory();
+        let root = temp_dir.path();
+        fs::write(root.join("src/.gitignore"), "*.tmp\n").unwrap();
+
+        let mut manager = GitignoreManager::new(root);
+        // No .gitignore at the root
+        assert!(!manager.load_dir_gitignore(root));
+        assert!(manager.load_dir_gitignore(&root.join("src")));
+        // Already loaded
+        assert!(!manager.load_dir_gitignore(&root.join("src")));
+
+        assert_eq!(manager.gitignore_count(), 1);
+        assert!(manager.is_ignored(&root.join("src/a.tmp")));
+    }
+
     #[test]
     fn test_specific_file_pattern() {
         let temp_dir = create_test_directory();
@@ -398,4 +709,112 @@ mod tests {
         // Specific file should be ignored
         assert!(manager.is_ignored(&temp_dir.path().join("Cargo.lock")));
     }
+
+    #[test]
+    fn test_explain_ignored_attributes_rule() {
+        let temp_dir = create_test_directory();
+        let root = temp_dir.path();
+        let gitignore_path = root.join(".gitignore");
+        fs::write(&gitignore_path, "# logs\n*.log\nbuild/\n").unwrap();
+
+        let mut manager = GitignoreManager::new(root);
+        manager.add_patterns_from_file(&gitignore_path).unwrap();
+
+        let explained = manager
+            .explain_ignored(&root.join("file.log"), false)
+            .unwrap();
+        assert_eq!(explained.pattern, "*.log");
+        assert_eq!(explained.source.as_deref(), Some(gitignore_path.as_path()));
+        assert_eq!(explained.matched_path, root.join("file.log"));
+
+        // Contents of an ignored directory are attributed to the directory's rule
+        let explained = manager
+            .explain_ignored(&root.join("build/output.js"), false)
+            .unwrap();
+        assert_eq!(explained.pattern, "build/");
+        assert_eq!(explained.matched_path, root.join("build"));
+
+        assert_eq!(
+            manager.explain_ignored(&root.join("src/main.rs"), false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_dockerignore_excludes_paths_gitignore_keeps() {
+        let temp_dir = create_test_directory();
+        let root = temp_dir.path();
+        fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        fs::write(
+            root.join(".dockerignore"),
+            "# build context\n./docs/\n/README.md\ntarget\n!docs/keep.md\n",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::create_dir_all(root.join("src/target")).unwrap();
+
+        let mut manager = GitignoreManager::new(root);
+        manager
+            .add_patterns_from_file(&root.join(".gitignore"))
+            .unwrap();
+        assert!(!manager.is_ignored_with_type(&root.join("docs/guide.md"), false));
+
+        assert!(manager.load_dockerignore());
+        assert!(manager.is_ignored_with_type(&root.join("docs"), true));
+        assert!(manager.is_ignored_with_type(&root.join("docs/guide.md"), false));
+        assert!(manager.is_ignored_with_type(&root.join("README.md"), false));
+        assert!(manager.is_ignored_with_type(&root.join("target"), true));
+        // Git rules still apply alongside
+        assert!(manager.is_ignored_with_type(&root.join("app.log"), false));
+
+        // Patterns are anchored at the root, unlike .gitignore
+        assert!(!manager.is_ignored_with_type(&root.join("src/target"), true));
+        assert!(!manager.is_ignored_with_type(&root.join("src/README.md"), false));
+        // Exceptions re-include files inside an excluded directory
+        assert!(!manager.is_ignored_with_type(&root.join("docs/keep.md"), false));
+
+        let explained = manager
+            .explain_ignored(&root.join("docs/guide.md"), false)
+            .unwrap();
+        assert_eq!(explained.matched_path, root.join("docs"));
+        assert_eq!(
+            explained.source.as_deref(),
+            Some(root.join(".dockerignore").as_path())
+        );
+    }
+
+    #[test]
+    fn test_dockerignore_only_loaded_from_root() {
+        let temp_dir = create_test_directory();
+        let root = temp_dir.path();
+        fs::write(root.join("src/.dockerignore"), "main.rs\n").unwrap();
+
+        let mut manager = GitignoreManager::new(root);
+        assert!(!manager.load_dockerignore());
+        manager.load_dir_gitignore(&root.join("src"));
+        assert!(!manager.is_ignored_with_type(&root.join("src/main.rs"), false));
+    }
+
+    #[test]
+    fn test_dockerignore_to_gitignore() {
+        assert_eq!(
+            dockerignore_to_gitignore("target").as_deref(),
+            Some("/target")
+        );
+        assert_eq!(
+            dockerignore_to_gitignore("./docs/").as_deref(),
+            Some("/docs")
+        );
+        assert_eq!(
+            dockerignore_to_gitignore("**/*.md").as_deref(),
+            Some("/**/*.md")
+        );
+        assert_eq!(
+            dockerignore_to_gitignore("! /keep ").as_deref(),
+            Some("!/keep")
+        );
+        assert_eq!(dockerignore_to_gitignore("# comment"), None);
+        assert_eq!(dockerignore_to_gitignore("   "), None);
+        assert_eq!(dockerignore_to_gitignore("/"), None);
+    }
 }
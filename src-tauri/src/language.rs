@@ -0,0 +1,142 @@
+//! File language detection
+//!
+//! Maps file names and extensions to language identifiers. The identifiers double as
+//! Markdown code fence tags, so the same value is stored in the index for the UI and used
+//! when fencing file contents in prompts.
+
+use std::path::Path;
+
+/// Languages recognized from the whole file name, for files without a telling extension
+const NAME_LANGUAGES: &[(&str, &str)] = &[
+    ("dockerfile", "dockerfile"),
+    ("containerfile", "dockerfile"),
+    ("makefile", "makefile"),
+    ("gnumakefile", "makefile"),
+    ("cmakelists.txt", "cmake"),
+    ("gemfile", "ruby"),
+    ("rakefile", "ruby"),
+    ("jenkinsfile", "groovy"),
+    ("vagrantfile", "ruby"),
+    (".bashrc", "bash"),
+    (".zshrc", "bash"),
+    (".profile", "bash"),
+];
+
+/// Languages recognized from the (lowercased) file extension
+const EXTENSION_LANGUAGES: &[(&str, &str)] = &[
+    ("rs", "rust"),
+    ("ts", "typescript"),
+    ("mts", "typescript"),
+    ("cts", "typescript"),
+    ("tsx", "tsx"),
+    ("js", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("jsx", "jsx"),
+    ("py", "python"),
+    ("pyi", "python"),
+    ("go", "go"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("kts", "kotlin"),
+    ("scala", "scala"),
+    ("groovy", "groovy"),
+    ("swift", "swift"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cc", "cpp"),
+    ("cpp", "cpp"),
+    ("cxx", "cpp"),
+    ("hpp", "cpp"),
+    ("hh", "cpp"),
+    ("cs", "csharp"),
+    ("fs", "fsharp"),
+    ("rb", "ruby"),
+    ("php", "php"),
+    ("lua", "lua"),
+    ("dart", "dart"),
+    ("r", "r"),
+    ("ex", "elixir"),
+    ("exs", "elixir"),
+    ("erl", "erlang"),
+    ("hs", "haskell"),
+    ("clj", "clojure"),
+    ("zig", "zig"),
+    ("sh", "bash"),
+    ("bash", "bash"),
+    ("zsh", "bash"),
+    ("ps1", "powershell"),
+    ("bat", "batch"),
+    ("cmd", "batch"),
+    ("sql", "sql"),
+    ("html", "html"),
+    ("htm", "html"),
+    ("css", "css"),
+    ("scss", "scss"),
+    ("sass", "sass"),
+    ("less", "less"),
+    ("vue", "vue"),
+    ("svelte", "svelte"),
+    ("json", "json"),
+    ("jsonc", "json"),
+    ("yaml", "yaml"),
+    ("yml", "yaml"),
+    ("toml", "toml"),
+    ("xml", "xml"),
+    ("md", "markdown"),
+    ("mdx", "markdown"),
+    ("graphql", "graphql"),
+    ("gql", "graphql"),
+    ("proto", "protobuf"),
+    ("tf", "hcl"),
+    ("cmake", "cmake"),
+    ("dockerfile", "dockerfile"),
+    ("mk", "makefile"),
+];
+
+/// Detect the language of a file from its name, falling back to its extension.
+///
+/// Returns `None` for plain text and anything unrecognized.
+pub fn detect_language(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    if let Some((_, language)) = NAME_LANGUAGES.iter().find(|(known, _)| *known == name) {
+        return Some(language);
+    }
+
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    EXTENSION_LANGUAGES
+        .iter()
+        .find(|(known, _)| *known == extension)
+        .map(|(_, language)| *language)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language_by_extension() {
+        assert_eq!(detect_language(Path::new("/src/main.rs")), Some("rust"));
+        assert_eq!(detect_language(Path::new("App.tsx")), Some("tsx"));
+        assert_eq!(detect_language(Path::new("utils.ts")), Some("typescript"));
+        assert_eq!(detect_language(Path::new("script.PY")), Some("python"));
+        assert_eq!(detect_language(Path::new("config.yml")), Some("yaml"));
+    }
+
+    #[test]
+    fn test_detect_language_by_name() {
+        assert_eq!(
+            detect_language(Path::new("/repo/Dockerfile")),
+            Some("dockerfile")
+        );
+        assert_eq!(detect_language(Path::new("Makefile")), Some("makefile"));
+        assert_eq!(detect_language(Path::new("CMakeLists.txt")), Some("cmake"));
+    }
+
+    #[test]
+    fn test_detect_language_unknown() {
+        assert_eq!(detect_language(Path::new("notes.txt")), None);
+        assert_eq!(detect_language(Path::new("LICENSE")), None);
+        assert_eq!(detect_language(Path::new("archive.xyz")), None);
+    }
+}
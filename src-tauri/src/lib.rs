@@ -1,25 +1,60 @@
 mod cache;
 mod commands;
+mod dates;
 mod db;
 mod error;
 pub mod gitignore;
+mod language;
+mod log_buffer;
+mod paths;
+mod project_config;
 mod templates;
 
 use cache::TextCache;
+use commands::browser::BrowserProcesses;
+use commands::indexing::{IndexingState, SearchCache};
+use commands::settings::{load_settings_internal, SettingsChanged, SETTINGS_CHANGED_EVENT};
 use db::DbConnection;
 use std::sync::Mutex;
-use tauri::Manager;
+use tauri::{AppHandle, Listener, Manager};
+
+/// Apply settings that affect long-lived state as soon as they change
+fn apply_settings_changes(app: &AppHandle, keys: &[String]) {
+    if keys.iter().any(|key| key == "cache_size_mb") {
+        let db = app.state::<DbConnection>();
+        let settings = match load_settings_internal(&db) {
+            Ok(loaded) => loaded.settings,
+            Err(e) => {
+                log::warn!("Failed to load settings after change: {}", e);
+                return;
+            }
+        };
+
+        let cache = app.state::<Mutex<TextCache>>();
+        let result = match cache.lock() {
+            Ok(mut cache) => cache.set_max_size(settings.cache_size_bytes()),
+            Err(e) => {
+                log::warn!("Failed to lock text cache: {}", e);
+                return;
+            }
+        };
+
+        match result {
+            Ok(()) => log::info!("Text cache limit set to {} MB", settings.cache_size_mb),
+            Err(e) => log::warn!("Failed to resize text cache: {}", e),
+        }
+    }
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Initialize logging
-    env_logger::Builder::from_default_env()
-        .filter_level(log::LevelFilter::Info)
-        .init();
+    // Initialize logging; recent lines are also kept in memory for get_recent_logs
+    let log_buffer = log_buffer::init();
 
     log::info!("Starting AI Context Collector application");
 
     tauri::Builder::default()
+        .manage(log_buffer)
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
@@ -41,26 +76,72 @@ pub fn run() {
             app.manage(Mutex::new(text_cache));
             log::info!("Text cache initialized successfully");
 
+            // Apply the configured cache size now and whenever it changes
+            apply_settings_changes(app.handle(), &["cache_size_mb".to_string()]);
+
+            let handle = app.handle().clone();
+            app.listen(SETTINGS_CHANGED_EVENT, move |event| {
+                let changed = serde_json::from_str::<SettingsChanged>(event.payload());
+                match changed {
+                    Ok(changed) => apply_settings_changes(&handle, &changed.keys),
+                    Err(e) => log::warn!("Invalid settings-changed payload: {}", e),
+                }
+            });
+
+            app.manage(BrowserProcesses::default());
+            app.manage(IndexingState::default());
+            app.manage(SearchCache::default());
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             commands::indexing::index_folder,
+            commands::indexing::is_indexing,
             commands::indexing::get_children,
+            commands::indexing::get_children_page,
+            commands::indexing::get_recent_files,
+            commands::indexing::suggest_files,
+            commands::indexing::count_selection_tokens,
+            commands::indexing::render_file_tree,
             commands::indexing::search_path,
+            commands::indexing::get_index_stats,
+            commands::indexing::get_roots,
             commands::indexing::clear_index,
+            commands::indexing::remove_from_index,
+            commands::indexing::reveal_in_file_manager,
+            commands::indexing::preview_ignored,
+            commands::indexing::explain_ignore,
             commands::browser::launch_browser,
             commands::browser::get_available_interfaces,
+            commands::browser::check_browser_prerequisites,
+            commands::browser::add_custom_interface,
+            commands::browser::remove_custom_interface,
+            commands::browser::get_browser_launch,
             commands::extraction::extract_text,
             commands::extraction::get_supported_file_types,
+            commands::extraction::invalidate_cache_entry,
+            commands::extraction::reextract_text,
             commands::prompts::get_templates,
+            commands::prompts::validate_template,
             commands::prompts::get_file_content,
             commands::prompts::get_file_contents,
+            commands::prompts::get_file_contents_detailed,
             commands::prompts::build_prompt_from_files,
+            commands::prompts::build_and_launch,
+            commands::prompts::build_prompt_to_file,
+            commands::prompts::build_prompt_chunks,
+            commands::prompts::estimate_prompt,
+            commands::prompts::files_to_markdown,
+            commands::prompts::estimate_tokens,
+            commands::git::get_git_changes,
             commands::history::save_history,
             commands::history::load_history,
             commands::history::validate_history_paths,
             commands::history::delete_history,
             commands::history::clear_history,
+            commands::bookmarks::add_bookmark,
+            commands::bookmarks::remove_bookmark,
+            commands::bookmarks::list_bookmarks,
             commands::settings::save_setting,
             commands::settings::get_setting,
             commands::settings::get_all_settings,
@@ -70,7 +151,19 @@ pub fn run() {
             commands::settings::import_settings,
             commands::settings::delete_setting,
             commands::settings::reset_settings,
+            commands::settings::get_project_config,
+            commands::logs::get_recent_logs,
+            commands::database::checkpoint_database,
+            commands::diagnostics::system_healthcheck,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Fold the WAL back into the database so the next start doesn't replay it
+                if let Some(db) = app.try_state::<DbConnection>() {
+                    db::checkpoint_on_shutdown(&db);
+                }
+            }
+        });
 }
@@ -0,0 +1,157 @@
+//! In-memory log capture
+//!
+//! `env_logger` only writes to stderr, which packaged-app users never see. The logger
+//! installed here forwards every record to `env_logger` as before and also keeps the most
+//! recent formatted lines in a bounded ring buffer, so they can be fetched from the UI.
+
+use crate::dates::civil_from_days;
+use log::{Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of log lines kept in memory
+pub const LOG_BUFFER_CAPACITY: usize = 2000;
+
+/// Bounded buffer of formatted log lines; the oldest line is evicted once full
+#[derive(Debug)]
+pub struct LogBuffer {
+    lines: Mutex<VecDeque<String>>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    pub fn push(&self, line: String) {
+        if self.capacity == 0 {
+            return;
+        }
+        // A poisoned lock only means another thread panicked mid-push; keep logging
+        let mut lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    /// The last `count` lines, oldest first
+    pub fn recent(&self, count: usize) -> Vec<String> {
+        let lines = self.lines.lock().unwrap_or_else(|e| e.into_inner());
+        let skip = lines.len().saturating_sub(count);
+        lines.iter().skip(skip).cloned().collect()
+    }
+}
+
+/// Logger that writes through `env_logger` and records the same lines in a `LogBuffer`
+struct BufferedLogger {
+    inner: env_logger::Logger,
+    buffer: Arc<LogBuffer>,
+}
+
+impl Log for BufferedLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.inner.matches(record) {
+            return;
+        }
+        self.inner.log(record);
+        self.buffer.push(format_record(record));
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Format a record as `YYYY-MM-DDTHH:MM:SSZ LEVEL target: message`
+fn format_record(record: &Record) -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days(secs.div_euclid(86_400));
+    let time = secs.rem_euclid(86_400);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z {} {}: {}",
+        year,
+        month,
+        day,
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60,
+        record.level(),
+        record.target(),
+        record.args()
+    )
+}
+
+/// Install the global logger, configured like the plain `env_logger` setup it replaces
+///
+/// Returns the buffer that receives every emitted line.
+pub fn init() -> Arc<LogBuffer> {
+    let inner = env_logger::Builder::from_default_env()
+        .filter_level(log::LevelFilter::Info)
+        .build();
+    let buffer = Arc::new(LogBuffer::new(LOG_BUFFER_CAPACITY));
+
+    let max_level = inner.filter();
+    let logger = BufferedLogger {
+        inner,
+        buffer: Arc::clone(&buffer),
+    };
+    match log::set_boxed_logger(Box::new(logger)) {
+        Ok(()) => log::set_max_level(max_level),
+        Err(e) => eprintln!("Failed to install logger: {}", e),
+    }
+
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_buffer_evicts_oldest_lines() {
+        let buffer = LogBuffer::new(3);
+        for i in 0..5 {
+            buffer.push(format!("line {}", i));
+        }
+
+        assert_eq!(buffer.recent(10), vec!["line 2", "line 3", "line 4"]);
+        assert_eq!(buffer.recent(2), vec!["line 3", "line 4"]);
+        assert!(buffer.recent(0).is_empty());
+    }
+
+    #[test]
+    fn test_log_buffer_zero_capacity_keeps_nothing() {
+        let buffer = LogBuffer::new(0);
+        buffer.push("dropped".to_string());
+        assert!(buffer.recent(10).is_empty());
+    }
+
+    #[test]
+    fn test_format_record() {
+        let line = format_record(
+            &Record::builder()
+                .level(log::Level::Warn)
+                .target("indexing")
+                .args(format_args!("slow disk"))
+                .build(),
+        );
+
+        assert!(line.ends_with("Z WARN indexing: slow disk"), "{}", line);
+        assert_eq!(&line[4..5], "-");
+        assert_eq!(&line[10..11], "T");
+    }
+}
@@ -0,0 +1,223 @@
+//! Canonical string form of filesystem paths
+//!
+//! Paths are stored in the index as strings, so every module that writes or looks up a path
+//! has to spell it the same way. `normalize_path` is that single spelling; `get_parent_path`
+//! derives a parent in the same form, without touching the filesystem.
+
+/// Normalize a path to the form stored in the index
+///
+/// - backslashes become forward slashes and repeated separators collapse to one
+/// - Windows verbatim prefixes (`\\?\C:\`, `\\?\UNC\server\share`) are stripped
+/// - drive letters are upper-cased, so `c:\src` and `C:/src` agree
+/// - UNC paths keep their leading `//`
+/// - a trailing separator is dropped unless the path is a root (`/`, `C:/`)
+pub fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let path = if let Some(unc) = path.strip_prefix("//?/UNC/") {
+        format!("//{}", unc)
+    } else if let Some(verbatim) = path.strip_prefix("//?/") {
+        verbatim.to_string()
+    } else {
+        path
+    };
+
+    let mut normalized = String::with_capacity(path.len());
+    if path.starts_with("//") {
+        // Collapsing below keeps one of the two leading slashes
+        normalized.push('/');
+    }
+    let mut previous_slash = false;
+    for c in path.chars() {
+        if c == '/' && previous_slash {
+            continue;
+        }
+        previous_slash = c == '/';
+        normalized.push(c);
+    }
+
+    if drive_prefix_len(&normalized) > 0 {
+        normalized[..1].make_ascii_uppercase();
+    }
+    if normalized.len() > root_len(&normalized) && normalized.ends_with('/') {
+        normalized.pop();
+    }
+    normalized
+}
+
+/// Parent of `path`, normalized; `None` for a root or a single relative component
+pub fn get_parent_path(path: &str) -> Option<String> {
+    let path = normalize_path(path);
+    let root = root_len(&path);
+    if path.len() <= root {
+        return None;
+    }
+
+    let parent = match path[root..].rfind('/') {
+        Some(index) => &path[..root + index],
+        None => &path[..root],
+    };
+    if parent.is_empty() {
+        None
+    } else {
+        Some(parent.to_string())
+    }
+}
+
+/// Whether normalized `path` is `root` itself or lies below it
+///
+/// Paths with a `..` component are never within a root, since they could climb out of it.
+pub fn is_within(path: &str, root: &str) -> bool {
+    if root.is_empty() || path.split('/').any(|component| component == "..") {
+        return false;
+    }
+    match path.strip_prefix(root) {
+        Some("") => true,
+        Some(rest) => root.ends_with('/') || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// Length of `C:` or `C:/` at the start of a normalized path, or 0
+fn drive_prefix_len(path: &str) -> usize {
+    let bytes = path.as_bytes();
+    if bytes.len() < 2 || !bytes[0].is_ascii_alphabetic() || bytes[1] != b':' {
+        return 0;
+    }
+    match bytes.get(2) {
+        None => 2,
+        Some(b'/') => 3,
+        // `c:foo` is drive-relative; treat it as a plain relative path
+        Some(_) => 0,
+    }
+}
+
+/// Length of the root that can't be stripped from a normalized path
+///
+/// `/` on Unix, `C:/` for a drive and `//server/share` for a UNC path; 0 when relative.
+fn root_len(path: &str) -> usize {
+    if let Some(unc) = path.strip_prefix("//") {
+        let mut parts = unc.splitn(3, '/');
+        let server = parts.next().unwrap_or("");
+        let share = parts
+            .next()
+            .filter(|share| !share.is_empty())
+            .map_or(0, |share| share.len() + 1);
+        return 2 + server.len() + share;
+    }
+    match drive_prefix_len(path) {
+        0 => usize::from(path.starts_with('/')),
+        len => len,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_unix_paths() {
+        assert_eq!(normalize_path("/home/user/project"), "/home/user/project");
+        assert_eq!(normalize_path("/home/user/project/"), "/home/user/project");
+        assert_eq!(
+            normalize_path("/home//user///project"),
+            "/home/user/project"
+        );
+        assert_eq!(normalize_path("/"), "/");
+        assert_eq!(normalize_path("src/main.rs"), "src/main.rs");
+        assert_eq!(normalize_path(""), "");
+    }
+
+    #[test]
+    fn test_normalize_windows_paths() {
+        assert_eq!(normalize_path(r"C:\Users\dev\src"), "C:/Users/dev/src");
+        assert_eq!(normalize_path(r"c:\Users\dev\src\"), "C:/Users/dev/src");
+        assert_eq!(normalize_path("c:/Users/dev/src"), "C:/Users/dev/src");
+        assert_eq!(normalize_path(r"C:\"), "C:/");
+        assert_eq!(normalize_path("d:"), "D:");
+        assert_eq!(
+            normalize_path(r"C:\mixed/separators\\here"),
+            "C:/mixed/separators/here"
+        );
+        assert_eq!(normalize_path(r"\\?\C:\Users\dev"), "C:/Users/dev");
+        // Not a drive letter
+        assert_eq!(normalize_path("c:foo"), "c:foo");
+    }
+
+    #[test]
+    fn test_normalize_unc_paths() {
+        assert_eq!(normalize_path(r"\\server\share\dir"), "//server/share/dir");
+        assert_eq!(normalize_path(r"\\server\share\"), "//server/share");
+        assert_eq!(
+            normalize_path(r"\\?\UNC\server\share\dir"),
+            "//server/share/dir"
+        );
+        assert_eq!(normalize_path("//server//share/dir/"), "//server/share/dir");
+    }
+
+    #[test]
+    fn test_normalize_is_idempotent() {
+        for path in [
+            r"C:\Users\dev\",
+            r"\\server\share\a\\b",
+            "/tmp//x/",
+            r"\\?\c:\x",
+            "relative\\dir",
+        ] {
+            let once = normalize_path(path);
+            assert_eq!(normalize_path(&once), once, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_get_parent_path() {
+        assert_eq!(get_parent_path("/a/b/c.rs").as_deref(), Some("/a/b"));
+        assert_eq!(get_parent_path("/a").as_deref(), Some("/"));
+        assert_eq!(get_parent_path("/a/b/").as_deref(), Some("/a"));
+        assert_eq!(get_parent_path("/"), None);
+
+        assert_eq!(
+            get_parent_path(r"c:\src\main.rs").as_deref(),
+            Some("C:/src")
+        );
+        assert_eq!(get_parent_path(r"C:\src").as_deref(), Some("C:/"));
+        assert_eq!(get_parent_path(r"C:\"), None);
+
+        assert_eq!(
+            get_parent_path(r"\\server\share\dir\f.txt").as_deref(),
+            Some("//server/share/dir")
+        );
+        assert_eq!(
+            get_parent_path(r"\\server\share\dir").as_deref(),
+            Some("//server/share")
+        );
+        assert_eq!(get_parent_path(r"\\server\share"), None);
+
+        assert_eq!(get_parent_path("src/main.rs").as_deref(), Some("src"));
+        assert_eq!(get_parent_path("main.rs"), None);
+    }
+
+    #[test]
+    fn test_is_within() {
+        assert!(is_within("/a/b", "/a/b"));
+        assert!(is_within("/a/b/c.rs", "/a/b"));
+        assert!(is_within("/a/b/c.rs", "/"));
+        assert!(is_within("C:/src/main.rs", "C:/"));
+        assert!(is_within("//server/share/f.txt", "//server/share"));
+
+        // A shared prefix is not enough
+        assert!(!is_within("/a/bc/d.rs", "/a/b"));
+        assert!(!is_within("/a", "/a/b"));
+        assert!(!is_within("/a/b/../../etc/passwd", "/a/b"));
+        assert!(!is_within("/a/b", ""));
+    }
+
+    #[test]
+    fn test_get_parent_path_matches_std_on_unix_paths() {
+        for path in ["/a/b/c", "/a/b", "/a", "/tmp/project/src/lib.rs"] {
+            let expected = std::path::Path::new(path)
+                .parent()
+                .map(|parent| parent.to_string_lossy().into_owned());
+            assert_eq!(get_parent_path(path), expected, "{}", path);
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Per-project configuration read from an `.ai-context.toml` at an indexed root
+//!
+//! A project config lets a repository commit its own defaults. For operations on that root,
+//! every value it sets takes precedence over the app settings, which in turn fall back to
+//! the built-in defaults: project config > global settings > defaults.
+
+use crate::commands::settings::AppSettings;
+use crate::error::{AppError, AppResult};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// File name looked up at the indexed root
+pub const PROJECT_CONFIG_FILE: &str = ".ai-context.toml";
+
+/// Settings a project can override; unset values leave the global setting in place
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectConfig {
+    pub default_template: Option<String>,
+    /// Extensions left out when indexing this root, with or without the leading dot
+    pub excluded_extensions: Option<Vec<String>>,
+    pub respect_gitignore: Option<bool>,
+    pub follow_symlinks: Option<bool>,
+    pub max_index_depth: Option<u32>,
+}
+
+impl ProjectConfig {
+    /// Read the config at `root`; `None` when the project has none
+    pub fn load(root: &Path) -> AppResult<Option<Self>> {
+        let path = root.join(PROJECT_CONFIG_FILE);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        toml::from_str(&contents)
+            .map(Some)
+            .map_err(|e| AppError::InvalidArgument(format!("Invalid {}: {}", path.display(), e)))
+    }
+
+    /// Like `load`, but a missing or malformed config counts as an empty one
+    pub fn load_or_default(root: &Path) -> Self {
+        Self::load(root)
+            .unwrap_or_else(|e| {
+                log::warn!("Ignoring project config: {}", e);
+                None
+            })
+            .unwrap_or_default()
+    }
+
+    /// `settings` with every value this config sets taking its place
+    pub fn apply(&self, mut settings: AppSettings) -> AppSettings {
+        if let Some(template) = &self.default_template {
+            settings.default_template = template.clone();
+        }
+        if let Some(extensions) = &self.excluded_extensions {
+            settings.excluded_extensions = extensions.clone();
+        }
+        if let Some(respect_gitignore) = self.respect_gitignore {
+            settings.respect_gitignore = respect_gitignore;
+        }
+        if let Some(follow_symlinks) = self.follow_symlinks {
+            settings.follow_symlinks = follow_symlinks;
+        }
+        if let Some(max_index_depth) = self.max_index_depth {
+            settings.max_index_depth = max_index_depth;
+        }
+        settings
+    }
+
+    /// Whether `path` has one of this config's excluded extensions, ignoring case
+    pub fn excludes_extension(&self, path: &Path) -> bool {
+        let (Some(excluded), Some(ext)) = (
+            &self.excluded_extensions,
+            path.extension().and_then(|ext| ext.to_str()),
+        ) else {
+            return false;
+        };
+
+        excluded
+            .iter()
+            .any(|excluded| excluded.trim_start_matches('.').eq_ignore_ascii_case(ext))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_parses_sample_config() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join(PROJECT_CONFIG_FILE),
+            r#"
+default_template = "planner"
+excluded_extensions = [".lock", "snap"]
+max_index_depth = 4
+"#,
+        )
+        .unwrap();
+
+        let config = ProjectConfig::load(temp_dir.path()).unwrap().unwrap();
+        assert_eq!(config.default_template.as_deref(), Some("planner"));
+        assert_eq!(config.max_index_depth, Some(4));
+        assert_eq!(config.respect_gitignore, None);
+
+        assert!(config.excludes_extension(Path::new("/p/Cargo.lock")));
+        assert!(config.excludes_extension(Path::new("/p/view.SNAP")));
+        assert!(!config.excludes_extension(Path::new("/p/main.rs")));
+        assert!(!config.excludes_extension(Path::new("/p/lock")));
+
+        // Set values win over the global ones, the rest are kept
+        let global = AppSettings {
+            respect_gitignore: false,
+            ..AppSettings::default()
+        };
+        let effective = config.apply(global);
+        assert_eq!(effective.default_template, "planner");
+        assert_eq!(effective.excluded_extensions, vec![".lock", "snap"]);
+        assert_eq!(effective.max_index_depth, 4);
+        assert!(!effective.respect_gitignore);
+    }
+
+    #[test]
+    fn test_load_missing_and_invalid_config() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(ProjectConfig::load(temp_dir.path()).unwrap(), None);
+
+        fs::write(
+            temp_dir.path().join(PROJECT_CONFIG_FILE),
+            "max_index_depth = \"deep\"",
+        )
+        .unwrap();
+        let err = ProjectConfig::load(temp_dir.path()).unwrap_err();
+        assert!(matches!(err, AppError::InvalidArgument(_)));
+        assert_eq!(
+            ProjectConfig::load_or_default(temp_dir.path()),
+            ProjectConfig::default()
+        );
+    }
+}
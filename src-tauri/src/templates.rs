@@ -1,4 +1,18 @@
+use crate::dates::civil_from_days;
+use crate::language::detect_language;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Placeholders `build_prompt` fills in; `{{git_diff}}` only when the caller supplies it
+pub const SUPPORTED_PLACEHOLDERS: [&str; 6] = [
+    "custom_instructions",
+    "files",
+    "file_count",
+    "total_tokens",
+    "date",
+    "git_diff",
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PromptTemplate {
@@ -6,30 +20,45 @@ pub struct PromptTemplate {
     pub name: String,
     pub description: String,
     pub template: String,
+    /// Placeholders used in `template`, in order of first use
+    #[serde(default)]
+    pub placeholders: Vec<String>,
+}
+
+impl PromptTemplate {
+    fn new(id: &str, name: &str, description: &str, template: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            template: template.to_string(),
+            placeholders: placeholder_names(template),
+        }
+    }
 }
 
 /// Get built-in prompt templates
 pub fn get_builtin_templates() -> Vec<PromptTemplate> {
     vec![
-        PromptTemplate {
-            id: "custom".to_string(),
-            name: "Custom".to_string(),
-            description: "Directly control the prompt structure".to_string(),
-            template: r#"{{custom_instructions}}"#.to_string(),
-        },
-        PromptTemplate {
-            id: "agent".to_string(),
-            name: "AI Agent".to_string(),
-            description: "General purpose AI agent task template".to_string(),
-            template: r#"You are an expert software engineer tasked with analyzing and working with the following codebase.
-
-{{custom_instructions}}"#.to_string(),
-        },
-        PromptTemplate {
-            id: "planning".to_string(),
-            name: "Planning".to_string(),
-            description: "Project planning and architecture template".to_string(),
-            template: r#"You are a technical architect reviewing the following codebase for planning purposes.
+        PromptTemplate::new(
+            "custom",
+            "Custom",
+            "Directly control the prompt structure",
+            r#"{{custom_instructions}}"#,
+        ),
+        PromptTemplate::new(
+            "agent",
+            "AI Agent",
+            "General purpose AI agent task template",
+            r#"You are an expert software engineer tasked with analyzing and working with the following codebase.
+
+{{custom_instructions}}"#,
+        ),
+        PromptTemplate::new(
+            "planning",
+            "Planning",
+            "Project planning and architecture template",
+            r#"You are a technical architect reviewing the following codebase for planning purposes.
 
 {{custom_instructions}}
 
@@ -37,13 +66,13 @@ Please review these files and provide:
 1. Architecture overview
 2. Key components and their relationships
 3. Potential improvements or concerns
-4. Implementation recommendations"#.to_string(),
-        },
-        PromptTemplate {
-            id: "debugging".to_string(),
-            name: "Debugging".to_string(),
-            description: "Code debugging and troubleshooting template".to_string(),
-            template: r#"You are a debugging expert analyzing the following code for issues.
+4. Implementation recommendations"#,
+        ),
+        PromptTemplate::new(
+            "debugging",
+            "Debugging",
+            "Code debugging and troubleshooting template",
+            r#"You are a debugging expert analyzing the following code for issues.
 
 {{custom_instructions}}
 
@@ -51,13 +80,13 @@ Please review the code and:
 1. Identify potential bugs or issues
 2. Suggest fixes and improvements
 3. Explain root causes
-4. Recommend best practices"#.to_string(),
-        },
-        PromptTemplate {
-            id: "review".to_string(),
-            name: "Code Review".to_string(),
-            description: "Code review template".to_string(),
-            template: r#"You are performing a code review on the following files.
+4. Recommend best practices"#,
+        ),
+        PromptTemplate::new(
+            "review",
+            "Code Review",
+            "Code review template",
+            r#"You are performing a code review on the following files.
 
 {{custom_instructions}}
 
@@ -66,13 +95,13 @@ Please provide:
 2. Potential issues or improvements
 3. Security concerns
 4. Performance considerations
-5. Maintainability suggestions"#.to_string(),
-        },
-        PromptTemplate {
-            id: "documentation".to_string(),
-            name: "Documentation".to_string(),
-            description: "Generate documentation for code".to_string(),
-            template: r#"You are a technical writer creating documentation for the following codebase.
+5. Maintainability suggestions"#,
+        ),
+        PromptTemplate::new(
+            "documentation",
+            "Documentation",
+            "Generate documentation for code",
+            r#"You are a technical writer creating documentation for the following codebase.
 
 {{custom_instructions}}
 
@@ -80,13 +109,13 @@ Please generate comprehensive documentation including:
 1. Overview and purpose
 2. Key functions and classes
 3. Usage examples
-4. API documentation"#.to_string(),
-        },
-        PromptTemplate {
-            id: "testing".to_string(),
-            name: "Testing".to_string(),
-            description: "Generate test cases for code".to_string(),
-            template: r#"You are a QA engineer creating test cases for the following code.
+4. API documentation"#,
+        ),
+        PromptTemplate::new(
+            "testing",
+            "Testing",
+            "Generate test cases for code",
+            r#"You are a QA engineer creating test cases for the following code.
 
 {{custom_instructions}}
 
@@ -94,60 +123,305 @@ Please generate:
 1. Unit test cases
 2. Edge cases to consider
 3. Integration test scenarios
-4. Test data examples"#.to_string(),
-        },
+4. Test data examples"#,
+        ),
+        PromptTemplate::new(
+            "diff",
+            "Diff Context",
+            "Debug uncommitted changes with the files around them",
+            r#"You are a debugging expert looking at work in progress. These are the uncommitted git changes:
+
+{{git_diff}}
+
+{{custom_instructions}}
+
+The files below show the code around the changes."#,
+        ),
     ]
 }
 
 /// Build a prompt from template, custom instructions, and file contents
-pub fn build_prompt(
+///
+/// Each file's block is appended to the output as the iterator yields it, so callers can
+/// read contents lazily and hold only one file in memory besides the prompt itself.
+///
+/// Besides `{{custom_instructions}}` and `{{files}}`, templates and instructions may use
+/// `{{file_count}}`, `{{total_tokens}}` (estimated) and `{{date}}`.
+pub fn build_prompt<I, P, C>(
+    template_id: &str,
+    custom_instructions: Option<&str>,
+    file_contents: I, // (path, content) pairs
+) -> Result<String, String>
+where
+    I: IntoIterator<Item = (P, C)>,
+    P: AsRef<str>,
+    C: AsRef<str>,
+{
+    build_prompt_with_vars(
+        template_id,
+        custom_instructions,
+        &HashMap::new(),
+        file_contents,
+    )
+}
+
+/// Build a prompt like `build_prompt`, filling in caller-supplied placeholders such as
+/// `{{git_diff}}` as well
+///
+/// The built-in placeholders take precedence over `extra_vars` with the same name.
+pub fn build_prompt_with_vars<I, P, C>(
+    template_id: &str,
+    custom_instructions: Option<&str>,
+    extra_vars: &HashMap<&str, String>,
+    file_contents: I,
+) -> Result<String, String>
+where
+    I: IntoIterator<Item = (P, C)>,
+    P: AsRef<str>,
+    C: AsRef<str>,
+{
+    let head = prompt_head(template_id, custom_instructions)?;
+
+    // Build files section with markdown code blocks
+    let mut prompt = String::new();
+    let mut file_count = 0;
+    for (path, content) in file_contents {
+        if file_count > 0 {
+            prompt.push_str("\n\n");
+        }
+        push_file_block(&mut prompt, path.as_ref(), content.as_ref());
+        file_count += 1;
+    }
+    if file_count == 0 {
+        prompt.push_str("No files provided.");
+    }
+
+    // Summary values are only known once every file has been read
+    let mut vars = extra_vars.clone();
+    vars.extend([
+        ("file_count", file_count.to_string()),
+        ("total_tokens", estimate_tokens(&prompt).to_string()),
+        ("date", current_date()),
+    ]);
+
+    // Splice the text around the files section in place; any further placeholder repeats it.
+    // Parts are rendered after splitting, so a value containing `{{files}}` stays as written.
+    let mut parts = head
+        .split("{{files}}")
+        .map(|part| render_template(part, &vars));
+    let before = parts.next().unwrap_or_default();
+    let files_len = prompt.len();
+    prompt.insert_str(0, &before);
+
+    let mut files_section: Option<String> = None;
+    for (i, part) in parts.enumerate() {
+        if i > 0 {
+            let section = files_section
+                .get_or_insert_with(|| prompt[before.len()..before.len() + files_len].to_string());
+            prompt.push_str(section);
+        }
+        prompt.push_str(&part);
+    }
+
+    Ok(prompt)
+}
+
+/// Build the same prompt as `build_prompt`, as ordered pieces that concatenate to it
+///
+/// The text around the files section and each file block are separate pieces, so callers
+/// can split a prompt between messages without cutting through a file. The separator after
+/// a file block belongs to that block's piece. Empty pieces are left out.
+pub fn build_prompt_pieces<I, P, C>(
+    template_id: &str,
+    custom_instructions: Option<&str>,
+    extra_vars: &HashMap<&str, String>,
+    file_contents: I,
+) -> Result<Vec<String>, String>
+where
+    I: IntoIterator<Item = (P, C)>,
+    P: AsRef<str>,
+    C: AsRef<str>,
+{
+    let head = prompt_head(template_id, custom_instructions)?;
+
+    let mut blocks: Vec<String> = file_contents
+        .into_iter()
+        .map(|(path, content)| {
+            let mut block = String::new();
+            push_file_block(&mut block, path.as_ref(), content.as_ref());
+            block
+        })
+        .collect();
+    let file_count = blocks.len();
+    if blocks.is_empty() {
+        blocks.push("No files provided.".to_string());
+    }
+    let last = blocks.len() - 1;
+    for block in &mut blocks[..last] {
+        block.push_str("\n\n");
+    }
+
+    let files_chars: usize = blocks.iter().map(|block| block.chars().count()).sum();
+    let mut vars = extra_vars.clone();
+    vars.extend([
+        ("file_count", file_count.to_string()),
+        ("total_tokens", files_chars.div_ceil(4).to_string()),
+        ("date", current_date()),
+    ]);
+
+    let mut parts = head
+        .split("{{files}}")
+        .map(|part| render_template(part, &vars));
+    let mut pieces = vec![parts.next().unwrap_or_default()];
+    for part in parts {
+        pieces.extend(blocks.iter().cloned());
+        pieces.push(part);
+    }
+    pieces.retain(|piece| !piece.is_empty());
+    Ok(pieces)
+}
+
+/// Render files as fenced code blocks under a list of their paths, with no template around them
+///
+/// The blocks are the same as in `build_prompt`, and like there each file is consumed as the
+/// iterator yields it.
+pub fn render_files_markdown<I, P, C>(file_contents: I) -> String
+where
+    I: IntoIterator<Item = (P, C)>,
+    P: AsRef<str>,
+    C: AsRef<str>,
+{
+    let mut markdown = String::from("Files:\n");
+    let mut blocks = String::new();
+    for (path, content) in file_contents {
+        markdown.push_str("- ");
+        markdown.push_str(path.as_ref());
+        markdown.push('\n');
+        blocks.push('\n');
+        push_file_block(&mut blocks, path.as_ref(), content.as_ref());
+        blocks.push('\n');
+    }
+    if blocks.is_empty() {
+        return "No files provided.".to_string();
+    }
+
+    markdown.push_str(&blocks);
+    markdown.pop();
+    markdown
+}
+
+/// Whether the template, with `custom_instructions` filled in, contains `{{name}}`
+///
+/// Lets callers skip computing a costly value such as `{{git_diff}}` that no one asked for.
+pub fn uses_placeholder(
     template_id: &str,
     custom_instructions: Option<&str>,
-    file_contents: &[(String, String)], // (path, content) pairs
-) -> Result<String, String> {
+    name: &str,
+) -> Result<bool, String> {
+    let head = prompt_head(template_id, custom_instructions)?;
+    Ok(head.contains(&format!("{{{{{}}}}}", name)))
+}
+
+/// The template with custom instructions filled in and a `{{files}}` placeholder ensured
+fn prompt_head(template_id: &str, custom_instructions: Option<&str>) -> Result<String, String> {
     let templates = get_builtin_templates();
     let template = templates
         .iter()
         .find(|t| t.id == template_id)
         .ok_or_else(|| format!("Template not found: {}", template_id))?;
 
-    let mut prompt = template.template.clone();
-
-    // Replace custom instructions
+    // Replace custom instructions first so they can use the other placeholders too
     let instructions = custom_instructions.unwrap_or("No additional instructions provided.");
-    prompt = prompt.replace("{{custom_instructions}}", instructions);
+    let mut head = render_template(
+        &template.template,
+        &HashMap::from([("custom_instructions", instructions.to_string())]),
+    );
 
     // Ensure {{files}} placeholder exists for templates that don't include it
-    if !prompt.contains("{{files}}") {
-        prompt.push_str("\n\n{{files}}");
+    if !head.contains("{{files}}") {
+        head.push_str("\n\n{{files}}");
     }
+    Ok(head)
+}
 
-    // Build files section with markdown code blocks
-    let files_section = if file_contents.is_empty() {
-        "No files provided.".to_string()
-    } else {
-        file_contents
-            .iter()
-            .map(|(path, content)| {
-                // Detect file extension for syntax highlighting
-                let extension = std::path::Path::new(path)
-                    .extension()
-                    .and_then(|ext| ext.to_str())
-                    .unwrap_or("txt");
-                format!(
-                    "<{path}>\n```{extension}\n{content}\n```",
-                    path = path,
-                    extension = extension,
-                    content = content
-                )
-            })
-            .collect::<Vec<_>>()
-            .join("\n\n")
-    };
+/// Substitute `{{name}}` placeholders whose names are in `vars`, in a single pass.
+///
+/// Every occurrence is replaced. Unknown placeholders are left untouched, and substituted
+/// values are not scanned again.
+pub fn render_template(template: &str, vars: &HashMap<&str, String>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
 
-    prompt = prompt.replace("{{files}}", &files_section);
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = &rest[start + 2..start + 2 + len];
+        let end = start + 2 + len + 2;
 
-    Ok(prompt)
+        output.push_str(&rest[..start]);
+        match vars.get(name) {
+            Some(value) => output.push_str(value),
+            None => output.push_str(&rest[start..end]),
+        }
+        rest = &rest[end..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Names of the `{{name}}` placeholders in `template`, without repeats, in order of first use
+///
+/// Finds placeholders the same way `render_template` does.
+pub fn placeholder_names(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let name = &rest[start + 2..start + 2 + len];
+        if !names.iter().any(|known| known == name) {
+            names.push(name.to_string());
+        }
+        rest = &rest[start + 2 + len + 2..];
+    }
+    names
+}
+
+/// Rough token count for `{{total_tokens}}`, at about four characters per token
+pub(crate) fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Today's date in UTC as YYYY-MM-DD
+fn current_date() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days(days as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Append one file as a fenced code block tagged with its path
+fn push_file_block(prompt: &mut String, path: &str, content: &str) {
+    // Tag the fence with the file's language for syntax highlighting, or its extension if unknown
+    let path_ref = std::path::Path::new(path);
+    let extension = detect_language(path_ref)
+        .or_else(|| path_ref.extension().and_then(|ext| ext.to_str()))
+        .unwrap_or("txt");
+
+    prompt.reserve(path.len() + extension.len() + content.len() + 12);
+    prompt.push('<');
+    prompt.push_str(path);
+    prompt.push_str(">\n```");
+    prompt.push_str(extension);
+    prompt.push('\n');
+    prompt.push_str(content);
+    prompt.push_str("\n```");
 }
 
 #[cfg(test)]
@@ -165,6 +439,21 @@ mod tests {
         assert!(templates.iter().any(|t| t.id == "custom"));
     }
 
+    #[test]
+    fn test_template_placeholders() {
+        let templates = get_builtin_templates();
+        let planning = templates.iter().find(|t| t.id == "planning").unwrap();
+        assert_eq!(planning.placeholders, vec!["custom_instructions"]);
+        let diff = templates.iter().find(|t| t.id == "diff").unwrap();
+        assert_eq!(diff.placeholders, vec!["git_diff", "custom_instructions"]);
+
+        assert_eq!(
+            placeholder_names("{{files}} {{bogus}} {{files}} {{custom_instructions}} {{open"),
+            vec!["files", "bogus", "custom_instructions"]
+        );
+        assert!(placeholder_names("plain text").is_empty());
+    }
+
     #[test]
     fn test_build_prompt() {
         let file_contents = vec![
@@ -172,20 +461,251 @@ mod tests {
             ("lib.rs".to_string(), "pub fn foo() {}".to_string()),
         ];
 
-        let prompt = build_prompt("agent", Some("Fix bugs"), &file_contents).unwrap();
+        let prompt = build_prompt("agent", Some("Fix bugs"), file_contents).unwrap();
 
         assert!(prompt.contains("Fix bugs"));
         assert!(prompt.contains("<main.rs>"));
-        assert!(prompt.contains("```rs"));
+        assert!(prompt.contains("```rust"));
         assert!(prompt.contains("fn main()"));
         assert!(prompt.contains("<lib.rs>"));
         assert!(prompt.contains("pub fn foo()"));
         assert!(!prompt.contains("{{files}}"));
     }
 
+    /// The previous eager implementation, kept to check the streamed output against
+    fn build_prompt_eager(
+        template_id: &str,
+        custom_instructions: Option<&str>,
+        file_contents: &[(String, String)], // (path, content) pairs
+    ) -> Result<String, String> {
+        let templates = get_builtin_templates();
+        let template = templates
+            .iter()
+            .find(|t| t.id == template_id)
+            .ok_or_else(|| format!("Template not found: {}", template_id))?;
+
+        let mut prompt = template.template.clone();
+
+        // Replace custom instructions
+        let instructions = custom_instructions.unwrap_or("No additional instructions provided.");
+        prompt = prompt.replace("{{custom_instructions}}", instructions);
+
+        // Ensure {{files}} placeholder exists for templates that don't include it
+        if !prompt.contains("{{files}}") {
+            prompt.push_str("\n\n{{files}}");
+        }
+
+        // Build files section with markdown code blocks
+        let files_section = if file_contents.is_empty() {
+            "No files provided.".to_string()
+        } else {
+            file_contents
+                .iter()
+                .map(|(path, content)| {
+                    // Detect file extension for syntax highlighting
+                    let path_ref = std::path::Path::new(path);
+                    let extension = detect_language(path_ref)
+                        .or_else(|| path_ref.extension().and_then(|ext| ext.to_str()))
+                        .unwrap_or("txt");
+                    format!(
+                        "<{path}>\n```{extension}\n{content}\n```",
+                        path = path,
+                        extension = extension,
+                        content = content
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        };
+
+        prompt = prompt.replace("{{files}}", &files_section);
+
+        Ok(prompt)
+    }
+
+    #[test]
+    fn test_build_prompt_matches_eager() {
+        let file_contents: Vec<(String, String)> = (0..300)
+            .map(|i| {
+                (
+                    format!("src/module_{}.rs", i),
+                    format!("// file {}\n{}", i, "let x = 1;\n".repeat(i % 7)),
+                )
+            })
+            .collect();
+
+        for template in get_builtin_templates() {
+            assert_eq!(
+                build_prompt(&template.id, Some("Explain"), file_contents.clone()).unwrap(),
+                build_prompt_eager(&template.id, Some("Explain"), &file_contents).unwrap(),
+                "template {}",
+                template.id
+            );
+        }
+
+        // Placeholder repeated through the instructions, and no files at all
+        let instructions = "Before:\n{{files}}\nAgain:\n{{files}}";
+        assert_eq!(
+            build_prompt("custom", Some(instructions), file_contents.clone()).unwrap(),
+            build_prompt_eager("custom", Some(instructions), &file_contents).unwrap()
+        );
+        assert_eq!(
+            build_prompt("custom", Some(instructions), Vec::<(String, String)>::new()).unwrap(),
+            build_prompt_eager("custom", Some(instructions), &[]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_build_prompt_reads_lazily() {
+        let mut yielded = 0;
+        let files = (0..3).map(|i| {
+            yielded += 1;
+            (format!("f{}.txt", i), "content".to_string())
+        });
+
+        let prompt = build_prompt("custom", Some("{{files}}"), files).unwrap();
+        assert_eq!(yielded, 3);
+        assert!(prompt.starts_with("<f0.txt>\n```txt\ncontent\n```\n\n<f1.txt>"));
+    }
+
+    #[test]
+    fn test_render_template_repeated_and_missing() {
+        let vars = HashMap::from([("name", "x".to_string())]);
+
+        assert_eq!(render_template("{{name}} and {{name}}", &vars), "x and x");
+        assert_eq!(render_template("no placeholders", &vars), "no placeholders");
+        assert_eq!(render_template("{{other}} {{name}}", &vars), "{{other}} x");
+        assert_eq!(render_template("unclosed {{name", &vars), "unclosed {{name");
+        // Values are not rendered again
+        let vars = HashMap::from([("a", "{{b}}".to_string()), ("b", "B".to_string())]);
+        assert_eq!(render_template("{{a}}", &vars), "{{b}}");
+    }
+
+    #[test]
+    fn test_build_prompt_file_count() {
+        let files = vec![
+            ("a.rs".to_string(), "a".to_string()),
+            ("b.rs".to_string(), "b".to_string()),
+        ];
+
+        let prompt =
+            build_prompt("custom", Some("{{file_count}} files: {{files}}"), files).unwrap();
+        assert!(prompt.starts_with("2 files: <a.rs>"));
+
+        let prompt = build_prompt(
+            "custom",
+            Some("{{file_count}}"),
+            Vec::<(String, String)>::new(),
+        )
+        .unwrap();
+        assert!(prompt.starts_with("0\n\nNo files provided."));
+    }
+
+    #[test]
+    fn test_build_prompt_total_tokens() {
+        // The files section "<a.txt>\n```txt\n12345678\n```" is 28 characters
+        let files = vec![("a.txt".to_string(), "12345678".to_string())];
+
+        let prompt = build_prompt("custom", Some("~{{total_tokens}} tokens"), files).unwrap();
+        assert!(prompt.starts_with("~7 tokens\n\n"));
+    }
+
+    #[test]
+    fn test_build_prompt_date() {
+        let prompt = build_prompt(
+            "custom",
+            Some("Date: {{date}}"),
+            Vec::<(String, String)>::new(),
+        )
+        .unwrap();
+        assert!(prompt.starts_with(&format!("Date: {}", current_date())));
+        assert!(!prompt.contains("{{date}}"));
+    }
+
+    #[test]
+    fn test_build_prompt_variable_used_twice() {
+        let files = vec![("a.rs".to_string(), "a".to_string())];
+
+        let prompt = build_prompt("custom", Some("{{file_count}}/{{file_count}}"), files).unwrap();
+        assert!(prompt.starts_with("1/1\n\n<a.rs>"));
+    }
+
+    #[test]
+    fn test_build_prompt_does_not_render_file_contents() {
+        let files = vec![(
+            "a.md".to_string(),
+            "literal {{date}} and {{files}}".to_string(),
+        )];
+
+        let prompt = build_prompt("custom", Some("{{files}}"), files).unwrap();
+        assert!(prompt.contains("literal {{date}} and {{files}}"));
+    }
+
+    #[test]
+    fn test_build_prompt_pieces_concatenate_to_prompt() {
+        let files = vec![
+            ("main.rs", "fn main() {}"),
+            ("lib.rs", "pub fn foo() {}"),
+            ("notes.md", "# Notes"),
+        ];
+
+        for template in ["agent", "planning", "custom"] {
+            let prompt =
+                build_prompt(template, Some("Fix {{file_count}} files"), files.clone()).unwrap();
+            let pieces = build_prompt_pieces(
+                template,
+                Some("Fix {{file_count}} files"),
+                &HashMap::new(),
+                files.clone(),
+            )
+            .unwrap();
+            assert_eq!(pieces.concat(), prompt, "{}", template);
+            assert!(pieces.iter().all(|piece| !piece.is_empty()));
+        }
+
+        let pieces = build_prompt_pieces("agent", None, &HashMap::new(), files.clone()).unwrap();
+        assert!(pieces[0].starts_with("You are an expert"));
+        assert!(pieces[1].starts_with("<main.rs>") && pieces[1].ends_with("```\n\n"));
+        assert!(pieces[3].starts_with("<notes.md>") && pieces[3].ends_with("```"));
+
+        let empty: Vec<(&str, &str)> = Vec::new();
+        assert_eq!(
+            build_prompt_pieces("agent", None, &HashMap::new(), empty.clone())
+                .unwrap()
+                .concat(),
+            build_prompt("agent", None, empty).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_build_prompt_with_git_diff() {
+        let files = vec![("a.rs", "fn a() {}")];
+        let diff = "M src/a.rs\n-{{files}}\n+{{file_count}}".to_string();
+        let vars = HashMap::from([("git_diff", diff.clone())]);
+
+        assert!(uses_placeholder("diff", None, "git_diff").unwrap());
+        assert!(!uses_placeholder("agent", None, "git_diff").unwrap());
+        assert!(uses_placeholder("agent", Some("See {{git_diff}}"), "git_diff").unwrap());
+
+        // The diff is inserted as is, even where it looks like a placeholder
+        let prompt = build_prompt_with_vars("diff", None, &vars, files.clone()).unwrap();
+        assert!(prompt.contains(&diff));
+        assert_eq!(prompt.matches("<a.rs>").count(), 1);
+        assert_eq!(
+            build_prompt_pieces("diff", None, &vars, files.clone())
+                .unwrap()
+                .concat(),
+            prompt
+        );
+
+        // Without a value the placeholder is left for the caller to notice
+        let prompt = build_prompt("diff", None, files).unwrap();
+        assert!(prompt.contains("{{git_diff}}"));
+    }
+
     #[test]
     fn test_build_prompt_invalid_template() {
-        let result = build_prompt("invalid", None, &[]);
+        let result = build_prompt("invalid", None, Vec::<(String, String)>::new());
         assert!(result.is_err());
     }
 }
@@ -9,6 +9,7 @@ import { useToast } from "./components/ui/toast";
 import { useConfirmDialog } from "./components/ui/alert-dialog";
 import { useAppSettings, useAppCustomInstructions } from "./contexts/AppContext";
 import { useSessionPersistence } from "./hooks/useSessionPersistence";
+import type { IndexSummary } from "./types";
 import "./App.css";
 
 // Layout & Views
@@ -95,9 +96,13 @@ function App() {
         if (paths && paths.length > 0) {
           for (const path of paths) {
             try {
-              await invoke("index_folder", { path });
+              const summary = await invoke<IndexSummary>("index_folder", { path });
               await emit("refresh-file-tree");
               success(`Indexing folder: ${path.split(/[\\/]/).pop()}`);
+              if (summary.error_count > 0) {
+                console.warn(`Entries skipped while indexing ${path}:`, summary.errors);
+                showError(`${summary.error_count} entries could not be indexed`);
+              }
             } catch (err) {
               console.error(`Failed to index dropped path ${path}:`, err);
               showError(`Failed to index: ${path.split(/[\\/]/).pop()}`);
@@ -152,9 +157,13 @@ function App() {
       });
 
       if (selected) {
-        await invoke("index_folder", { path: selected as string });
+        const summary = await invoke<IndexSummary>("index_folder", { path: selected as string });
         await emit("refresh-file-tree");
         success("Folder added to index");
+        if (summary.error_count > 0) {
+          console.warn("Entries skipped while indexing:", summary.errors);
+          showError(`${summary.error_count} entries could not be indexed`);
+        }
       }
     } catch (err) {
       console.error("Failed to open folder dialog:", err);
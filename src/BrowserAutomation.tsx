@@ -2,6 +2,37 @@ import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { Button } from "./components/ui/button";
 import { Input } from "./components/ui/input";
+import { formatCommandError } from "./lib/commandError";
+
+interface InterfaceDef {
+  id: string;
+  name: string;
+  default_url: string;
+  builtin: boolean;
+}
+
+const FALLBACK_INTERFACES: InterfaceDef[] = [
+  { id: "chatgpt", name: "ChatGPT", default_url: "https://chat.openai.com/", builtin: true },
+  { id: "claude", name: "Claude", default_url: "https://claude.ai/", builtin: true },
+  { id: "gemini", name: "Gemini", default_url: "https://gemini.google.com/", builtin: true },
+  { id: "aistudio", name: "AI Studio", default_url: "https://aistudio.google.com/", builtin: true },
+];
+
+interface BrowserLaunch {
+  pid: number;
+  interface: string;
+  started_at: number;
+}
+
+interface BrowserPrerequisites {
+  node_available: boolean;
+  node_version: string | null;
+  node_version_supported: boolean;
+  min_node_version: string;
+  sidecar_found: boolean;
+  sidecar_path: string | null;
+  errors: string[];
+}
 
 /**
  * BrowserAutomation component - Test interface for Phase 6 browser automation
@@ -18,17 +49,28 @@ function BrowserAutomation() {
   const [status, setStatus] = useState<string>("");
   const [error, setError] = useState<string>("");
   const [loading, setLoading] = useState<boolean>(false);
-  const [availableInterfaces, setAvailableInterfaces] = useState<string[]>([]);
+  const [availableInterfaces, setAvailableInterfaces] = useState<InterfaceDef[]>([]);
 
   // Load available interfaces on mount
   useEffect(() => {
-    invoke<string[]>("get_available_interfaces")
+    invoke<InterfaceDef[]>("get_available_interfaces")
       .then((interfaces) => {
         setAvailableInterfaces(interfaces);
       })
       .catch((err) => {
         console.error("Failed to load interfaces:", err);
-        setAvailableInterfaces(["chatgpt", "claude", "gemini", "aistudio"]);
+        setAvailableInterfaces(FALLBACK_INTERFACES);
+      });
+
+    // Check Node.js and sidecar availability before the first launch
+    invoke<BrowserPrerequisites>("check_browser_prerequisites")
+      .then((prerequisites) => {
+        if (prerequisites.errors.length > 0) {
+          setError(prerequisites.errors.join("\n"));
+        }
+      })
+      .catch((err) => {
+        console.error("Failed to check browser prerequisites:", err);
       });
   }, []);
 
@@ -43,16 +85,16 @@ function BrowserAutomation() {
     setError("");
 
     try {
-      await invoke("launch_browser", {
+      const launch = await invoke<BrowserLaunch>("launch_browser", {
         interface: interface_,
         text: prompt,
         customUrl: customUrl || null,
       });
 
-      setStatus("✓ Browser launched successfully! Check your browser window.");
+      setStatus(`✓ Browser launched successfully (PID ${launch.pid})! Check your browser window.`);
       setLoading(false);
     } catch (err) {
-      setError(`Failed to launch browser: ${err}`);
+      setError(`Failed to launch browser: ${formatCommandError(err)}`);
       setStatus("");
       setLoading(false);
     }
@@ -74,20 +116,11 @@ function BrowserAutomation() {
           onChange={(e) => setInterface(e.target.value)}
           className="w-full p-2.5 text-sm rounded-md border border-input bg-background"
         >
-          {availableInterfaces.length > 0 ? (
-            availableInterfaces.map((iface) => (
-              <option key={iface} value={iface}>
-                {iface.charAt(0).toUpperCase() + iface.slice(1)}
-              </option>
-            ))
-          ) : (
-            <>
-              <option value="chatgpt">ChatGPT</option>
-              <option value="claude">Claude</option>
-              <option value="gemini">Gemini</option>
-              <option value="aistudio">AI Studio</option>
-            </>
-          )}
+          {(availableInterfaces.length > 0 ? availableInterfaces : FALLBACK_INTERFACES).map((iface) => (
+            <option key={iface.id} value={iface.id}>
+              {iface.name}
+            </option>
+          ))}
         </select>
       </div>
 
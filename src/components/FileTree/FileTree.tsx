@@ -107,9 +107,13 @@ const FileTreeInner = memo(function FileTreeInner({
   const highlightQuery = (() => {
     const trimmed = (searchQuery || "").trim();
     if (!trimmed) return "";
-    // Extract the meaningful search term (strip file:/dir: prefixes)
+    // Extract the meaningful search term (strip file:/dir:/size:/modified: prefixes)
     const parts = trimmed.split(/\s+/).filter(
-      (p) => !p.toLowerCase().startsWith("file:") && !p.toLowerCase().startsWith("dir:")
+      (p) =>
+        !p.toLowerCase().startsWith("file:") &&
+        !p.toLowerCase().startsWith("dir:") &&
+        !p.toLowerCase().startsWith("size:") &&
+        !p.toLowerCase().startsWith("modified:")
     );
     if (parts.length > 0) return parts.join(" ");
     // If only prefixed filters, use the value after the colon
@@ -13,6 +13,8 @@ type TooltipState = 'visible' | 'hidden' | 'ready';
 const TOOLTIP_CONTENT = `Advanced search:
 \u2022 file:name - fuzzy match filename
 \u2022 dir:name - filter by directory
+\u2022 size:>1mb, size:<100kb, size:10kb..1mb - filter files by size
+\u2022 modified:<7d, modified:>2024-01-01 - filter by modification date
 \u2022 /pattern$/ - auto-detected regex
 \u2022 Combine: file:App dir:src (AND logic)`;
 
@@ -5,6 +5,7 @@ import {
 } from "../services/prompts";
 import { assemblePrompt } from "../services/assembly";
 import { cn } from "@/lib/utils";
+import { formatCommandError } from "@/lib/commandError";
 import { useAppCustomInstructions } from "../contexts/AppContext";
 import { PlusCircle, Bot, PencilRuler, Bug, BookOpen, Shield, FileText } from "lucide-react";
 
@@ -41,7 +42,7 @@ export const PromptBuilder = forwardRef<PromptBuilderHandle, PromptBuilderProps>
       const templateList = await getTemplates();
       setTemplates(templateList.filter(t => t.id !== 'custom'));
     } catch (err) {
-      setError(`Failed to load templates: ${err}`);
+      setError(`Failed to load templates: ${formatCommandError(err)}`);
     }
   };
 
@@ -91,7 +92,7 @@ export const PromptBuilder = forwardRef<PromptBuilderHandle, PromptBuilderProps>
       }
     } catch (err) {
       console.log('PromptBuilder error caught:', err);
-      setError(`Failed to build prompt: ${err}`);
+      setError(`Failed to build prompt: ${formatCommandError(err)}`);
       throw err;
     }
   };
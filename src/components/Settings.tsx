@@ -25,6 +25,26 @@ interface AppSettings {
   auto_save_history: boolean;
   cache_size_mb: number;
   respect_gitignore: boolean;
+  use_global_gitignore: boolean;
+  respect_dockerignore: boolean;
+  follow_symlinks: boolean;
+  include_hidden: boolean;
+  max_index_depth: number;
+  index_batch_size: number;
+  browser_profile_dir: string;
+  text_extensions: string[];
+  text_file_names: string[];
+  blocked_text_extensions: string[];
+}
+
+interface SettingWarning {
+  key: string;
+  value: string;
+  message: string;
+}
+
+interface LoadedSettings extends AppSettings {
+  warnings?: SettingWarning[];
 }
 
 interface SettingsProps {
@@ -39,6 +59,16 @@ const Settings: React.FC<SettingsProps> = ({ onSettingsChange }) => {
     auto_save_history: true,
     cache_size_mb: 100,
     respect_gitignore: true,
+    use_global_gitignore: false,
+    respect_dockerignore: false,
+    follow_symlinks: false,
+    include_hidden: true,
+    max_index_depth: 0,
+    index_batch_size: 1000,
+    browser_profile_dir: '',
+    text_extensions: [],
+    text_file_names: [],
+    blocked_text_extensions: [],
   });
 
   const [loading, setLoading] = useState(true);
@@ -52,13 +82,17 @@ const Settings: React.FC<SettingsProps> = ({ onSettingsChange }) => {
   const loadSettings = useCallback(async () => {
     setLoading(true);
     try {
-      const loaded = await invoke<AppSettings>('load_settings');
+      const loaded = await invoke<LoadedSettings>('load_settings');
       if (loaded && typeof loaded === 'object') {
+        const { warnings = [], ...loadedSettings } = loaded;
         setSettings(prev => ({
           ...prev,
-          ...loaded,
-          excluded_extensions: loaded.excluded_extensions || [],
+          ...loadedSettings,
+          excluded_extensions: loadedSettings.excluded_extensions || [],
         }));
+        if (warnings.length > 0) {
+          warning(`Invalid values reset to defaults: ${warnings.map(w => w.key).join(', ')}`);
+        }
       }
     } catch (error) {
       console.error('Failed to load settings:', error);
@@ -66,7 +100,7 @@ const Settings: React.FC<SettingsProps> = ({ onSettingsChange }) => {
     } finally {
       setLoading(false);
     }
-  }, [showError]);
+  }, [showError, warning]);
 
   useEffect(() => {
     loadSettings();
@@ -319,6 +353,125 @@ const Settings: React.FC<SettingsProps> = ({ onSettingsChange }) => {
                 </label>
               </div>
 
+              <div className="p-4 bg-white/5 border border-white/10 rounded-md">
+                <label className="flex items-start gap-3 cursor-pointer group">
+                  <div className="pt-0.5 relative">
+                    <input
+                      type="checkbox"
+                      checked={settings.use_global_gitignore}
+                      onChange={(e) => setSettings(prev => ({ ...prev, use_global_gitignore: e.target.checked }))}
+                      disabled={!settings.respect_gitignore}
+                      className="sr-only peer"
+                    />
+                    <div className="size-4 border border-white/20 rounded bg-black/40 peer-checked:bg-primary peer-checked:border-primary transition-all flex items-center justify-center peer-focus:ring-1 peer-focus:ring-primary/50">
+                      <Check size={12} className="text-white scale-0 peer-checked:scale-100 transition-transform" aria-hidden="true" />
+                    </div>
+                  </div>
+                  <div className="space-y-0.5">
+                    <div className="text-[11px] font-bold text-white group-hover:text-primary transition-colors">Use Global Git Excludes</div>
+                    <div className="text-[9px] text-white/30 leading-relaxed">Also apply the global excludesfile from your git config. .git/info/exclude is always applied.</div>
+                  </div>
+                </label>
+              </div>
+
+              <div className="p-4 bg-white/5 border border-white/10 rounded-md">
+                <label className="flex items-start gap-3 cursor-pointer group">
+                  <div className="pt-0.5 relative">
+                    <input
+                      type="checkbox"
+                      checked={settings.respect_dockerignore}
+                      onChange={(e) => setSettings(prev => ({ ...prev, respect_dockerignore: e.target.checked }))}
+                      disabled={!settings.respect_gitignore}
+                      className="sr-only peer"
+                    />
+                    <div className="size-4 border border-white/20 rounded bg-black/40 peer-checked:bg-primary peer-checked:border-primary transition-all flex items-center justify-center peer-focus:ring-1 peer-focus:ring-primary/50">
+                      <Check size={12} className="text-white scale-0 peer-checked:scale-100 transition-transform" aria-hidden="true" />
+                    </div>
+                  </div>
+                  <div className="space-y-0.5">
+                    <div className="text-[11px] font-bold text-white group-hover:text-primary transition-colors">Respect .dockerignore</div>
+                    <div className="text-[9px] text-white/30 leading-relaxed">Also exclude paths listed in the .dockerignore at the root of the indexed folder.</div>
+                  </div>
+                </label>
+              </div>
+
+              <div className="p-4 bg-white/5 border border-white/10 rounded-md">
+                <label className="flex items-start gap-3 cursor-pointer group">
+                  <div className="pt-0.5 relative">
+                    <input
+                      type="checkbox"
+                      checked={settings.follow_symlinks}
+                      onChange={(e) => setSettings(prev => ({ ...prev, follow_symlinks: e.target.checked }))}
+                      className="sr-only peer"
+                    />
+                    <div className="size-4 border border-white/20 rounded bg-black/40 peer-checked:bg-primary peer-checked:border-primary transition-all flex items-center justify-center peer-focus:ring-1 peer-focus:ring-primary/50">
+                      <Check size={12} className="text-white scale-0 peer-checked:scale-100 transition-transform" aria-hidden="true" />
+                    </div>
+                  </div>
+                  <div className="space-y-0.5">
+                    <div className="text-[11px] font-bold text-white group-hover:text-primary transition-colors">Follow Symlinks</div>
+                    <div className="text-[9px] text-white/30 leading-relaxed">Index the contents of symlinked directories. Directories reached more than once are indexed only the first time.</div>
+                  </div>
+                </label>
+              </div>
+
+              <div className="p-4 bg-white/5 border border-white/10 rounded-md">
+                <label className="flex items-start gap-3 cursor-pointer group">
+                  <div className="pt-0.5 relative">
+                    <input
+                      type="checkbox"
+                      checked={settings.include_hidden}
+                      onChange={(e) => setSettings(prev => ({ ...prev, include_hidden: e.target.checked }))}
+                      className="sr-only peer"
+                    />
+                    <div className="size-4 border border-white/20 rounded bg-black/40 peer-checked:bg-primary peer-checked:border-primary transition-all flex items-center justify-center peer-focus:ring-1 peer-focus:ring-primary/50">
+                      <Check size={12} className="text-white scale-0 peer-checked:scale-100 transition-transform" aria-hidden="true" />
+                    </div>
+                  </div>
+                  <div className="space-y-0.5">
+                    <div className="text-[11px] font-bold text-white group-hover:text-primary transition-colors">Include Hidden Files</div>
+                    <div className="text-[9px] text-white/30 leading-relaxed">Index files and folders whose name starts with a dot, such as .env or .github. .gitignore rules apply either way.</div>
+                  </div>
+                </label>
+              </div>
+
+              <div className="p-4 bg-white/5 border border-white/10 rounded-md">
+                <div className="flex items-center justify-between">
+                  <div className="space-y-0.5">
+                    <label htmlFor="max-index-depth" className="text-[11px] font-bold text-white">Max Index Depth</label>
+                    <div className="text-[9px] text-white/30">Folders deeper than this are listed but not indexed (0 = unlimited)</div>
+                  </div>
+                  <input
+                    id="max-index-depth"
+                    type="number"
+                    value={settings.max_index_depth}
+                    onChange={(e) => setSettings(prev => ({ ...prev, max_index_depth: Math.max(0, parseInt(e.target.value) || 0) }))}
+                    className="w-20 h-7 px-2 bg-black/40 border border-white/10 rounded text-[11px] text-white text-right focus:outline-none focus:border-primary/50 focus:ring-1 focus:ring-primary/30"
+                    min="0"
+                    step="1"
+                  />
+                </div>
+              </div>
+
+              <div className="p-4 bg-white/5 border border-white/10 rounded-md">
+                <div className="flex items-center justify-between">
+                  <div className="space-y-0.5">
+                    <label htmlFor="index-batch-size" className="text-[11px] font-bold text-white">Index Batch Size</label>
+                    <div className="text-[9px] text-white/30">Entries written per batch; larger is faster on slow disks (100 - 50000)</div>
+                  </div>
+                  <input
+                    id="index-batch-size"
+                    type="number"
+                    value={settings.index_batch_size}
+                    onChange={(e) => setSettings(prev => ({ ...prev, index_batch_size: Math.min(50000, Math.max(100, parseInt(e.target.value) || 1000)) }))}
+                    className="w-20 h-7 px-2 bg-black/40 border border-white/10 rounded text-[11px] text-white text-right focus:outline-none focus:border-primary/50 focus:ring-1 focus:ring-primary/30"
+                    min="100"
+                    max="50000"
+                    step="100"
+                  />
+                </div>
+              </div>
+
               <div className="space-y-3">
                 <h3 className="block text-[10px] font-bold text-white/50 uppercase tracking-wider text-green-400/80">
                   Cache Management:
@@ -342,6 +495,21 @@ const Settings: React.FC<SettingsProps> = ({ onSettingsChange }) => {
                   </div>
                 </div>
               </div>
+
+              <div className="p-4 bg-white/5 border border-white/10 rounded-md space-y-2">
+                <div className="space-y-0.5">
+                  <label htmlFor="browser-profile-dir" className="text-[11px] font-bold text-white">Browser Profile Directory</label>
+                  <div className="text-[9px] text-white/30">Each AI interface keeps its own profile here. Leave empty to use the app data directory.</div>
+                </div>
+                <input
+                  id="browser-profile-dir"
+                  type="text"
+                  value={settings.browser_profile_dir}
+                  onChange={(e) => setSettings(prev => ({ ...prev, browser_profile_dir: e.target.value }))}
+                  placeholder="Default (app data directory)"
+                  className="w-full h-7 px-2 bg-black/40 border border-white/10 rounded text-[11px] text-white focus:outline-none focus:border-primary/50 focus:ring-1 focus:ring-primary/30"
+                />
+              </div>
             </section>
 
             {/* Prompt section */}
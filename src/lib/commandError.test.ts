@@ -0,0 +1,17 @@
+import { describe, it, expect } from 'vitest';
+import { formatCommandError, isCommandError } from './commandError';
+
+describe('commandError', () => {
+  it('should recognize structured command errors', () => {
+    expect(isCommandError({ code: 'NOT_FOUND', message: 'File not found in index' })).toBe(true);
+    expect(isCommandError('Failed to lock database')).toBe(false);
+    expect(isCommandError(null)).toBe(false);
+    expect(isCommandError({ code: 'IO' })).toBe(false);
+  });
+
+  it('should format structured, Error and string rejections', () => {
+    expect(formatCommandError({ code: 'IO', message: 'IO error: denied' })).toBe('IO error: denied');
+    expect(formatCommandError(new Error('boom'))).toBe('boom');
+    expect(formatCommandError('plain string')).toBe('plain string');
+  });
+});
@@ -0,0 +1,54 @@
+/**
+ * Structured errors returned by backend commands
+ *
+ * Commands that return `CommandError` reject with `{ code, message }` instead of a plain string.
+ * `code` is stable and safe to branch on; `message` is for display only.
+ */
+
+/**
+ * Error codes emitted by the backend
+ */
+export type CommandErrorCode =
+  | 'DB'
+  | 'IO'
+  | 'SERIALIZATION'
+  | 'PATH'
+  | 'INVALID_ARGUMENT'
+  | 'BROWSER'
+  | 'NOT_FOUND'
+  | 'LOCK'
+  | 'BUSY'
+  | 'UNKNOWN';
+
+/**
+ * Error payload rejected by structured commands
+ */
+export interface CommandError {
+  code: CommandErrorCode;
+  message: string;
+}
+
+/**
+ * Check whether a rejected value is a structured command error
+ */
+export function isCommandError(err: unknown): err is CommandError {
+  return (
+    typeof err === 'object' &&
+    err !== null &&
+    typeof (err as CommandError).code === 'string' &&
+    typeof (err as CommandError).message === 'string'
+  );
+}
+
+/**
+ * Get a human-readable message from any rejected invoke value
+ */
+export function formatCommandError(err: unknown): string {
+  if (isCommandError(err)) {
+    return err.message;
+  }
+  if (err instanceof Error) {
+    return err.message;
+  }
+  return String(err);
+}
@@ -0,0 +1,8 @@
+import { invoke } from "@tauri-apps/api/core";
+
+/**
+ * Get the last `lines` application log lines, oldest first
+ */
+export async function getRecentLogs(lines: number): Promise<string[]> {
+  return await invoke<string[]>("get_recent_logs", { lines });
+}
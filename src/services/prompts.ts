@@ -8,20 +8,62 @@ export interface PromptTemplate {
 }
 
 export interface BuildPromptRequest {
-  template_id: string;
+  template_id?: string;
   custom_instructions?: string;
   file_paths: string[];
+  per_file_char_limit?: number;
+  binary_placeholders?: boolean;
 }
 
 export interface BuildPromptResponse {
   prompt: string;
   file_count: number;
+  total_chars: number;  // Characters; the size to show users
+  total_bytes: number;  // UTF-8 bytes
+  truncated_files: number;
+  omitted_files: string[];
+}
+
+export interface PromptFileResponse {
+  path: string;
+  bytes_written: number;
+  file_count: number;
   total_chars: number;
+  total_bytes: number;
+  truncated_files: number;
+  omitted_files: string[];
+}
+
+export interface PromptChunk {
+  index: number;
+  total: number;
+  content: string;
+  token_estimate: number;
 }
 
 export interface FileContent {
   path: string;
   content: string;
+  size?: number | null;
+  mtime?: number | null;
+  token_count?: number | null;
+  language?: string | null;
+  encoding?: string | null;  // Encoding the content was decoded from
+}
+
+export type FileFailureReason =
+  | { kind: "not_indexed" }
+  | { kind: "is_directory" }
+  | { kind: "io"; message: string };
+
+export interface FileFailure {
+  path: string;
+  reason: FileFailureReason;
+}
+
+export interface FileContentsResult {
+  contents: FileContent[];
+  failures: FileFailure[];
 }
 
 /**
@@ -42,6 +84,70 @@ export async function buildPromptFromFiles(
   });
 }
 
+export interface BrowserLaunch {
+  pid: number;
+  interface: string;
+  started_at: number;
+}
+
+export interface BuildAndLaunchResponse {
+  prompt: BuildPromptResponse;
+  launch: BrowserLaunch;
+}
+
+/**
+ * Build a prompt and open it in an AI interface in one call
+ */
+export async function buildAndLaunch(
+  request: BuildPromptRequest,
+  interfaceId: string,
+  customUrl?: string
+): Promise<BuildAndLaunchResponse> {
+  return await invoke<BuildAndLaunchResponse>("build_and_launch", {
+    request,
+    interface: interfaceId,
+    customUrl,
+  });
+}
+
+/**
+ * Build a prompt and write it to a file instead of returning it
+ */
+export async function buildPromptToFile(
+  request: BuildPromptRequest,
+  destPath: string,
+  overwrite = false
+): Promise<PromptFileResponse> {
+  return await invoke<PromptFileResponse>("build_prompt_to_file", {
+    request,
+    destPath,
+    overwrite,
+  });
+}
+
+/**
+ * Build a prompt split into ordered chunks of at most `maxTokensPerChunk` tokens each
+ */
+export async function buildPromptChunks(
+  request: BuildPromptRequest,
+  maxTokensPerChunk: number
+): Promise<PromptChunk[]> {
+  return await invoke<PromptChunk[]>("build_prompt_chunks", {
+    request,
+    maxTokensPerChunk,
+  });
+}
+
+/**
+ * Estimate tokens for arbitrary text using the backend heuristic
+ */
+export async function estimateTokens(
+  text: string,
+  model?: string
+): Promise<number> {
+  return await invoke<number>("estimate_tokens", { text, model });
+}
+
 /**
  * Get content of a single file by path
  */
@@ -57,3 +163,14 @@ export async function getFileContents(
 ): Promise<FileContent[]> {
   return await invoke<FileContent[]>("get_file_contents", { filePaths });
 }
+
+/**
+ * Get content of multiple files, with the reason each unreadable file was skipped
+ */
+export async function getFileContentsDetailed(
+  filePaths: string[]
+): Promise<FileContentsResult> {
+  return await invoke<FileContentsResult>("get_file_contents_detailed", {
+    filePaths,
+  });
+}
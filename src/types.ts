@@ -8,6 +8,20 @@ export interface FileEntry {
   token_count: number | null;
   fingerprint: string | null;
   child_count: number | null;
+  symlink_target?: string | null;
+  truncated_at_depth?: boolean;
+  language?: string | null;
+  lossy_path?: boolean;
+}
+
+export type SortKey = 'name' | 'size' | 'mtime' | 'token_count';
+
+export type TreeAnnotation = 'none' | 'size' | 'tokens';  // Detail shown after files in render_file_tree
+
+export interface ChildrenPage {
+  entries: FileEntry[];
+  total: number;  // Total children of the node, across all pages
+  offset: number;
 }
 
 export interface TreeNode extends FileEntry {
@@ -23,6 +37,14 @@ export interface TreeNode extends FileEntry {
 
 export interface SearchResult extends FileEntry {
   score: number;
+  match_ranges?: [number, number][];  // Byte ranges [start, end) into path
+}
+
+export interface SearchGroup {
+  dir: string | null;
+  match_count: number;
+  best_score: number;
+  results: SearchResult[];
 }
 
 export interface IndexProgress {
@@ -31,3 +53,67 @@ export interface IndexProgress {
   current_path: string;
   errors: number;
 }
+
+export interface IndexError {
+  path: string;  // Empty when the failure has no path
+  message: string;
+}
+
+export interface IndexSummary {
+  inserted: number;
+  updated: number;
+  ignored: number;
+  gitignore_files: number;  // .gitignore files found in the tree and applied
+  error_count: number;  // Total failures; may exceed errors.length
+  errors: IndexError[];
+}
+
+export interface IndexStats {
+  total_entries: number;
+  file_count: number;
+  dir_count: number;
+  total_bytes: number;
+  total_tokens: number;
+  root_count: number;
+}
+
+export interface IndexRoot {
+  path: string;
+  name: string;
+  is_dir: boolean;
+  child_count: number;  // Direct children only
+  file_count: number;  // Files at any depth
+  total_bytes: number;
+}
+
+export interface IgnorePreview {
+  indexed: string[];
+  ignored: string[];
+  indexed_count: number;
+  ignored_count: number;
+}
+
+export interface FileSuggestion {
+  paths: string[];
+  total_tokens: number;
+}
+
+export interface PathTokens {
+  path: string;
+  tokens: number;
+  file_count: number;  // 0 when the path isn't indexed
+  uncounted_files: number;  // Files without a token count yet
+}
+
+export interface SelectionTokens {
+  total_tokens: number;  // Each distinct file counted once
+  file_count: number;
+  uncounted_files: number;
+  paths: PathTokens[];  // Per selected path; totals may overlap
+}
+
+export interface IgnoreExplanation {
+  gitignore: string | null;
+  pattern: string;
+  matched_path: string;
+}